@@ -10,17 +10,27 @@ fn main() {
 
     println!("cargo:rerun-if-changed=../runtime/src/index.ts");
     println!("cargo:rerun-if-changed=../init");
+    println!("cargo:rerun-if-changed=src/run/icudtl.dat");
     let path = Path::new("../runtime/src/index.ts")
         .canonicalize()
         .expect("Failed to canonicalize path");
 
     // Transpile the runtime TypeScript to JavaScript
     let transpiled = transpile::parse_and_gen_path(&path).expect("Failed to transpile");
-    fs::write(runtime_path.clone(), transpiled.source).expect("Failed to write transpiled file");
+    fs::write(runtime_path.clone(), &transpiled.source).expect("Failed to write transpiled file");
 
-    // Create a dummy snapshot file for now - we'll create the actual snapshot at runtime
+    // Evaluate the runtime into a startup snapshot, so it is not recompiled on every run.
+    // The snapshot is made by the build script's own V8, which only matches the
+    // binary's when building for the host; cross builds evaluate the runtime from
+    // source instead.
     let snapshot_path = out_dir.join("MYCO_SNAPSHOT.bin");
-    fs::write(snapshot_path, b"").expect("Failed to write dummy snapshot");
+    let snapshot = if env::var("HOST").ok() == env::var("TARGET").ok() {
+        create_snapshot(&transpiled.source)
+    } else {
+        println!("cargo:warning=Cross-compiling; building without a runtime snapshot");
+        Vec::new()
+    };
+    fs::write(snapshot_path, snapshot).expect("Failed to write snapshot");
 
     let init_zip_path = out_dir.join("MYCO_INIT.zip");
     zip_directory(
@@ -33,3 +43,47 @@ fn main() {
     )
     .unwrap();
 }
+
+/// Evaluates the runtime script in a fresh context and snapshots the result. The
+/// script's completion value, the runtime factory, is stored as the context's first
+/// snapshot data rather than on `globalThis`.
+///
+/// Nothing in the snapshot may reference a native function: ops are only created at
+/// startup, and handed to the factory.
+#[cfg(feature = "snapshot")]
+fn create_snapshot(runtime_source: &str) -> Vec<u8> {
+    #[repr(C, align(16))]
+    struct IcuData<T: ?Sized>(T);
+    static ICU_DATA: &IcuData<[u8]> = &IcuData(*include_bytes!("src/run/icudtl.dat"));
+
+    v8::icu::set_common_data_77(&ICU_DATA.0).expect("Failed to set ICU data");
+    let platform = v8::new_default_platform(0, false).make_shared();
+    v8::V8::initialize_platform(platform);
+    v8::V8::initialize();
+
+    let mut isolate = v8::Isolate::snapshot_creator(None, None);
+    {
+        v8::scope!(let scope, &mut isolate);
+        let context = v8::Context::new(scope, Default::default());
+        let scope = &mut v8::ContextScope::new(scope, context);
+
+        let source = v8::String::new(scope, runtime_source).expect("Runtime source too large");
+        let script = v8::Script::compile(scope, source, None).expect("Failed to compile runtime");
+        let completion_value = script.run(scope).expect("Failed to evaluate runtime");
+        let factory = v8::Local::<v8::Function>::try_from(completion_value)
+            .expect("Runtime did not evaluate to a factory function");
+
+        scope.add_context_data(context, factory);
+        scope.set_default_context(context);
+    }
+
+    isolate
+        .create_blob(v8::FunctionCodeHandling::Keep)
+        .expect("Failed to create snapshot")
+        .to_vec()
+}
+
+#[cfg(not(feature = "snapshot"))]
+fn create_snapshot(_runtime_source: &str) -> Vec<u8> {
+    Vec::new()
+}
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use log::{debug, trace};
+use sha2::{Digest, Sha256};
+use util::transpile::{transpiler_id, TranspiledFile};
+use util::UtilError;
+
+use crate::errors::MycoError;
+
+/// The cache directory of the project rooted at `project_dir`.
+pub fn cache_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".myco").join("cache")
+}
+
+/// Removes everything cached for the project rooted at `project_dir`. Returns
+/// whether there was anything to remove.
+pub fn clean(project_dir: &Path) -> Result<bool, MycoError> {
+    let dir = cache_dir(project_dir);
+    match std::fs::remove_dir_all(&dir) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(MycoError::DirectoryRemoval {
+            path: dir.display().to_string(),
+            source: e,
+        }),
+    }
+}
+
+/// Transpiled TypeScript, stored on disk so that unchanged files are not
+/// re-transpiled on every run.
+///
+/// Entries are keyed by a hash of the file's path and contents, the swc versions
+/// and the transpile options, so they never need invalidating: a changed file or
+/// a new transpiler simply misses. Failing to read or write the cache is never an
+/// error; the file is transpiled as if there were no cache.
+pub struct TranspileCache {
+    dir: PathBuf,
+}
+
+impl TranspileCache {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            dir: cache_dir(project_dir).join("transpile"),
+        }
+    }
+
+    /// Transpiles the TypeScript file at `path`, or returns its cached output.
+    pub fn transpile(&self, path: &Path) -> Result<TranspiledFile, UtilError> {
+        let source = std::fs::read_to_string(path).map_err(|e| UtilError::FileRead {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+
+        let key = cache_key(&[
+            transpiler_id().as_bytes(),
+            path.to_string_lossy().as_bytes(),
+            source.as_bytes(),
+        ]);
+        let code_path = self.dir.join(format!("{}.js", key));
+        let map_path = self.dir.join(format!("{}.js.map", key));
+
+        if let (Ok(source), Ok(source_map)) = (
+            std::fs::read_to_string(&code_path),
+            std::fs::read(&map_path),
+        ) {
+            trace!("Transpile cache hit for {}", path.display());
+            return Ok(TranspiledFile { source, source_map });
+        }
+
+        debug!("Transpile cache miss for {}", path.display());
+        let transpiled = util::transpile::parse_and_gen_source(path, source)?;
+        if let Err(e) = self.store(&code_path, &map_path, &transpiled) {
+            debug!("Failed to cache transpiled {}: {}", path.display(), e);
+        }
+        Ok(transpiled)
+    }
+
+    /// Writes an entry. The source map goes first and each file is renamed into
+    /// place, so a concurrent run never sees a partial entry.
+    fn store(
+        &self,
+        code_path: &Path,
+        map_path: &Path,
+        transpiled: &TranspiledFile,
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        write_replace(map_path, &transpiled.source_map)?;
+        write_replace(code_path, transpiled.source.as_bytes())
+    }
+}
+
+/// V8's compiled code for modules, stored on disk so that unchanged modules are
+/// not parsed and compiled from scratch on every run.
+///
+/// Entries are keyed by a hash of the module's URL and the exact code V8
+/// compiled, along with V8's cache version tag. V8 checks the rest (flags, build)
+/// itself and rejects a stale entry, in which case the module compiles from source
+/// and the entry is replaced. As with [`TranspileCache`], cache I/O never fails
+/// a run.
+pub struct CodeCache {
+    dir: PathBuf,
+}
+
+impl CodeCache {
+    pub fn new(project_dir: &Path) -> Self {
+        Self {
+            dir: cache_dir(project_dir).join("v8"),
+        }
+    }
+
+    pub fn key(module_url: &str, code: &str) -> String {
+        cache_key(&[
+            &v8::script_compiler::cached_data_version_tag().to_le_bytes(),
+            module_url.as_bytes(),
+            code.as_bytes(),
+        ])
+    }
+
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.dir.join(format!("{}.bin", key))).ok()
+    }
+
+    pub fn store(&self, key: &str, data: &[u8]) {
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|()| write_replace(&self.dir.join(format!("{}.bin", key)), data));
+        if let Err(e) = result {
+            debug!("Failed to store code cache {}: {}", key, e);
+        }
+    }
+}
+
+/// Hashes `parts` into a hex cache key. Each part is length-prefixed, so
+/// different splits of the same bytes never collide.
+fn cache_key(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn write_replace(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
@@ -0,0 +1,460 @@
+//! Packages installed from myco-lock.toml, and the bare specifiers that import them.
+//!
+//! `myco install` extracts each locked package to `vendor/<name>/` and keeps the
+//! package's myco.toml beside it as `vendor/<name>.toml`, where its entry points and
+//! dependencies are read from. A package can only be imported by name from a package
+//! that declares it in `[deps]`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+use log::debug;
+
+use super::lockfile::LockFile;
+use crate::errors::MycoError;
+use crate::manifest::{ModuleType, MycoToml, PackageName, PackageVersion};
+
+pub const VENDOR_DIR: &str = "vendor";
+
+/// Where `myco install` keeps the manifest of a package it extracted to `vendor_dir`.
+pub fn vendored_manifest_path(vendor_dir: &Path, name: &PackageName) -> PathBuf {
+    vendor_dir.join(format!("{}.toml", name))
+}
+
+#[derive(Debug, Clone)]
+struct InstalledPackage {
+    version: PackageVersion,
+    dir: PathBuf,
+    dependencies: BTreeSet<PackageName>,
+    main: Option<String>,
+    exports: Option<BTreeMap<String, String>>,
+    module_type: Option<ModuleType>,
+}
+
+impl InstalledPackage {
+    /// The file or directory in the package that `subpath`, such as "." or "./core",
+    /// imports, relative to the package directory.
+    fn entry_point(&self, subpath: &str) -> Option<String> {
+        match &self.exports {
+            Some(exports) => {
+                if let Some(target) = exports.get(subpath) {
+                    return Some(target.clone());
+                }
+                // Keys ending with '/' export everything below them, the longest first
+                exports
+                    .iter()
+                    .filter(|(key, _)| key.ends_with('/') && subpath.starts_with(key.as_str()))
+                    .max_by_key(|(key, _)| key.len())
+                    .map(|(key, target)| format!("{}{}", target, &subpath[key.len()..]))
+            }
+            None if subpath == "." => Some(self.main.clone().unwrap_or_else(|| ".".to_string())),
+            None => Some(subpath.to_string()),
+        }
+    }
+}
+
+/// The packages installed for a project, keyed by name.
+#[derive(Debug, Clone)]
+pub struct InstalledPackages {
+    name: String,
+    dependencies: BTreeSet<PackageName>,
+    packages: BTreeMap<PackageName, InstalledPackage>,
+}
+
+impl InstalledPackages {
+    /// Loads the packages installed for the project in `dir`, whose myco.toml is
+    /// `myco_toml`. A project without a lockfile has none installed.
+    pub fn load(dir: &Path, myco_toml: &MycoToml) -> Result<Self, MycoError> {
+        let lockfile = match LockFile::load_in(dir) {
+            Ok(lockfile) => lockfile,
+            Err(MycoError::ReadFile { .. }) => {
+                debug!(
+                    "No lockfile in {}, so no packages are installed",
+                    dir.display()
+                );
+                LockFile::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        let vendor_dir = dir.join(VENDOR_DIR);
+        let mut packages = BTreeMap::new();
+        for locked in lockfile.package {
+            let manifest_path = vendored_manifest_path(&vendor_dir, &locked.name);
+            let manifest = if manifest_path.exists() {
+                let contents =
+                    std::fs::read_to_string(&manifest_path).map_err(|e| MycoError::ReadFile {
+                        path: manifest_path.display().to_string(),
+                        source: e,
+                    })?;
+                Some(
+                    toml::from_str::<MycoToml>(&contents)
+                        .map_err(|e| MycoError::ManifestParse { source: e })?,
+                )
+            } else {
+                debug!(
+                    "No manifest for {} at {}, it has no entry points or dependencies",
+                    locked.name,
+                    manifest_path.display()
+                );
+                None
+            };
+
+            let definition = manifest.as_ref().and_then(|m| m.package.as_ref());
+            let package = InstalledPackage {
+                version: locked.version,
+                dir: vendor_dir.join(locked.name.to_string()),
+                dependencies: manifest
+                    .as_ref()
+                    .map(|m| m.clone_deps().into_keys().collect())
+                    .unwrap_or_default(),
+                main: definition.and_then(|d| d.main.clone()),
+                exports: definition.and_then(|d| d.exports.clone()),
+                module_type: definition.and_then(|d| d.module_type),
+            };
+            packages.insert(locked.name, package);
+        }
+
+        Ok(Self {
+            name: myco_toml
+                .package
+                .as_ref()
+                .map(|p| p.name.clone())
+                .unwrap_or("<unnamed project>".to_string()),
+            dependencies: myco_toml.clone_deps().into_keys().collect(),
+            packages,
+        })
+    }
+
+    /// Resolves a specifier that names a package, like `@myco/std` or `@myco/std/core`,
+    /// as imported from `referrer`. Returns `None` when it names neither a dependency
+    /// nor an installed package, which is left to other resolution.
+    pub fn resolve(
+        &self,
+        specifier: &str,
+        referrer: Option<&Path>,
+    ) -> Result<Option<PathBuf>, MycoError> {
+        let Some((name, subpath)) = split_specifier(specifier) else {
+            return Ok(None);
+        };
+
+        // Packages import what they declare, and so does the project
+        let importer = referrer.and_then(|referrer| self.package_of(referrer));
+        let (importer_name, dependencies) = match importer {
+            Some((importer_name, package)) => (importer_name.to_string(), &package.dependencies),
+            None => (self.name.clone(), &self.dependencies),
+        };
+        let is_declared = dependencies.contains(&name) || importer_name == name.to_string();
+
+        let package = match (self.packages.get(&name), is_declared) {
+            (None, false) => return Ok(None),
+            (Some(_), false) => {
+                return Err(MycoError::UndeclaredDependency {
+                    package: name.to_string(),
+                    importer: importer_name,
+                });
+            }
+            (None, true) => {
+                return Err(MycoError::PackageNotInstalled {
+                    package: name.to_string(),
+                });
+            }
+            (Some(package), true) => package,
+        };
+
+        let subpath = format!(".{}", subpath);
+        let not_exported = || MycoError::PackageNotExported {
+            package: name.to_string(),
+            subpath: subpath.clone(),
+        };
+        let entry_point = package.entry_point(&subpath).ok_or_else(not_exported)?;
+
+        // Neither the import nor the package's exports can reach outside the package
+        let entry_point = Path::new(&entry_point);
+        if entry_point
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(not_exported());
+        }
+        let mut path = package.dir.clone();
+        path.extend(entry_point.components().filter(|c| *c != Component::CurDir));
+
+        debug!(
+            "Resolved {} to {} v{} at {}",
+            specifier,
+            name,
+            package.version,
+            path.display()
+        );
+        Ok(Some(path))
+    }
+
+    /// The directory of the installed package that `path` is in, if it is in one.
+    pub fn package_dir(&self, path: &Path) -> Option<&Path> {
+        self.package_of(path)
+            .map(|(_, package)| package.dir.as_path())
+    }
+
+    /// Checks that `path`, imported from `referrer` by its path rather than by a
+    /// package's name, does not cross into or out of an installed package. A package
+    /// only reaches its own files by path, and nothing else reaches into a package.
+    pub fn check_boundary(&self, path: &Path, referrer: Option<&Path>) -> Result<(), MycoError> {
+        let path = normalize(path);
+        let importer = referrer.and_then(|referrer| self.package_of(&normalize(referrer)));
+        let imported = self.package_of(&path);
+        if importer.map(|(name, _)| name) == imported.map(|(name, _)| name) {
+            return Ok(());
+        }
+
+        Err(MycoError::PackageBoundary {
+            path: path.display().to_string(),
+            importer: importer.map_or_else(|| self.name.clone(), |(name, _)| name.to_string()),
+        })
+    }
+
+    /// Whether `path` is in an installed package that declares `type = "commonjs"`, so
+    /// that its `.js` files are CommonJS.
+    pub fn is_commonjs(&self, path: &Path) -> bool {
+        self.package_of(path)
+            .is_some_and(|(_, package)| package.module_type == Some(ModuleType::CommonJs))
+    }
+
+    fn package_of(&self, path: &Path) -> Option<(&PackageName, &InstalledPackage)> {
+        self.packages
+            .iter()
+            .find(|(_, package)| path.starts_with(&package.dir))
+    }
+
+    /// tsconfig `paths` for the project's dependencies, so that type checking resolves
+    /// package imports the way `myco run` does. Each path is relative to the project.
+    pub fn type_paths(&self) -> Vec<(String, String)> {
+        let mut paths = Vec::new();
+        for name in &self.dependencies {
+            let Some(package) = self.packages.get(name) else {
+                continue;
+            };
+            let dir = format!("./{}/{}", VENDOR_DIR, name);
+            let target = |target: &str| format!("{}/{}", dir, target.trim_start_matches("./"));
+
+            match &package.exports {
+                Some(exports) => {
+                    for (key, value) in exports {
+                        let pattern = format!("{}{}", name, key.trim_start_matches('.'));
+                        match pattern.strip_suffix('/') {
+                            Some(prefix) => {
+                                paths.push((format!("{}/*", prefix), format!("{}*", target(value))))
+                            }
+                            None => paths.push((pattern, target(value))),
+                        }
+                    }
+                }
+                None => {
+                    let main = package.main.as_deref().map_or(dir.clone(), target);
+                    paths.push((name.to_string(), main));
+                    paths.push((format!("{}/*", name), format!("{}/*", dir)));
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// Removes the `.` and `..` components of `path` without touching the filesystem, so
+/// that a path which climbs out of a package is not taken to be inside it.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Splits a specifier into the package it names and the rest of it, which is empty
+/// or starts with '/'.
+fn split_specifier(specifier: &str) -> Option<(PackageName, &str)> {
+    if !specifier.starts_with('@') {
+        return None;
+    }
+    let end = specifier
+        .match_indices('/')
+        .nth(1)
+        .map(|(index, _)| index)
+        .unwrap_or(specifier.len());
+    let name = PackageName::from_str(&specifier[..end]).ok()?;
+    if name.name.is_empty() {
+        return None;
+    }
+    Some((name, &specifier[end..]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn package(
+        main: Option<&str>,
+        exports: Option<&[(&str, &str)]>,
+        dependencies: &[&str],
+    ) -> InstalledPackage {
+        InstalledPackage {
+            version: PackageVersion::from_str("1.0.0").unwrap(),
+            dir: PathBuf::new(),
+            dependencies: dependencies
+                .iter()
+                .map(|d| PackageName::from_str(d).unwrap())
+                .collect(),
+            main: main.map(str::to_string),
+            exports: exports.map(|exports| {
+                exports
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            }),
+            module_type: None,
+        }
+    }
+
+    fn installed(
+        packages: Vec<(&str, InstalledPackage)>,
+        dependencies: &[&str],
+    ) -> InstalledPackages {
+        InstalledPackages {
+            name: "@app/main".to_string(),
+            dependencies: dependencies
+                .iter()
+                .map(|d| PackageName::from_str(d).unwrap())
+                .collect(),
+            packages: packages
+                .into_iter()
+                .map(|(name, mut package)| {
+                    package.dir = PathBuf::from("/app/vendor").join(name);
+                    (PackageName::from_str(name).unwrap(), package)
+                })
+                .collect(),
+        }
+    }
+
+    fn resolve(packages: &InstalledPackages, specifier: &str, referrer: &str) -> Option<String> {
+        packages
+            .resolve(specifier, Some(Path::new(referrer)))
+            .unwrap()
+            .map(|path| path.display().to_string())
+    }
+
+    #[test]
+    fn test_split_specifier() {
+        let (name, subpath) = split_specifier("@myco/std/collections/lists").unwrap();
+        assert_eq!(name.to_string(), "@myco/std");
+        assert_eq!(subpath, "/collections/lists");
+        assert_eq!(split_specifier("@myco/std").unwrap().1, "");
+        assert!(split_specifier("@myco").is_none());
+        assert!(split_specifier("./std").is_none());
+    }
+
+    #[test]
+    fn test_entry_points() {
+        let packages = installed(
+            vec![
+                ("@myco/std", package(None, None, &[])),
+                ("@myco/test", package(Some("./main.ts"), None, &[])),
+                (
+                    "@myco/fs",
+                    package(
+                        None,
+                        Some(&[(".", "./fs.ts"), ("./io/", "./streams/")]),
+                        &[],
+                    ),
+                ),
+            ],
+            &["@myco/std", "@myco/test", "@myco/fs"],
+        );
+        let referrer = "/app/main.ts";
+        assert_eq!(
+            resolve(&packages, "@myco/std", referrer).as_deref(),
+            Some("/app/vendor/@myco/std")
+        );
+        assert_eq!(
+            resolve(&packages, "@myco/std/core", referrer).as_deref(),
+            Some("/app/vendor/@myco/std/core")
+        );
+        assert_eq!(
+            resolve(&packages, "@myco/test", referrer).as_deref(),
+            Some("/app/vendor/@myco/test/main.ts")
+        );
+        assert_eq!(
+            resolve(&packages, "@myco/fs", referrer).as_deref(),
+            Some("/app/vendor/@myco/fs/fs.ts")
+        );
+        assert_eq!(
+            resolve(&packages, "@myco/fs/io/reader.ts", referrer).as_deref(),
+            Some("/app/vendor/@myco/fs/streams/reader.ts")
+        );
+        assert!(packages
+            .resolve("@myco/fs/internal.ts", Some(Path::new(referrer)))
+            .is_err());
+        assert!(packages
+            .resolve("@myco/std/../../secret.ts", Some(Path::new(referrer)))
+            .is_err());
+        assert_eq!(resolve(&packages, "@other/lib", referrer), None);
+    }
+
+    #[test]
+    fn test_declared_dependencies() {
+        let packages = installed(
+            vec![
+                ("@myco/std", package(None, None, &[])),
+                ("@myco/test", package(None, None, &["@myco/std"])),
+            ],
+            &["@myco/test", "@myco/missing"],
+        );
+        assert!(matches!(
+            packages.resolve("@myco/std", Some(Path::new("/app/main.ts"))),
+            Err(MycoError::UndeclaredDependency { .. })
+        ));
+        assert!(matches!(
+            packages.resolve("@myco/missing", Some(Path::new("/app/main.ts"))),
+            Err(MycoError::PackageNotInstalled { .. })
+        ));
+        assert_eq!(
+            resolve(&packages, "@myco/std", "/app/vendor/@myco/test/index.ts").as_deref(),
+            Some("/app/vendor/@myco/std")
+        );
+    }
+    #[test]
+    fn test_package_boundary() {
+        let packages = installed(
+            vec![
+                ("@myco/std", package(None, None, &[])),
+                ("@myco/test", package(None, None, &["@myco/std"])),
+            ],
+            &["@myco/test"],
+        );
+        let check = |path: &str, referrer: &str| {
+            packages
+                .check_boundary(Path::new(path), Some(Path::new(referrer)))
+                .is_ok()
+        };
+        assert!(check("/app/src/util.ts", "/app/main.ts"));
+        assert!(check(
+            "/app/vendor/@myco/std/./core/../lists.ts",
+            "/app/vendor/@myco/std/index.ts"
+        ));
+        assert!(!check("/app/vendor/@myco/std/index.ts", "/app/main.ts"));
+        assert!(!check(
+            "/app/vendor/@myco/std/../../../main.ts",
+            "/app/vendor/@myco/std/index.ts"
+        ));
+        assert!(!check(
+            "/app/vendor/@myco/test/../std/index.ts",
+            "/app/vendor/@myco/test/index.ts"
+        ));
+    }
+}
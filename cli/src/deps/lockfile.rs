@@ -1,11 +1,13 @@
 use colored::*;
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, path::Path};
 
 use serde::{Deserialize, Serialize};
 
 use super::registry::{ResolvedVersion, ResolvedVersionDiff};
 use crate::errors::MycoError;
 
+pub const LOCKFILE: &str = "myco-lock.toml";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LockFile {
     pub package: Vec<ResolvedVersion>,
@@ -15,16 +17,19 @@ impl LockFile {
     pub fn save(&self) -> Result<(), MycoError> {
         let contents =
             toml::to_string_pretty(self).map_err(|e| MycoError::ManifestSerialize { source: e })?;
-        std::fs::write("myco-lock.toml", contents)
-            .map_err(|e| MycoError::LockfileSave { source: e })
+        std::fs::write(LOCKFILE, contents).map_err(|e| MycoError::LockfileSave { source: e })
     }
 
     pub fn load() -> Result<LockFile, MycoError> {
-        let contents =
-            std::fs::read_to_string("myco-lock.toml").map_err(|e| MycoError::ReadFile {
-                path: "myco-lock.toml".to_string(),
-                source: e,
-            })?;
+        Self::load_in(Path::new(""))
+    }
+
+    pub fn load_in(dir: &Path) -> Result<LockFile, MycoError> {
+        let path = dir.join(LOCKFILE);
+        let contents = std::fs::read_to_string(&path).map_err(|e| MycoError::ReadFile {
+            path: path.display().to_string(),
+            source: e,
+        })?;
         toml::from_str(&contents).map_err(|e| MycoError::ManifestParse { source: e })
     }
 
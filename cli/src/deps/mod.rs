@@ -1,14 +1,18 @@
 use changes::DepsChange;
 pub use changes::{write_deps_changes, write_new_package_version};
 
+pub use installed::InstalledPackages;
 pub use lockfile::LockFileDiff;
 use log::{debug, error, info, warn};
+use std::path::Path;
+use util::zip::extract_archive;
 
 use crate::errors::MycoError;
 use crate::integrity::calculate_integrity;
 use crate::manifest::{Location, MycoToml, PackageName};
 
 mod changes;
+mod installed;
 mod lockfile;
 mod registry;
 mod resolver;
@@ -85,6 +89,9 @@ fn install_from_lockfile(
         lockfile.package.len()
     );
 
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| MycoError::TokioRuntime { source: e })?;
+
     // TODO: Make this more efficient by only downloading the files we don't have yet
     debug!("Removing existing vendor directory");
     std::fs::remove_dir_all("vendor").unwrap_or(());
@@ -134,37 +141,55 @@ fn install_from_lockfile(
         debug!("Package integrity verified");
 
         debug!("Extracting package archive");
-        let mut zip_archive = zip::ZipArchive::new(std::io::Cursor::new(zip_file))
-            .map_err(|e| MycoError::PackageExtraction { source: e })?;
-
-        // Iterate through the entries in the ZIP archive
-        debug!("Archive contains {} files", zip_archive.len());
-        for i in 0..zip_archive.len() {
-            let mut entry = zip_archive
-                .by_index(i)
-                .map_err(|e| MycoError::PackageExtraction { source: e })?;
-            let out_path = std::path::PathBuf::from("./vendor").join(entry.name());
-
-            if entry.is_dir() {
-                // Create a new directory if the entry is a directory
-                debug!("Creating directory: {}", out_path.display());
-                std::fs::create_dir_all(&out_path)
-                    .map_err(|e| MycoError::VendorDirCreation { source: e })?;
-            } else {
-                // Create a new file and write the entry's contents to it
-                debug!("Extracting file: {}", out_path.display());
-                let mut out_file =
-                    std::fs::File::create(&out_path).map_err(|e| MycoError::FileWrite {
-                        path: out_path.display().to_string(),
-                        source: e,
-                    })?;
-                std::io::copy(&mut entry, &mut out_file).map_err(|e| MycoError::FileWrite {
-                    path: out_path.display().to_string(),
+        std::fs::create_dir_all(installed::VENDOR_DIR)
+            .map_err(|e| MycoError::VendorDirCreation { source: e })?;
+        let extracted = extract_archive(
+            std::io::Cursor::new(zip_file),
+            Path::new(installed::VENDOR_DIR),
+        )?;
+        debug!("Extracted {} archive entries", extracted.len());
+        debug!("Successfully extracted package: {}", version.name);
+
+        // The archive keeps the package's manifest beside it, for its entry points and
+        // dependencies, so the manifest is covered by the integrity check too
+        let manifest_path =
+            installed::vendored_manifest_path(Path::new(installed::VENDOR_DIR), &version.name);
+        let embedded = extracted.contains(&format!("{}.toml", version.name));
+        let manifest: MycoToml = if embedded {
+            debug!("Reading package manifest: {}", manifest_path.display());
+            let contents =
+                std::fs::read_to_string(&manifest_path).map_err(|e| MycoError::ReadFile {
+                    path: manifest_path.display().to_string(),
                     source: e,
                 })?;
-            }
+            toml::from_str(&contents).map_err(|e| MycoError::ManifestParse { source: e })?
+        } else {
+            // Archives packed before manifests went in them only have one beside them
+            debug!(
+                "No manifest in the archive of {}, fetching it from {:?}",
+                version.name, version.toml_url
+            );
+            runtime.block_on(registry::fetch_contents(&version.toml_url))?
+        };
+
+        let matches_lockfile = manifest.package.as_ref().is_some_and(|package| {
+            package.name == version.name.to_string() && package.version == version.version
+        });
+        if !matches_lockfile {
+            return Err(MycoError::PackageManifestMismatch {
+                package: version.name.to_string(),
+                version: version.version.to_string(),
+            });
+        }
+        if !embedded {
+            debug!("Writing package manifest: {}", manifest_path.display());
+            std::fs::write(&manifest_path, manifest.to_string()?).map_err(|e| {
+                MycoError::FileWrite {
+                    path: manifest_path.display().to_string(),
+                    source: e,
+                }
+            })?;
         }
-        debug!("Successfully extracted package: {}", version.name);
     }
 
     // Create .myco directory and myco.d.ts file
@@ -1,5 +1,6 @@
+use crate::deps::InstalledPackages;
 use crate::errors::MycoError;
-use crate::manifest::{myco_local::MycoLocalToml, MycoToml};
+use crate::manifest::{import_map::ImportMap, myco_local::MycoLocalToml, MycoToml};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -30,6 +31,8 @@ struct CompilerOptions {
     module_resolution: String,
     #[serde(rename = "allowImportingTsExtensions")]
     allow_importing_ts_extensions: bool,
+    #[serde(rename = "resolveJsonModule")]
+    resolve_json_module: bool,
     paths: HashMap<String, Vec<String>>,
 }
 
@@ -51,6 +54,7 @@ impl Default for TSConfig {
                 no_emit: true,
                 module_resolution: "NodeNext".to_string(),
                 allow_importing_ts_extensions: true,
+                resolve_json_module: true,
                 paths,
             },
             include: vec!["./.myco/myco.d.ts".to_string()],
@@ -119,6 +123,26 @@ pub fn generate_tsconfig_json(myco_toml: &MycoToml) -> Result<String, MycoError>
         }
     }
 
+    let package_dir =
+        std::env::current_dir().map_err(|e| MycoError::GetCurrentDirectory { source: e })?;
+
+    // Dependencies are imported by name
+    for (pattern, path) in InstalledPackages::load(&package_dir, myco_toml)?.type_paths() {
+        tsconfig.compiler_options.paths.insert(pattern, vec![path]);
+    }
+
+    // Mirror the import map's top-level mappings so that type checking resolves bare
+    // specifiers the way `myco run` does. Scoped mappings have no tsconfig equivalent.
+    if let Some(import_map) = ImportMap::load(&package_dir, myco_toml)? {
+        for (specifier, address) in import_map.package_imports() {
+            let (pattern, path) = match specifier.strip_suffix('/') {
+                Some(prefix) => (format!("{}/*", prefix), format!("./{}*", address)),
+                None => (specifier.to_string(), format!("./{}", address)),
+            };
+            tsconfig.compiler_options.paths.insert(pattern, vec![path]);
+        }
+    }
+
     // Convert TSConfig to JSON Value for manipulation
     let mut tsconfig_value =
         serde_json::to_value(&tsconfig).map_err(|e| MycoError::JsonSerialize { source: e })?;
@@ -94,12 +94,43 @@ pub enum MycoError {
         resolved_path: String,
     },
 
+    #[error("Cannot import {specifier}: {message}")]
+    ImportAttributes { specifier: String, message: String },
+
+    #[error("Cannot load {path}: JSON modules can only be loaded from inside {root}")]
+    JsonOutsideRoot { path: String, root: String },
+
+    #[error("Failed to parse JSON module {path}: {message}")]
+    JsonModule { path: String, message: String },
+
+    #[error("Failed to evaluate CommonJS module {path}: {message}")]
+    CommonJsEvaluation { path: String, message: String },
+
+    #[error("Cannot require {specifier}: {message}")]
+    Require { specifier: String, message: String },
+
     #[error("Event loop error: {message}")]
     EventLoop { message: String },
 
     #[error("Unhandled error: {message}")]
     UnhandledError { message: String },
 
+    #[error("Unhandled promise rejection: {message}")]
+    UnhandledRejection { message: String },
+
+    #[error("Script ran out of memory: {used_mb:.1} MB in use, {total_mb:.1} MB allocated, heap limit {limit_mb:.1} MB")]
+    HeapLimit {
+        used_mb: f64,
+        total_mb: f64,
+        limit_mb: f64,
+    },
+
+    #[error("Script timed out after {timeout:?}")]
+    Timeout { timeout: std::time::Duration },
+
+    #[error("Invalid [run] configuration in myco.toml: {message}")]
+    InvalidRunConfig { message: String },
+
     #[error("Inspector error: {message}")]
     Inspector { message: String },
 
@@ -125,6 +156,9 @@ pub enum MycoError {
         actual: String,
     },
 
+    #[error("The manifest in the archive of package {package} is not for version {version}")]
+    PackageManifestMismatch { package: String, version: String },
+
     #[error("Failed to download package from {url}: {source}")]
     PackageDownload {
         url: String,
@@ -178,6 +212,24 @@ pub enum MycoError {
     #[error("No myco-local.toml found in {myco_toml_dir}")]
     LocalManifestNotFound { myco_toml_dir: String },
 
+    #[error("Invalid import map {path}: {message}")]
+    InvalidImportMap { path: String, message: String },
+
+    #[error("Import map cannot resolve {specifier}: {message}")]
+    ImportMapResolution { specifier: String, message: String },
+
+    #[error("Cannot import {package} from {importer}: it is not a declared dependency")]
+    UndeclaredDependency { package: String, importer: String },
+
+    #[error("Cannot import {path} from {importer}: it crosses a package boundary")]
+    PackageBoundary { path: String, importer: String },
+
+    #[error("Package {package} is not installed - have you run `myco install`?")]
+    PackageNotInstalled { package: String },
+
+    #[error("Package {package} does not export {subpath}")]
+    PackageNotExported { package: String, subpath: String },
+
     #[error("Failed to serialize myco.toml: {source}")]
     ManifestSerialize {
         #[source]
@@ -212,6 +264,13 @@ pub enum MycoError {
         source: std::io::Error,
     },
 
+    #[error("Failed to remove directory '{path}': {source}")]
+    DirectoryRemoval {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
     #[error("Failed to extract init files: {source}")]
     InitFileExtraction {
         #[source]
@@ -308,6 +367,7 @@ impl From<util::UtilError> for MycoError {
                 path,
                 message: "Invalid file path".to_string(),
             },
+            util::UtilError::Zip { source } => MycoError::PackageExtraction { source },
             _ => MycoError::Internal {
                 message: err.to_string(),
             },
@@ -1,4 +1,5 @@
 use std::env;
+use std::time::Duration;
 
 use clap::{arg, command, ArgAction, Command};
 use log::{debug, error, info, warn, LevelFilter};
@@ -9,6 +10,7 @@ use crate::deps::write_deps_changes;
 use crate::errors::MycoError;
 use crate::manifest::{MycoToml, PackageName};
 
+mod cache;
 mod deps;
 mod errors;
 mod init;
@@ -40,6 +42,10 @@ fn run_main() -> Result<(), MycoError> {
                 .arg(arg!(--"inspect-port" <PORT> "Port for V8 inspector to listen on").value_parser(clap::value_parser!(u16)).default_value("9229"))
                 .arg(arg!(--"inspect-brk" "Enable V8 inspector and break on start").action(clap::ArgAction::SetTrue))
                 .arg(arg!(--"inspect-wait" "Enable V8 inspector and wait for connection").action(clap::ArgAction::SetTrue))
+                .arg(arg!(--"max-heap-mb" <MB> "The most heap, in megabytes, the script may use before it is stopped").value_parser(clap::value_parser!(u64).range(1..)))
+                .arg(arg!(--timeout <SECONDS> "How long, in seconds, the script may run before it is stopped").value_parser(parse_timeout))
+                .arg(arg!(--"unhandled-rejections" <MODE> "What an unhandled promise rejection does: fail the process, or only warn").value_parser(["strict", "warn"]).default_value("strict"))
+                .arg(arg!(--"log-format" <FORMAT> "How console output is written: formatted text, or one JSON object per call").value_parser(["text", "json"]).default_value("text"))
         )
         .subcommand(
             Command::new("init")
@@ -82,6 +88,15 @@ fn run_main() -> Result<(), MycoError> {
                 .about("Publish the current package to a registry")
                 .arg(arg!(<registry> "The registry to publish to"))
         )
+        .subcommand(
+            Command::new("cache")
+                .about("Manage the project's build cache")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("clean")
+                        .about("Remove everything cached under .myco/cache")
+                )
+        )
         .subcommand(
             Command::new("workspace")
                 .alias("ws")
@@ -117,6 +132,10 @@ fn run_main() -> Result<(), MycoError> {
     };
 
     let use_colors = !no_color;
+    if no_color {
+        // Also covers what scripts print through the console
+        colored::control::set_override(false);
+    }
     if let Err(e) = logger::init_logger(level, use_colors) {
         eprintln!("Failed to initialize logger: {}", e);
         std::process::exit(1);
@@ -142,8 +161,20 @@ fn run_main() -> Result<(), MycoError> {
             inspect, inspect_brk, inspect_wait, inspect_port
         );
 
+        let log_format = match matches.get_one::<String>("log-format").map(String::as_str) {
+            Some("json") => run::LogFormat::Json,
+            _ => run::LogFormat::Text,
+        };
+        let unhandled_rejections = match matches
+            .get_one::<String>("unhandled-rejections")
+            .map(String::as_str)
+        {
+            Some("warn") => run::UnhandledRejections::Warn,
+            _ => run::UnhandledRejections::Strict,
+        };
+
         // Enable debugging if any inspect flag is set
-        let debug_options = if inspect || inspect_brk || inspect_wait {
+        let debug = if inspect || inspect_brk || inspect_wait {
             info!("Debug mode enabled on port {}", inspect_port);
             Some(run::DebugOptions {
                 port: inspect_port,
@@ -153,6 +184,14 @@ fn run_main() -> Result<(), MycoError> {
         } else {
             None
         };
+        let options = run::RunOptions {
+            debug,
+            log_format,
+            unhandled_rejections,
+            max_heap_mb: matches.get_one::<u64>("max-heap-mb").map(|mb| *mb as usize),
+            timeout: matches.get_one::<Duration>("timeout").copied(),
+        };
+        debug!("Run options: {:?}", options);
 
         let current_dir =
             env::current_dir().map_err(|e| MycoError::GetCurrentDirectory { source: e })?;
@@ -174,10 +213,10 @@ fn run_main() -> Result<(), MycoError> {
             env::set_current_dir(&working_dir).map_err(|_e| MycoError::SetCurrentDirectory {
                 dir: working_dir.display().to_string(),
             })?;
-            run::run(&myco_toml, script, debug_options)?
+            run::run(&myco_toml, script, options)?
         } else {
             info!("Running script '{}' as standalone file", script);
-            run::run_file(script, debug_options)?
+            run::run_file(script, options)?
         };
         info!("Script execution completed with exit code: {}", exit_code);
         std::process::exit(exit_code);
@@ -337,7 +376,7 @@ fn run_main() -> Result<(), MycoError> {
         if let Some(package) = myco_toml.package.as_ref() {
             if let Some(pre_pack) = &package.pre_pack {
                 info!("Running pre-pack script: {}", pre_pack);
-                let exit_code = run::run(&myco_toml, pre_pack, None)?;
+                let exit_code = run::run(&myco_toml, pre_pack, run::RunOptions::default())?;
                 if exit_code != 0 {
                     return Err(MycoError::ScriptExecution {
                         message: format!("Pre-pack script exited with code {}", exit_code),
@@ -377,6 +416,24 @@ fn run_main() -> Result<(), MycoError> {
             message: e.to_string(),
         })?;
         info!("Package published successfully");
+    } else if let Some(cache_matches) = matches.subcommand_matches("cache") {
+        info!("Running 'cache' subcommand");
+        if cache_matches.subcommand_matches("clean").is_some() {
+            info!("Running 'cache clean' subcommand");
+            let current_dir =
+                env::current_dir().map_err(|e| MycoError::GetCurrentDirectory { source: e })?;
+            let project_dir = match MycoToml::load_nearest(current_dir.clone()) {
+                Ok((dir, _)) => dir,
+                Err(_) => current_dir,
+            };
+            let cache_dir = cache::cache_dir(&project_dir);
+            debug!("Cache directory: {}", cache_dir.display());
+            if cache::clean(&project_dir)? {
+                println!("Removed {}", cache_dir.display());
+            } else {
+                println!("Nothing to clean");
+            }
+        }
     } else if let Some(ws_matches) = matches.subcommand_matches("workspace") {
         info!("Running 'workspace' subcommand");
         if let Some(_list_matches) = ws_matches.subcommand_matches("list") {
@@ -436,3 +493,14 @@ fn run_main() -> Result<(), MycoError> {
 
     Ok(())
 }
+
+/// Parses `--timeout`, a positive number of seconds.
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of seconds", value))?;
+    if seconds <= 0.0 {
+        return Err("the timeout must be more than 0 seconds".to_string());
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
@@ -0,0 +1,329 @@
+//! Import maps, as specified by WHATWG:
+//! https://html.spec.whatwg.org/multipage/webappapis.html#import-maps
+//!
+//! A package declares one either as `[imports]` and `[scopes]` tables in its myco.toml
+//! or as an `import_map.json` next to it, but not both. Addresses and scope prefixes
+//! are relative to the package directory.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::errors::MycoError;
+use crate::manifest::MycoToml;
+
+pub const IMPORT_MAP_FILE: &str = "import_map.json";
+
+/// An import map as written, before its specifiers and addresses are resolved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportMapDefinition {
+    #[serde(default)]
+    pub imports: BTreeMap<String, String>,
+    #[serde(default)]
+    pub scopes: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ImportMapDefinition {
+    /// Loads the import map of the package in `dir`, whose myco.toml is `myco_toml`.
+    pub fn load(dir: &Path, myco_toml: &MycoToml) -> Result<Option<Self>, MycoError> {
+        let from_manifest =
+            (myco_toml.imports.is_some() || myco_toml.scopes.is_some()).then(|| {
+                ImportMapDefinition {
+                    imports: myco_toml.imports.clone().unwrap_or_default(),
+                    scopes: myco_toml.scopes.clone().unwrap_or_default(),
+                }
+            });
+
+        let file_path = dir.join(IMPORT_MAP_FILE);
+        if !file_path.exists() {
+            return Ok(from_manifest);
+        }
+        if from_manifest.is_some() {
+            return Err(MycoError::InvalidImportMap {
+                path: file_path.display().to_string(),
+                message: "myco.toml already declares an import map".to_string(),
+            });
+        }
+
+        let contents = std::fs::read_to_string(&file_path).map_err(|e| MycoError::ReadFile {
+            path: file_path.display().to_string(),
+            source: e,
+        })?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| MycoError::InvalidImportMap {
+                path: file_path.display().to_string(),
+                message: e.to_string(),
+            })
+    }
+}
+
+// Specifier keys with their addresses, in descending order so that longer prefixes
+// are tried first. An address that failed to parse is `None`, and blocks its key.
+type SpecifierMap = Vec<(String, Option<Url>)>;
+
+/// An import map with its specifiers and addresses resolved against its base URL.
+#[derive(Debug, Clone)]
+pub struct ImportMap {
+    base: Url,
+    imports: SpecifierMap,
+    scopes: Vec<(String, SpecifierMap)>,
+}
+
+impl ImportMap {
+    /// Loads the import map of the package in `dir`, which must be absolute.
+    pub fn load(dir: &Path, myco_toml: &MycoToml) -> Result<Option<Self>, MycoError> {
+        let Some(definition) = ImportMapDefinition::load(dir, myco_toml)? else {
+            return Ok(None);
+        };
+        let base = Url::from_directory_path(dir).map_err(|_| MycoError::InvalidImportMap {
+            path: dir.display().to_string(),
+            message: "the package directory is not an absolute path".to_string(),
+        })?;
+        Ok(Some(Self::new(&definition, &base)))
+    }
+
+    pub fn new(definition: &ImportMapDefinition, base: &Url) -> Self {
+        let mut scopes: Vec<_> = definition
+            .scopes
+            .iter()
+            .filter_map(|(prefix, imports)| match base.join(prefix) {
+                Ok(prefix) => Some((prefix.to_string(), sort_and_normalize(imports, base))),
+                Err(e) => {
+                    warn!("Ignoring import map scope '{}': {}", prefix, e);
+                    None
+                }
+            })
+            .collect();
+        scopes.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        Self {
+            base: base.clone(),
+            imports: sort_and_normalize(&definition.imports, base),
+            scopes,
+        }
+    }
+
+    /// Resolves `specifier` as imported from `referrer`. Returns `None` when the map
+    /// does not apply to a bare specifier, which is left to other resolution.
+    pub fn resolve(&self, specifier: &str, referrer: &Url) -> Result<Option<Url>, MycoError> {
+        let as_url = resolve_url_like(specifier, referrer);
+        let normalized = as_url
+            .as_ref()
+            .map(Url::to_string)
+            .unwrap_or_else(|| specifier.to_string());
+
+        let referrer = referrer.as_str();
+        for (prefix, imports) in &self.scopes {
+            let in_scope =
+                prefix == referrer || (prefix.ends_with('/') && referrer.starts_with(prefix));
+            if in_scope {
+                if let Some(url) = resolve_imports_match(&normalized, as_url.as_ref(), imports)? {
+                    return Ok(Some(url));
+                }
+            }
+        }
+
+        if let Some(url) = resolve_imports_match(&normalized, as_url.as_ref(), &self.imports)? {
+            return Ok(Some(url));
+        }
+        Ok(as_url)
+    }
+
+    /// The top-level mappings of bare specifiers to files in the package, with each
+    /// address relative to the package directory. A key that is a prefix ends with
+    /// `/`, and so does its address.
+    pub fn package_imports(&self) -> impl Iterator<Item = (&str, String)> {
+        self.imports.iter().filter_map(|(key, address)| {
+            let address = address.as_ref()?;
+            if Url::parse(key).is_ok() || address.scheme() != "file" {
+                return None;
+            }
+            let relative = self.base.make_relative(address)?;
+            if relative.starts_with("../") {
+                return None;
+            }
+            Some((key.as_str(), relative))
+        })
+    }
+}
+
+/// Parses a specifier that is a URL or starts with `/`, `./` or `../`, which are
+/// relative to `base`. Bare specifiers are `None`.
+fn resolve_url_like(specifier: &str, base: &Url) -> Option<Url> {
+    if specifier.starts_with('/') || specifier.starts_with("./") || specifier.starts_with("../") {
+        return base.join(specifier).ok();
+    }
+    Url::parse(specifier).ok()
+}
+
+fn sort_and_normalize(imports: &BTreeMap<String, String>, base: &Url) -> SpecifierMap {
+    let mut normalized: SpecifierMap = imports
+        .iter()
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, address)| {
+            let key = resolve_url_like(key, base)
+                .map(|url| url.to_string())
+                .unwrap_or_else(|| key.clone());
+            let address = match resolve_url_like(address, base) {
+                Some(url) if key.ends_with('/') && !url.as_str().ends_with('/') => {
+                    warn!(
+                        "Ignoring import map address '{}' for '{}': a prefix must map to a prefix",
+                        url, key
+                    );
+                    None
+                }
+                Some(url) => Some(url),
+                None => {
+                    warn!(
+                        "Ignoring import map address '{}' for '{}': not a URL or relative path",
+                        address, key
+                    );
+                    None
+                }
+            };
+            (key, address)
+        })
+        .collect();
+    normalized.sort_by(|(a, _), (b, _)| b.cmp(a));
+    normalized
+}
+
+fn resolve_imports_match(
+    normalized: &str,
+    as_url: Option<&Url>,
+    imports: &SpecifierMap,
+) -> Result<Option<Url>, MycoError> {
+    let blocked = |message: &str| MycoError::ImportMapResolution {
+        specifier: normalized.to_string(),
+        message: message.to_string(),
+    };
+
+    for (key, address) in imports {
+        if key == normalized {
+            return address
+                .clone()
+                .map(Some)
+                .ok_or_else(|| blocked("its mapping is invalid"));
+        }
+
+        let is_prefix = key.ends_with('/')
+            && normalized.starts_with(key.as_str())
+            && as_url.is_none_or(is_special);
+        if is_prefix {
+            let address = address
+                .as_ref()
+                .ok_or_else(|| blocked("its mapping is invalid"))?;
+            let after_prefix = &normalized[key.len()..];
+            let url = address
+                .join(after_prefix)
+                .map_err(|_| blocked("the mapped address is not a valid URL"))?;
+            // A mapping cannot be used to reach outside its address
+            if !url.as_str().starts_with(address.as_str()) {
+                return Err(blocked("it backtracks above its mapping"));
+            }
+            return Ok(Some(url));
+        }
+    }
+
+    Ok(None)
+}
+
+fn is_special(url: &Url) -> bool {
+    matches!(
+        url.scheme(),
+        "file" | "http" | "https" | "ws" | "wss" | "ftp"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn import_map(json: &str) -> ImportMap {
+        let definition: ImportMapDefinition = serde_json::from_str(json).unwrap();
+        ImportMap::new(&definition, &Url::parse("file:///project/").unwrap())
+    }
+
+    fn resolve(map: &ImportMap, specifier: &str, referrer: &str) -> Option<String> {
+        let referrer = Url::parse(referrer).unwrap();
+        map.resolve(specifier, &referrer)
+            .unwrap()
+            .map(|url| url.to_string())
+    }
+
+    #[test]
+    fn test_bare_specifiers() {
+        let map = import_map(
+            r#"{ "imports": {
+                "lodash": "./vendor/lodash/index.ts",
+                "lodash/": "./vendor/lodash/",
+                "lodash/fp/": "./vendor/lodash-fp/"
+            } }"#,
+        );
+        let referrer = "file:///project/main.ts";
+        assert_eq!(
+            resolve(&map, "lodash", referrer).as_deref(),
+            Some("file:///project/vendor/lodash/index.ts")
+        );
+        assert_eq!(
+            resolve(&map, "lodash/array.ts", referrer).as_deref(),
+            Some("file:///project/vendor/lodash/array.ts")
+        );
+        assert_eq!(
+            resolve(&map, "lodash/fp/map.ts", referrer).as_deref(),
+            Some("file:///project/vendor/lodash-fp/map.ts")
+        );
+        assert_eq!(resolve(&map, "react", referrer), None);
+    }
+
+    #[test]
+    fn test_relative_specifiers() {
+        let map = import_map(r#"{ "imports": { "./src/config.ts": "./src/config.prod.ts" } }"#);
+        assert_eq!(
+            resolve(&map, "./config.ts", "file:///project/src/main.ts").as_deref(),
+            Some("file:///project/src/config.prod.ts")
+        );
+        assert_eq!(
+            resolve(&map, "./util.ts", "file:///project/src/main.ts").as_deref(),
+            Some("file:///project/src/util.ts")
+        );
+    }
+
+    #[test]
+    fn test_scopes() {
+        let map = import_map(
+            r#"{
+                "imports": { "dep": "./vendor/dep@2/index.ts" },
+                "scopes": {
+                    "./vendor/a/": { "dep": "./vendor/dep@1/index.ts" },
+                    "./vendor/a/nested/": { "dep": "./vendor/dep@3/index.ts" }
+                }
+            }"#,
+        );
+        assert_eq!(
+            resolve(&map, "dep", "file:///project/vendor/a/index.ts").as_deref(),
+            Some("file:///project/vendor/dep@1/index.ts")
+        );
+        assert_eq!(
+            resolve(&map, "dep", "file:///project/vendor/a/nested/index.ts").as_deref(),
+            Some("file:///project/vendor/dep@3/index.ts")
+        );
+        assert_eq!(
+            resolve(&map, "dep", "file:///project/vendor/b/index.ts").as_deref(),
+            Some("file:///project/vendor/dep@2/index.ts")
+        );
+    }
+
+    #[test]
+    fn test_invalid_mappings() {
+        let map =
+            import_map(r#"{ "imports": { "dep/": "./vendor/dep", "other/": "./vendor/other/" } }"#);
+        let referrer = Url::parse("file:///project/main.ts").unwrap();
+        assert!(map.resolve("dep/index.ts", &referrer).is_err());
+        assert!(map.resolve("other/../../secret.ts", &referrer).is_err());
+    }
+}
@@ -1,10 +1,11 @@
 pub use dependency_version::DependencyVersion;
-pub use myco_toml::{Location, MycoToml, PackageDefinition};
+pub use myco_toml::{Location, ModuleType, MycoToml, PackageDefinition, RunConfig};
 pub use package_name::PackageName;
 pub use package_version::PackageVersion;
 pub use workspace_toml::WorkspaceManifest;
 
 mod dependency_version;
+pub mod import_map;
 pub mod myco_local;
 mod myco_toml;
 mod package_name;
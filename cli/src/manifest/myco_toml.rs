@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -63,10 +64,50 @@ impl Display for Location {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MycoToml {
     pub package: Option<PackageDefinition>,
-    pub run: Option<BTreeMap<String, String>>,
+    pub run: Option<RunConfig>,
     pub registries: Option<BTreeMap<String, Location>>,
     pub deps: Option<BTreeMap<PackageName, DependencyVersion>>,
     pub tsconfig: Option<BTreeMap<String, serde_json::Value>>,
+    // An import map, unless the package has an import_map.json
+    pub imports: Option<BTreeMap<String, String>>,
+    pub scopes: Option<BTreeMap<String, BTreeMap<String, String>>>,
+}
+
+/// The `[run]` table: limits for `myco run`, which its flags override, and the
+/// scripts it can run by name, each mapped to a file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_heap_mb: Option<u64>,
+    // In seconds, like `--timeout`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<f64>,
+    #[serde(flatten)]
+    pub scripts: BTreeMap<String, String>,
+}
+
+impl RunConfig {
+    pub fn max_heap_mb(&self) -> Result<Option<usize>, MycoError> {
+        match self.max_heap_mb {
+            Some(0) => Err(MycoError::InvalidRunConfig {
+                message: "max_heap_mb must be at least 1".to_string(),
+            }),
+            max_heap_mb => Ok(max_heap_mb.map(|mb| mb as usize)),
+        }
+    }
+
+    pub fn timeout(&self) -> Result<Option<Duration>, MycoError> {
+        self.timeout
+            .map(|seconds| {
+                Duration::try_from_secs_f64(seconds)
+                    .ok()
+                    .filter(|timeout| !timeout.is_zero())
+                    .ok_or_else(|| MycoError::InvalidRunConfig {
+                        message: format!("timeout must be more than 0 seconds, not {}", seconds),
+                    })
+            })
+            .transpose()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +119,21 @@ pub struct PackageDefinition {
     pub license: Option<String>,
     pub pre_pack: Option<String>,
     pub include: Option<PackageInclude>,
+    // Entry points, relative to the packed directory. `exports` maps subpaths such as
+    // "." or "./core" to files, and when present nothing else can be imported.
+    pub main: Option<String>,
+    pub exports: Option<BTreeMap<String, String>>,
+    // How an installed package's `.js` files are loaded, as ES modules unless it is
+    // "commonjs"
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub module_type: Option<ModuleType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleType {
+    Module,
+    CommonJs,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
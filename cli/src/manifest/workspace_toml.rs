@@ -4,7 +4,7 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::errors::MycoError;
-use crate::manifest::{DependencyVersion, Location, PackageName};
+use crate::manifest::{DependencyVersion, Location, PackageName, RunConfig};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct WorkspaceDefinition {
@@ -14,7 +14,7 @@ pub struct WorkspaceDefinition {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceManifest {
     pub workspace: WorkspaceDefinition,
-    pub run: Option<BTreeMap<String, String>>,
+    pub run: Option<RunConfig>,
     pub registries: Option<BTreeMap<String, Location>>,
     pub deps: Option<BTreeMap<PackageName, DependencyVersion>>,
     pub tsconfig: Option<BTreeMap<String, serde_json::Value>>,
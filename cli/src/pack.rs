@@ -31,6 +31,14 @@ pub fn pack(package: &PackageDefinition) -> Result<String, MycoError> {
         source: e,
     })?;
 
+    debug!("Reading myco.toml for distribution");
+    let raw_toml = std::fs::read_to_string("./myco.toml").map_err(|e| MycoError::ReadFile {
+        path: "./myco.toml".to_string(),
+        source: e,
+    })?;
+
+    // The manifest goes in the archive too, beside the package's directory, so that
+    // installing it reads the manifest the lockfile's integrity covers
     info!("Creating package archive from ./src directory");
     zip_directory(
         "./src",
@@ -38,6 +46,10 @@ pub fn pack(package: &PackageDefinition) -> Result<String, MycoError> {
         ZipOptions {
             strip_prefix: Some("./src".to_string()),
             apply_prefix: Some(package.name.to_string()),
+            extra_files: vec![(
+                format!("{}.toml", package.name),
+                raw_toml.clone().into_bytes(),
+            )],
             ..ZipOptions::default()
         },
     )
@@ -46,11 +58,6 @@ pub fn pack(package: &PackageDefinition) -> Result<String, MycoError> {
     })?;
     debug!("Successfully created package archive");
 
-    debug!("Reading myco.toml for distribution");
-    let raw_toml = std::fs::read_to_string("./myco.toml").map_err(|e| MycoError::ReadFile {
-        path: "./myco.toml".to_string(),
-        source: e,
-    })?;
     debug!("Writing manifest to distribution directory");
     std::fs::write(toml_path, raw_toml).map_err(|e| MycoError::FileWrite {
         path: format!("./dist/{}.toml", package.version),
@@ -5,6 +5,8 @@ use std::rc::Rc;
 
 use rand::{thread_rng, Rng};
 
+use crate::run::ops::filesystem::GlobWalk;
+use crate::run::ops::process::ChildProcess;
 use crate::run::state::MycoState;
 
 pub type Token = String;
@@ -41,10 +43,39 @@ pub enum Capability {
     ExecDir(String),
     FetchUrl(String),
     FetchPrefix(String),
+    // The name of an environment variable that may be read and passed to children
+    EnvVar(String),
+    FileLock(std::fs::File),
+    Child(ChildProcess),
+    GlobWalk(GlobWalk),
     TcpListener(Box<RefCell<tokio::net::TcpListener>>),
     TcpStream(Box<RefCell<tokio::net::TcpStream>>),
 }
 
+impl Capability {
+    /// The kind of token object the runtime rebuilds for this capability when it is
+    /// transferred to a worker. Capabilities tied to a live resource (locks, child
+    /// processes, sockets) cannot be transferred.
+    pub fn transfer_kind(&self) -> Option<&'static str> {
+        match self {
+            Capability::ReadFile(_) => Some("read"),
+            Capability::WriteFile(_) => Some("write"),
+            Capability::ExecFile(_) => Some("exec"),
+            Capability::ReadDir(_) => Some("readDir"),
+            Capability::WriteDir(_) => Some("writeDir"),
+            Capability::ExecDir(_) => Some("execDir"),
+            Capability::FetchUrl(_) => Some("fetch"),
+            Capability::FetchPrefix(_) => Some("fetchPrefix"),
+            Capability::EnvVar(_) => Some("env"),
+            Capability::FileLock(_)
+            | Capability::Child(_)
+            | Capability::GlobWalk(_)
+            | Capability::TcpListener(_)
+            | Capability::TcpStream(_) => None,
+        }
+    }
+}
+
 pub struct CapabilityRegistry {
     capabilities: HashMap<String, Capability>,
 }
@@ -77,6 +108,12 @@ impl CapabilityRegistry {
         token
     }
 
+    /// Registers a capability under a token minted by another isolate's registry,
+    /// for capabilities transferred to a worker.
+    pub fn adopt(&mut self, token: String, capability: Capability) {
+        self.capabilities.insert(token, capability);
+    }
+
     pub fn unregister(&mut self, token: String) -> Option<Capability> {
         let capability = self.capabilities.remove(&token);
         if capability.is_some() {
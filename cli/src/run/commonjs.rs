@@ -0,0 +1,404 @@
+//! CommonJS modules, for `.cjs` files, the `.js` files of packages with
+//! `type = "commonjs"` and the files they require.
+//!
+//! A CommonJS file is wrapped in a function taking `exports`, `require`, `module`,
+//! `__filename` and `__dirname`, and runs once, when it is first imported or required.
+//! Importing it gives `module.exports` as the default export and each of its own
+//! enumerable properties as a named export. Since those names are needed to create the
+//! module, an imported CommonJS file runs as its importer is linked.
+//!
+//! `require` only loads files relative to the requiring one. There are no Node
+//! built-ins to require, since those would be ambient authority.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use log::{debug, trace};
+
+use crate::errors::MycoError;
+use crate::run::modules::{
+    check_package_boundary, confine_json_module, load_and_compile_module, module_resolve_callback,
+    FileType, ImportType,
+};
+use crate::run::ops::macros::throw_js_error;
+use crate::run::state::MycoState;
+
+// The wrapper opens on the file's first line, so only that line's columns are shifted
+const WRAPPER_START: &str = "(function (exports, require, module, __filename, __dirname) {";
+const WRAPPER_END: &str = "\n})";
+
+// What `require("./name")` tries after the path itself, in order
+const REQUIRE_EXTENSIONS: [&str; 3] = [".js", ".cjs", ".json"];
+const REQUIRE_INDEX_FILES: [&str; 3] = ["index.js", "index.cjs", "index.json"];
+
+/// Creates an ES module for a CommonJS file, which is run here so that its exports are
+/// known. A file that throws fails the import.
+pub fn create_commonjs_module<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    module_url: &str,
+    path: &Path,
+) -> Result<v8::Local<'s, v8::Module>, MycoError> {
+    let (exports, names) = {
+        v8::tc_scope!(let scope, scope);
+        let Some(exports) = require_commonjs(scope, path) else {
+            let message = scope
+                .exception()
+                .map(|exception| exception.to_rust_string_lossy(scope))
+                .unwrap_or_else(|| "Unknown error".to_string());
+            return Err(MycoError::CommonJsEvaluation {
+                path: path.display().to_string(),
+                message,
+            });
+        };
+        let names = export_names(scope, exports);
+        (v8::Global::new(scope, exports), names)
+    };
+    trace!("CommonJS module {} exports {:?}", path.display(), names);
+
+    let module_name = v8::String::new(scope, module_url).ok_or(MycoError::V8StringCreation)?;
+    let mut export_strings =
+        vec![v8::String::new(scope, "default").ok_or(MycoError::V8StringCreation)?];
+    for name in &names {
+        export_strings.push(v8::String::new(scope, name).ok_or(MycoError::V8StringCreation)?);
+    }
+    let module = v8::Module::create_synthetic_module(
+        scope,
+        module_name,
+        &export_strings,
+        evaluate_commonjs_module,
+    );
+
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    if let Some(state) = unsafe { state_ptr.as_mut() } {
+        state
+            .commonjs_exports
+            .insert(v8::Global::new(scope, module), (exports, names));
+    }
+
+    Ok(module)
+}
+
+/// Evaluation steps for a CommonJS module: sets `module.exports` as the default export
+/// and its properties as the named exports.
+fn evaluate_commonjs_module<'s>(
+    context: v8::Local<'s, v8::Context>,
+    module: v8::Local<'s, v8::Module>,
+) -> Option<v8::Local<'s, v8::Value>> {
+    v8::callback_scope!(unsafe let scope, context);
+
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    let state = unsafe { state_ptr.as_mut() }?;
+    let (exports, names) = state
+        .commonjs_exports
+        .remove(&v8::Global::new(scope, module))?;
+
+    let exports = v8::Local::new(scope, exports);
+    let default_name = v8::String::new(scope, "default")?;
+    module.set_synthetic_module_export(scope, default_name, exports)?;
+    if let Ok(object) = v8::Local::<v8::Object>::try_from(exports) {
+        for name in names {
+            let name = v8::String::new(scope, &name)?;
+            let value = object.get(scope, name.into())?;
+            module.set_synthetic_module_export(scope, name, value)?;
+        }
+    }
+
+    let resolver = v8::PromiseResolver::new(scope)?;
+    let undefined = v8::undefined(scope);
+    resolver.resolve(scope, undefined.into())?;
+    Some(resolver.get_promise(scope).into())
+}
+
+/// The names to export for `module.exports`: its own enumerable string keys, except
+/// `default`, which is `module.exports` itself.
+fn export_names(scope: &mut v8::PinScope<'_, '_>, exports: v8::Local<v8::Value>) -> Vec<String> {
+    let Ok(object) = v8::Local::<v8::Object>::try_from(exports) else {
+        return Vec::new();
+    };
+    let Some(keys) = object.get_own_property_names(
+        scope,
+        v8::GetPropertyNamesArgsBuilder::new()
+            .key_conversion(v8::KeyConversionMode::ConvertToString)
+            .build(),
+    ) else {
+        return Vec::new();
+    };
+
+    (0..keys.length())
+        .filter_map(|i| keys.get_index(scope, i))
+        .map(|key| key.to_rust_string_lossy(scope))
+        .filter(|name| name != "default")
+        .collect()
+}
+
+/// Runs a CommonJS file unless it already has, and returns its `module.exports`. On
+/// failure an exception is pending.
+fn require_commonjs<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    path: &Path,
+) -> Option<v8::Local<'s, v8::Value>> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let exports_key = v8::String::new(scope, "exports")?;
+
+    // A file required again, even while it is still running, gives its exports so far
+    if let Some(module) = cached_module(scope, &path) {
+        return module.get(scope, exports_key.into());
+    }
+
+    let source = match std::fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) => {
+            let error = MycoError::ReadFile {
+                path: path.display().to_string(),
+                source: e,
+            };
+            throw_js_error(scope, &error.to_string());
+            return None;
+        }
+    };
+    // A shebang line is not JavaScript, but stays a line so that positions hold
+    let source = match source.strip_prefix("#!") {
+        Some(rest) => format!("//{}", rest),
+        None => source,
+    };
+    debug!("Running CommonJS module {}", path.display());
+
+    let module = v8::Object::new(scope);
+    let exports = v8::Object::new(scope);
+    module.set(scope, exports_key.into(), exports.into())?;
+    cache_module(scope, &path, module);
+
+    let filename = v8::String::new(scope, &path.to_string_lossy())?;
+    let dirname = path.parent().unwrap_or(Path::new("/"));
+    let dirname = v8::String::new(scope, &dirname.to_string_lossy())?;
+    let require = v8::Function::builder(require)
+        .data(dirname.into())
+        .build(scope)?;
+
+    let result = compile_wrapper(scope, &path, &source).and_then(|wrapper| {
+        wrapper.call(
+            scope,
+            exports.into(),
+            &[
+                exports.into(),
+                require.into(),
+                module.into(),
+                filename.into(),
+                dirname.into(),
+            ],
+        )
+    });
+    if result.is_none() {
+        // A file that threw runs again if it is required again
+        let state_ptr = scope.get_data(0) as *mut MycoState;
+        if let Some(state) = unsafe { state_ptr.as_mut() } {
+            state.commonjs_modules.remove(&path);
+        }
+        return None;
+    }
+
+    module.get(scope, exports_key.into())
+}
+
+/// Compiles a CommonJS file into the function it is wrapped in.
+fn compile_wrapper<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    path: &Path,
+    source: &str,
+) -> Option<v8::Local<'s, v8::Function>> {
+    let wrapped = format!("{}{}{}", WRAPPER_START, source, WRAPPER_END);
+    let wrapped = v8::String::new(scope, &wrapped)?;
+    let name = v8::String::new(scope, &format!("file://{}", path.to_string_lossy()))?;
+    let origin = v8::ScriptOrigin::new(
+        scope,
+        name.into(),
+        0,     // line_offset
+        0,     // column_offset
+        false, // is_cross_origin
+        -1,    // script_id
+        None,  // source_map_url
+        false, // is_opaque
+        false, // is_wasm
+        false, // is_module
+        None,  // host_defined_options
+    );
+    let script = v8::Script::compile(scope, wrapped, Some(&origin))?;
+    let wrapper = script.run(scope)?;
+    v8::Local::<v8::Function>::try_from(wrapper).ok()
+}
+
+fn cached_module<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    path: &Path,
+) -> Option<v8::Local<'s, v8::Object>> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    let module = unsafe { state_ptr.as_ref() }?.commonjs_modules.get(path)?;
+    Some(v8::Local::new(scope, module))
+}
+
+fn cache_module(scope: &mut v8::PinScope<'_, '_>, path: &Path, module: v8::Local<v8::Object>) {
+    let module = v8::Global::new(scope, module);
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    if let Some(state) = unsafe { state_ptr.as_mut() } {
+        state.commonjs_modules.insert(path.to_path_buf(), module);
+    }
+}
+
+/// `require` for a CommonJS file, whose directory is in the function's data. A `.js`
+/// file is CommonJS, as in Node without `"type": "module"`, while `.mjs` and TypeScript
+/// files are ES modules, which are required as their namespace.
+fn require<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    mut rv: v8::ReturnValue<'s>,
+) {
+    let specifier = args.get(0).to_rust_string_lossy(scope);
+    let dirname = PathBuf::from(args.data().to_rust_string_lossy(scope));
+
+    let path = resolve_require(&specifier, &dirname)
+        .and_then(|path| check_package_boundary(scope, &path, Some(&dirname)).map(|_| path));
+    let path = match path {
+        Ok(path) => path,
+        Err(e) => {
+            throw_js_error(scope, &e.to_string());
+            return;
+        }
+    };
+    trace!("Requiring {} as {}", specifier, path.display());
+
+    let is_mjs = path.extension().is_some_and(|extension| extension == "mjs");
+    let exports = match FileType::from_path(&path) {
+        FileType::CommonJs => require_commonjs(scope, &path),
+        FileType::JavaScript if !is_mjs => require_commonjs(scope, &path),
+        FileType::JavaScript | FileType::TypeScript => require_es_module(scope, &specifier, &path),
+        FileType::Json => require_json(scope, &path, &dirname),
+        FileType::Unknown => {
+            let error = MycoError::Require {
+                specifier,
+                message: format!("{} is not a script or JSON file", path.display()),
+            };
+            throw_js_error(scope, &error.to_string());
+            return;
+        }
+    };
+    if let Some(exports) = exports {
+        rv.set(exports);
+    }
+}
+
+/// Finds the file a `require` loads: the path itself, then with each of
+/// `REQUIRE_EXTENSIONS`, then a directory's index file.
+fn resolve_require(specifier: &str, dirname: &Path) -> Result<PathBuf, MycoError> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return Err(MycoError::Require {
+            specifier: specifier.to_string(),
+            message: "only relative paths can be required, and there are no Node built-ins"
+                .to_string(),
+        });
+    }
+
+    let path = dirname.join(specifier);
+    let with_extensions = REQUIRE_EXTENSIONS.iter().map(|extension| {
+        let mut with_extension = OsString::from(path.as_os_str());
+        with_extension.push(extension);
+        PathBuf::from(with_extension)
+    });
+    let index_files = REQUIRE_INDEX_FILES.iter().map(|index| path.join(index));
+
+    std::iter::once(path.clone())
+        .chain(with_extensions)
+        .chain(index_files)
+        .find(|candidate| candidate.is_file())
+        .ok_or_else(|| MycoError::ModuleNotFound {
+            specifier: specifier.to_string(),
+            resolved_path: path.display().to_string(),
+        })
+}
+
+/// Parses a required JSON file, once, like a CommonJS file that exports its value. It is
+/// confined like an imported JSON module, to the project or the requiring package.
+fn require_json<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    path: &Path,
+    dirname: &Path,
+) -> Option<v8::Local<'s, v8::Value>> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    if let Some(state) = unsafe { state_ptr.as_ref() } {
+        if let Err(e) = confine_json_module(state, path, dirname) {
+            throw_js_error(scope, &e.to_string());
+            return None;
+        }
+    }
+
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let exports_key = v8::String::new(scope, "exports")?;
+    if let Some(module) = cached_module(scope, &path) {
+        return module.get(scope, exports_key.into());
+    }
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            let error = MycoError::ReadFile {
+                path: path.display().to_string(),
+                source: e,
+            };
+            throw_js_error(scope, &error.to_string());
+            return None;
+        }
+    };
+    let content = v8::String::new(scope, &content)?;
+    let value = v8::json::parse(scope, content)?;
+
+    let module = v8::Object::new(scope);
+    module.set(scope, exports_key.into(), value)?;
+    cache_module(scope, &path, module);
+    Some(value)
+}
+
+/// Loads, links and runs an ES module for `require`, which gives its namespace. A
+/// module that is still waiting on top-level await cannot be required.
+fn require_es_module<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    specifier: &str,
+    path: &Path,
+) -> Option<v8::Local<'s, v8::Value>> {
+    let dirname = path.parent().unwrap_or(Path::new("/"));
+    let module = match load_and_compile_module(
+        scope,
+        &path.to_string_lossy(),
+        dirname,
+        ImportType::JavaScript,
+    ) {
+        Ok(module) => module,
+        Err(e) => {
+            throw_js_error(scope, &e.to_string());
+            return None;
+        }
+    };
+
+    module.instantiate_module(scope, module_resolve_callback)?;
+    let evaluation = module.evaluate(scope)?;
+    let Ok(evaluation) = v8::Local::<v8::Promise>::try_from(evaluation) else {
+        return Some(module.get_module_namespace());
+    };
+
+    // The exception is rethrown from `require`, so the evaluation's is handled
+    evaluation.mark_as_handled();
+    match evaluation.state() {
+        v8::PromiseState::Fulfilled => Some(module.get_module_namespace()),
+        v8::PromiseState::Rejected => {
+            let exception = evaluation.result(scope);
+            scope.throw_exception(exception);
+            None
+        }
+        v8::PromiseState::Pending => {
+            let error = MycoError::Require {
+                specifier: specifier.to_string(),
+                message: "it uses top-level await, so it must be imported".to_string(),
+            };
+            throw_js_error(scope, &error.to_string());
+            None
+        }
+    }
+}
@@ -1,4 +1,5 @@
-// Runtime snapshot is empty for now since we're not using snapshots yet
+// Startup snapshot holding the evaluated runtime, written by `build.rs`. Empty when
+// the `snapshot` feature is off, in which case the runtime is evaluated from source.
 pub static RUNTIME_SNAPSHOT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/MYCO_SNAPSHOT.bin"));
 
 #[repr(C, align(16))]
@@ -1,16 +1,25 @@
-use log::{debug, info, trace, warn};
+use log::{debug, info, trace};
 use std::path::PathBuf;
-use std::sync::Once;
+use std::sync::{Arc, Once};
 use tokio::sync::mpsc;
 
+use crate::cache::{CodeCache, TranspileCache};
+use crate::deps::InstalledPackages;
 use crate::errors::MycoError;
+use crate::manifest::import_map::ImportMap;
 use crate::manifest::myco_local::MycoLocalToml;
 use crate::run::constants::{ICU_DATA, RUNTIME_SNAPSHOT};
+use crate::run::errors::promise_reject_callback;
 use crate::run::event_loop::run_event_loop;
 use crate::run::inspector;
-use crate::run::modules::{host_import_module_dynamically_callback, load_and_run_module, FileType};
+use crate::run::limits::{near_heap_limit_callback, Limits, Watchdog};
+use crate::run::modules::{
+    host_import_module_dynamically_callback, load_and_run_module, store_code_caches, FileType,
+};
 use crate::run::ops;
-use crate::run::state::{DebugOptions, MycoState};
+use crate::run::ops::convert::ToV8;
+use crate::run::state::{MycoState, RunOptions};
+use crate::run::worker::{TransferredToken, WorkerInit};
 
 static V8_INIT: Once = Once::new();
 
@@ -32,11 +41,43 @@ macro_rules! inspector_debug {
 pub async fn run_js(
     file_path: &PathBuf,
     myco_local: Option<MycoLocalToml>,
-    debug_options: Option<DebugOptions>,
+    import_map: Option<ImportMap>,
+    packages: Option<InstalledPackages>,
+    options: RunOptions,
+) -> Result<i32, MycoError> {
+    run_isolate(file_path, myco_local, import_map, packages, options, None).await
+}
+
+/// Runs a worker's module in a new isolate on the current thread, which must not
+/// be running any other isolate.
+pub async fn run_worker(file_path: &PathBuf, mut init: WorkerInit) -> Result<i32, MycoError> {
+    let options = init.options.clone();
+    let myco_local = init.myco_local.take();
+    let import_map = init.import_map.take();
+    let packages = init.packages.take();
+    run_isolate(
+        file_path,
+        myco_local,
+        import_map,
+        packages,
+        options,
+        Some(init),
+    )
+    .await
+}
+
+async fn run_isolate(
+    file_path: &PathBuf,
+    myco_local: Option<MycoLocalToml>,
+    import_map: Option<ImportMap>,
+    packages: Option<InstalledPackages>,
+    options: RunOptions,
+    worker: Option<WorkerInit>,
 ) -> Result<i32, MycoError> {
     info!("Starting JavaScript execution for: {}", file_path.display());
     debug!("Myco local configuration: {:?}", myco_local.is_some());
-    debug!("Debug options: {:?}", debug_options);
+    debug!("Run options: {:?}", options);
+    let debug_options = options.debug;
 
     // Initialize V8 (only once per process)
     V8_INIT.call_once(|| {
@@ -54,7 +95,38 @@ pub async fn run_js(
     });
 
     debug!("Creating V8 isolate");
-    let mut isolate = v8::Isolate::new(Default::default());
+    let params = if RUNTIME_SNAPSHOT.is_empty() {
+        debug!("No runtime snapshot; the runtime will be evaluated from source");
+        v8::CreateParams::default()
+    } else {
+        debug!(
+            "Restoring runtime snapshot ({} bytes)",
+            RUNTIME_SNAPSHOT.len()
+        );
+        v8::CreateParams::default().snapshot_blob(v8::StartupData::from(RUNTIME_SNAPSHOT))
+    };
+    let params = match options.max_heap_mb {
+        Some(max_heap_mb) => {
+            debug!("Limiting the heap to {} MB", max_heap_mb);
+            params.heap_limits(0, max_heap_mb * 1024 * 1024)
+        }
+        None => params,
+    };
+    let mut isolate = v8::Isolate::new(params);
+
+    // Running out of heap, or time, stops the script instead of the process
+    let limits = Arc::new(Limits::new(isolate.thread_safe_handle()));
+    isolate.add_near_heap_limit_callback(
+        near_heap_limit_callback,
+        Arc::as_ptr(&limits) as *mut std::ffi::c_void,
+    );
+    let _watchdog = match options.timeout {
+        Some(timeout) => {
+            debug!("Starting watchdog with a timeout of {:?}", timeout);
+            Some(Watchdog::start(limits.clone(), timeout)?)
+        }
+        None => None,
+    };
 
     // Set up inspector if debugging is enabled
     let inspector_rx = if let Some(debug_opts) = debug_options.as_ref() {
@@ -67,6 +139,7 @@ pub async fn run_js(
         let (session_tx, session_rx) = mpsc::channel(1);
         let inspector_server = inspector::Inspector::new(debug_opts, session_tx);
         inspector_server.start();
+        let activity = inspector_server.activity();
 
         info!("Inspector server started on port {}", debug_opts.port);
 
@@ -76,7 +149,7 @@ pub async fn run_js(
             debug!("Inspector ready - debugger can connect at any time");
         }
 
-        Some(session_rx)
+        Some((session_rx, activity))
     } else {
         debug!("No debug options provided, running without inspector");
         None
@@ -86,6 +159,10 @@ pub async fn run_js(
     debug!("Setting up dynamic import callback");
     isolate.set_host_import_module_dynamically_callback(host_import_module_dynamically_callback);
 
+    // Track promises that are rejected with nobody to handle them
+    debug!("Setting up promise rejection tracking");
+    isolate.set_promise_reject_callback(promise_reject_callback);
+
     // Get the current runtime handle to pass to MycoState
     debug!("Getting current Tokio runtime handle");
     let runtime_handle = tokio::runtime::Handle::current();
@@ -93,31 +170,53 @@ pub async fn run_js(
     // Store state in isolate data
     debug!("Creating Myco runtime state");
     let mut state = MycoState::new(myco_local, runtime_handle);
+    state.import_map = import_map;
+    state.packages = packages;
+    state.log_format = options.log_format;
+    state.unhandled_rejections = options.unhandled_rejections;
+    state.max_heap_mb = options.max_heap_mb;
+    // The state is never freed, so the near-heap-limit callback's data stays valid
+    state.limits = Some(limits);
+    // The working directory is the project root by now
+    match std::env::current_dir() {
+        Ok(project_dir) => {
+            state.transpile_cache = Some(TranspileCache::new(&project_dir));
+            state.code_cache = Some(CodeCache::new(&project_dir));
+            state.project_dir = Some(project_dir.canonicalize().unwrap_or(project_dir));
+        }
+        Err(e) => debug!("Transpile and code caches disabled: {}", e),
+    }
+    let transferred = worker.map(|init| {
+        debug!("Installing worker {} state", init.id);
+        init.install(&mut state, &mut isolate)
+    });
 
     // Create inspector first, before any scopes, to avoid borrow conflicts
-    let inspector =
-        if let (Some(session_rx), Some(debug_opts)) = (inspector_rx, debug_options.as_ref()) {
-            debug!("Creating inspector with V8 context");
-            // Create a temporary scope just to create the context
-            let global_context = {
-                v8::scope!(let temp_scope, &mut isolate);
-                let context = v8::Context::new(temp_scope, Default::default());
-                v8::Global::new(temp_scope, context)
-            }; // The scope is dropped here to release the borrow
-
-            debug!("Initializing Myco inspector");
-            // Now create the inspector with the isolate outside of any scope
-            Some(inspector::MycoInspector::new(
-                &mut isolate,
-                global_context,
-                session_rx,
-                debug_opts.break_on_start,
-                debug_opts.wait_for_connection,
-            ))
-        } else {
-            debug!("No inspector needed, running without debugging");
-            None
-        };
+    let inspector = if let (Some((session_rx, activity)), Some(debug_opts)) =
+        (inspector_rx, debug_options.as_ref())
+    {
+        debug!("Creating inspector with V8 context");
+        // Create a temporary scope just to create the context
+        let global_context = {
+            v8::scope!(let temp_scope, &mut isolate);
+            let context = v8::Context::new(temp_scope, Default::default());
+            v8::Global::new(temp_scope, context)
+        }; // The scope is dropped here to release the borrow
+
+        debug!("Initializing Myco inspector");
+        // Now create the inspector with the isolate outside of any scope
+        Some(inspector::MycoInspector::new(
+            &mut isolate,
+            global_context,
+            session_rx,
+            activity,
+            debug_opts.break_on_start,
+            debug_opts.wait_for_connection,
+        ))
+    } else {
+        debug!("No inspector needed, running without debugging");
+        None
+    };
 
     state.inspector = inspector;
     debug!("Storing state in V8 isolate");
@@ -171,13 +270,8 @@ pub async fn run_js(
     let (myco_ops, partial_myco) = ops::register_ops(scope)?;
     info!("JavaScript runtime operations registered");
 
-    // Snapshots are not implemented yet; RUNTIME_SNAPSHOT is an empty stub, so the
-    // script path is what actually runs.
-    if !RUNTIME_SNAPSHOT.is_empty() {
-        warn!("Runtime snapshot is present but snapshot bootstrap is unimplemented; falling back to script evaluation");
-    }
-    debug!("Executing runtime code to build the powerbox");
-    let myco_powerbox = execute_runtime_code(scope, myco_ops, partial_myco)?;
+    debug!("Calling the runtime factory to build the powerbox");
+    let myco_powerbox = build_powerbox(scope, myco_ops, partial_myco, transferred)?;
     let myco_powerbox = v8::Global::new(scope, myco_powerbox);
     debug!("Runtime code executed successfully; powerbox held by Rust");
 
@@ -187,11 +281,12 @@ pub async fn run_js(
     info!("File type detected: {:?}", file_type);
 
     let is_module = match file_type {
-        FileType::TypeScript | FileType::JavaScript => {
+        FileType::TypeScript | FileType::JavaScript | FileType::CommonJs => {
             info!("Loading file as ES module");
             // Compile, instantiate and evaluate the user module directly, then call its
             // default export with the powerbox.
-            load_and_run_module(scope, file_path, &myco_powerbox)?;
+            load_and_run_module(scope, file_path, &myco_powerbox)
+                .map_err(|e| limit_error_or(scope, e))?;
             debug!("ES module loaded and entry point scheduled");
             true
         }
@@ -215,11 +310,12 @@ pub async fn run_js(
             })?;
 
             debug!("Executing compiled script");
-            script
-                .run(scope)
-                .ok_or_else(|| MycoError::ScriptExecution {
+            if script.run(scope).is_none() {
+                let error = MycoError::ScriptExecution {
                     message: "Failed to run user script".to_string(),
-                })?;
+                };
+                return Err(limit_error_or(scope, error));
+            }
 
             info!("Script executed successfully");
             false
@@ -228,11 +324,26 @@ pub async fn run_js(
 
     // Run the event loop
     debug!("Starting event loop");
-    run_event_loop(scope).await?;
+    if let Err(e) = run_event_loop(scope).await {
+        return Err(limit_error_or(scope, e));
+    }
     debug!("Event loop completed");
 
-    // Extract the exit code recorded by the entry-point promise chain (modules only)
-    let exit_code = if is_module {
+    store_code_caches(scope);
+
+    // Workers this isolate started do not outlive it
+    let state = unsafe { &mut *(scope.get_data(0) as *mut MycoState) };
+    for worker in state.workers.values() {
+        worker.terminate();
+    }
+
+    // An explicit `Myco.process.exit` wins; otherwise use the exit code recorded by
+    // the entry-point promise chain (modules only)
+    let exit_requested = unsafe { (*(scope.get_data(0) as *const MycoState)).exit_requested };
+    let exit_code = if let Some(exit_code) = exit_requested {
+        debug!("Exit requested with code: {}", exit_code);
+        exit_code
+    } else if is_module {
         debug!("Extracting exit code recorded by the entry point");
         let state_ptr = scope.get_data(0) as *mut MycoState;
         if state_ptr.is_null() {
@@ -254,15 +365,62 @@ pub async fn run_js(
     Ok(exit_code)
 }
 
-/// Evaluates the transpiled runtime script. Its completion value is a factory function;
-/// calling it with `MycoOps` and the partial `Myco` object yields the powerbox, which is
-/// returned here so Rust can hold it. The runtime still installs the deliberately-ambient
-/// globals (`console`, `TextEncoder`, `TextDecoder`, `TOML`, ...) itself.
-fn execute_runtime_code<'s>(
+/// A script stopped for going over a limit fails with whatever it was doing at the
+/// time, so the limit is reported instead of that failure.
+fn limit_error_or(scope: &mut v8::PinScope<'_, '_>, error: MycoError) -> MycoError {
+    let state = unsafe { &*(scope.get_data(0) as *const MycoState) };
+    state
+        .limits
+        .as_ref()
+        .and_then(|limits| limits.error(scope))
+        .unwrap_or(error)
+}
+
+/// Calls the runtime factory with `MycoOps` and the partial `Myco` object, yielding the
+/// powerbox, which is returned here so Rust can hold it. In a worker, the factory
+/// also gets the tokens transferred to it, and builds a powerbox that can only use
+/// those.
+fn build_powerbox<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     myco_ops: v8::Local<v8::Object>,
     partial_myco: v8::Local<v8::Object>,
+    transferred: Option<Vec<Vec<TransferredToken>>>,
 ) -> Result<v8::Local<'s, v8::Value>, MycoError> {
+    let factory = if RUNTIME_SNAPSHOT.is_empty() {
+        execute_runtime_code(scope)?
+    } else {
+        // The build stores the factory as the context's first snapshot data, so it is
+        // never reachable from `globalThis`.
+        trace!("Taking runtime factory from the snapshot");
+        scope
+            .get_context_data_from_snapshot_once::<v8::Function>(0)
+            .map_err(|_| MycoError::RuntimeExecution)?
+    };
+
+    trace!("Invoking runtime factory to build the powerbox");
+    let undefined = v8::undefined(scope);
+    let transferred = match transferred {
+        Some(transferred) => transferred.to_v8(scope),
+        None => undefined.into(),
+    };
+    let powerbox = factory
+        .call(
+            scope,
+            undefined.into(),
+            &[myco_ops.into(), partial_myco.into(), transferred],
+        )
+        .ok_or(MycoError::RuntimeExecution)?;
+
+    debug!("Powerbox built");
+    Ok(powerbox)
+}
+
+/// Evaluates the transpiled runtime script, for builds without a startup snapshot. Its
+/// completion value is the runtime factory; evaluating it also installs the
+/// deliberately-ambient globals (`console`, `TextEncoder`, `TextDecoder`, `TOML`, ...).
+fn execute_runtime_code<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+) -> Result<v8::Local<'s, v8::Function>, MycoError> {
     // Read the transpiled runtime code
     debug!("Loading runtime JavaScript code");
     let runtime_code = include_str!(concat!(env!("OUT_DIR"), "/runtime.js"));
@@ -275,18 +433,6 @@ fn execute_runtime_code<'s>(
     trace!("Executing runtime code");
     let completion_value = script.run(scope).ok_or(MycoError::RuntimeExecution)?;
 
-    trace!("Invoking runtime factory to build the powerbox");
-    let factory = v8::Local::<v8::Function>::try_from(completion_value)
-        .map_err(|_| MycoError::RuntimeExecution)?;
-    let undefined = v8::undefined(scope);
-    let powerbox = factory
-        .call(
-            scope,
-            undefined.into(),
-            &[myco_ops.into(), partial_myco.into()],
-        )
-        .ok_or(MycoError::RuntimeExecution)?;
-
     debug!("Runtime code execution completed");
-    Ok(powerbox)
+    v8::Local::<v8::Function>::try_from(completion_value).map_err(|_| MycoError::RuntimeExecution)
 }
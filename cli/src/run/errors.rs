@@ -1,6 +1,41 @@
 use crate::run::stack_trace;
+use crate::run::state::MycoState;
 use log::{debug, trace, warn};
 
+/// Tracks promises rejected while nothing handles them. A handler attached later, even
+/// in the same microtask checkpoint, takes the promise off the list again, so only the
+/// ones still unhandled when the event loop looks are reported.
+pub extern "C" fn promise_reject_callback(message: v8::PromiseRejectMessage) {
+    v8::callback_scope!(unsafe let scope, &message);
+
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    let Some(state) = (unsafe { state_ptr.as_mut() }) else {
+        return;
+    };
+    let promise = message.get_promise();
+
+    match message.get_event() {
+        v8::PromiseRejectEvent::PromiseRejectWithNoHandler => {
+            let reason = message
+                .get_value()
+                .unwrap_or_else(|| v8::undefined(scope).into());
+            trace!("Promise rejected with no handler");
+            state.pending_rejections.push((
+                v8::Global::new(scope, promise),
+                v8::Global::new(scope, reason),
+            ));
+        }
+        v8::PromiseRejectEvent::PromiseHandlerAddedAfterReject => {
+            trace!("Handler added to a rejected promise");
+            state
+                .pending_rejections
+                .retain(|(pending, _)| *pending != promise);
+        }
+        // Settling a promise twice is not an error, and V8 ignores it
+        _ => {}
+    }
+}
+
 pub fn get_exception_message_with_stack(
     scope: &mut v8::PinScope<'_, '_>,
     exception: v8::Local<v8::Value>,
@@ -1,8 +1,12 @@
 use crate::errors::MycoError;
 use crate::run::errors::get_exception_message_with_stack;
-use crate::run::state::MycoState;
+use crate::run::limits::Limits;
+use crate::run::ops::convert::StructuredClone;
+use crate::run::state::FinalOpResult;
+use crate::run::state::{MycoState, PortMessage, UnhandledRejections};
 use log::{debug, error, info, trace, warn};
-use std::time::{Duration, Instant};
+use std::io::{self, Write};
+use std::time::Instant;
 
 // Macro for inspector debug logging
 #[cfg(feature = "inspector-debug")]
@@ -19,202 +23,446 @@ macro_rules! inspector_debug {
     };
 }
 
-pub async fn run_event_loop(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
-    info!("Starting JavaScript event loop");
-    let mut consecutive_empty_rounds = 0;
-    let max_empty_rounds = 10;
-    let mut total_rounds = 0;
+/// What woke the event loop up while it was waiting.
+enum Wake {
+    Op(FinalOpResult),
+    Signal(i32),
+    Port(PortMessage),
+    Timer,
+    Inspector,
+    Limit,
+}
 
-    // Extract the op receiver from the state (we can only do this once)
-    debug!("Extracting operation receiver from isolate state");
-    let mut op_receiver = {
-        let state_ptr = scope.get_data(0) as *mut MycoState;
-        if state_ptr.is_null() {
-            return Err(MycoError::EventLoop {
-                message: "Failed to get isolate state".to_string(),
-            });
-        }
-        let state = unsafe { &mut *state_ptr };
-        state
-            .op_receiver
-            .take()
-            .ok_or_else(|| MycoError::EventLoop {
-                message: "Op receiver already taken".to_string(),
-            })?
-    };
-    debug!("Operation receiver extracted successfully");
+fn state<'a>(scope: &mut v8::PinScope<'_, '_>) -> Result<&'a mut MycoState, MycoError> {
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    if state_ptr.is_null() {
+        return Err(MycoError::EventLoop {
+            message: "Failed to get isolate state".to_string(),
+        });
+    }
+    Ok(unsafe { &mut *state_ptr })
+}
 
-    loop {
-        total_rounds += 1;
-        trace!("Event loop round #{}", total_rounds);
+/// The loop keeps running for as long as something can still call back into JS.
+/// Unref'd timers and immediates and the inspector do not count, but signal
+/// listeners do until they are removed. Running workers count through the pending
+/// op that waits for them to exit, and a worker stays alive while it listens for
+/// messages from its parent.
+fn is_alive(state: &MycoState) -> bool {
+    !state.pending_ops.is_empty()
+        || state.timers.has_refs()
+        || state.immediates.has_refs()
+        || !state.signal_listeners.is_empty()
+        || state
+            .parent_port
+            .as_ref()
+            .is_some_and(|parent| parent.on_message.is_some())
+}
+
+/// Whether a message that arrived before its handler was set can now be delivered.
+fn has_deliverable_messages(state: &MycoState) -> bool {
+    let parent = state
+        .parent_port
+        .as_ref()
+        .is_some_and(|parent| parent.on_message.is_some() && !parent.queued.is_empty());
+    parent
+        || state
+            .workers
+            .values()
+            .any(|worker| worker.on_message.is_some() && !worker.queued.is_empty())
+}
 
-        // Check for unhandled errors that were recorded by the promise rejection handler
-        trace!("Checking for unhandled promise rejections");
-        let unhandled_error = {
-            let state_ptr = scope.get_data(0) as *mut MycoState;
-            if state_ptr.is_null() {
-                None
-            } else {
-                let state = unsafe { &mut *state_ptr };
-                state.unhandled_error.take()
+/// Calls a callback on behalf of the event loop. Nothing above it can catch what it
+/// throws, so an exception is recorded as the process's unhandled error.
+fn call_callback<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    callback: v8::Local<'s, v8::Function>,
+    args: &[v8::Local<'s, v8::Value>],
+) -> Result<(), MycoError> {
+    v8::tc_scope!(let scope, scope);
+    let global = scope.get_current_context().global(scope);
+    if callback.call(scope, global.into(), args).is_some() || scope.has_terminated() {
+        return Ok(());
+    }
+    if let Some(exception) = scope.exception() {
+        let exception = v8::Global::new(scope, exception);
+        state(scope)?.unhandled_error.get_or_insert(exception);
+    }
+    Ok(())
+}
+
+fn resolve_op(scope: &mut v8::PinScope<'_, '_>, op_result: FinalOpResult) -> Result<(), MycoError> {
+    let op_id = op_result.get_op_id();
+    trace!("Processing async operation result (op_id: {})", op_id);
+
+    // Find and resolve the corresponding promise
+    if let Some(resolver_global) = state(scope)?.complete_pending_op(op_id) {
+        let resolver = v8::Local::new(scope, &resolver_global);
+        op_result.resolve_promise(scope, resolver);
+    } else {
+        warn!("No pending promise found for op_id: {}", op_id);
+    }
+    Ok(())
+}
+
+/// Calls the listeners for `signal` in registration order. A signal nobody
+/// listens for any more gets its default action, which is to exit with 128 +
+/// signal; the runtime's default handler runs the `beforeExit` hooks first.
+fn dispatch_signal(scope: &mut v8::PinScope<'_, '_>, signal: i32) -> Result<(), MycoError> {
+    debug!("Dispatching signal {}", signal);
+    let state = state(scope)?;
+    let mut listeners: Vec<_> = state
+        .signal_listeners
+        .iter()
+        .filter(|(_, listener)| listener.signal == signal)
+        .map(|(id, listener)| (*id, listener.name.clone(), listener.callback.clone()))
+        .collect();
+    if listeners.is_empty() {
+        let code = 128 + signal;
+        match state.signal_default.clone() {
+            Some(handler) => {
+                let handler = v8::Local::new(scope, &handler);
+                let code = v8::Integer::new(scope, code);
+                call_callback(scope, handler, &[code.into()])?;
+            }
+            None => {
+                state.exit_requested.get_or_insert(code);
             }
-        };
-        if let Some(error_value) = unhandled_error {
-            let error_value = v8::Local::new(scope, &error_value);
-            let error_message = get_exception_message_with_stack(scope, error_value);
-            return Err(MycoError::UnhandledError {
-                message: error_message,
-            });
         }
+        return Ok(());
+    }
+    listeners.sort_by_key(|(id, _, _)| *id);
 
-        let mut executed_any_timer = false;
-        let mut processed_any_op = false;
+    for (_, name, callback) in listeners {
+        let callback = v8::Local::new(scope, &callback);
+        let name = v8::String::new(scope, &name).ok_or(MycoError::V8StringCreation)?;
+        call_callback(scope, callback, &[name.into()])?;
+    }
+    Ok(())
+}
 
-        // Poll for completed async operations (non-blocking)
-        trace!("Polling for completed async operations");
-        let mut op_count = 0;
-        while let Ok(op_result) = op_receiver.try_recv() {
-            op_count += 1;
-            processed_any_op = true;
-            trace!(
-                "Processing async operation result (op_id: {})",
-                op_result.get_op_id()
-            );
-
-            let state_ptr = scope.get_data(0) as *mut MycoState;
-            if !state_ptr.is_null() {
-                let state = unsafe { &mut *state_ptr };
-
-                // Find and resolve the corresponding promise
-                if let Some(resolver_global) = state.complete_pending_op(op_result.get_op_id()) {
-                    let resolver = v8::Local::new(scope, &resolver_global);
-                    op_result.resolve_promise(scope, resolver);
-                } else {
-                    warn!(
-                        "No pending promise found for op_id: {}",
-                        op_result.get_op_id()
-                    );
+fn deliver_message(
+    scope: &mut v8::PinScope<'_, '_>,
+    handler: &v8::Global<v8::Function>,
+    data: Option<Vec<u8>>,
+) -> Result<(), MycoError> {
+    let message = match data {
+        Some(data) => match StructuredClone::deserialize(scope, &data) {
+            Some(message) => message,
+            None => {
+                error!("Failed to deserialize worker message");
+                return Ok(());
+            }
+        },
+        None => v8::undefined(scope).into(),
+    };
+    let handler = v8::Local::new(scope, handler);
+    call_callback(scope, handler, &[message])
+}
+
+fn handle_port_message(
+    scope: &mut v8::PinScope<'_, '_>,
+    message: PortMessage,
+) -> Result<(), MycoError> {
+    let state = state(scope)?;
+    match message {
+        PortMessage::FromWorker { id, data } => {
+            // Messages from a worker that is already gone are dropped
+            let Some(worker) = state.workers.get_mut(&id) else {
+                return Ok(());
+            };
+            match &worker.on_message {
+                Some(handler) => {
+                    let handler = handler.clone();
+                    deliver_message(scope, &handler, data)?;
                 }
+                None => worker.queued.push(data),
             }
         }
-        if op_count > 0 {
-            debug!("Processed {} async operation results", op_count);
-        }
-
-        // Check for and execute ready timers
-        let now = Instant::now();
-        let state_ptr = scope.get_data(0) as *mut MycoState;
-        if !state_ptr.is_null() {
-            let state = unsafe { &mut *state_ptr };
-
-            // Poll inspector sessions if we have one
-            if let Some(inspector_rc) = &state.inspector {
-                let mut inspector = inspector_rc.borrow_mut();
-                match inspector.poll_sessions() {
-                    Ok(()) => {
-                        // Inspector processing completed normally
-                    }
-                    Err(_e) => {
-                        inspector_debug!("Inspector error: {:?}", _e);
-                    }
+        PortMessage::WorkerExited { id } => {
+            debug!("Worker {} exited", id);
+            state.workers.remove(&id);
+        }
+        PortMessage::FromParent(data) => {
+            let Some(parent) = state.parent_port.as_mut() else {
+                return Ok(());
+            };
+            match &parent.on_message {
+                Some(handler) => {
+                    let handler = handler.clone();
+                    deliver_message(scope, &handler, data)?;
                 }
+                None => parent.queued.push(data),
             }
+        }
+        PortMessage::Terminate => {
+            debug!("Worker terminated by its parent");
+            state.exit_requested.get_or_insert(1);
+        }
+    }
+    Ok(())
+}
 
-            // Find ready timers (execute_at <= now)
-            let mut ready_timers = Vec::new();
-            let mut remaining_timers = Vec::new();
-
-            for timer in state.timers.drain(..) {
-                if timer.execute_at <= now {
-                    ready_timers.push(timer);
-                } else {
-                    remaining_timers.push(timer);
-                }
+/// Delivers messages that were queued while their port had no handler, oldest
+/// first, before anything that arrived later.
+fn deliver_queued_messages(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    let state = state(scope)?;
+    if let Some(parent) = state.parent_port.as_mut() {
+        if let Some(handler) = parent.on_message.clone() {
+            for data in std::mem::take(&mut parent.queued) {
+                deliver_message(scope, &handler, data)?;
+            }
+        }
+    }
+    let mut ids: Vec<u32> = state.workers.keys().copied().collect();
+    ids.sort();
+    for id in ids {
+        let Some(worker) = state.workers.get_mut(&id) else {
+            continue;
+        };
+        if let Some(handler) = worker.on_message.clone() {
+            for data in std::mem::take(&mut worker.queued) {
+                deliver_message(scope, &handler, data)?;
             }
+        }
+    }
+    Ok(())
+}
 
-            // Put back the remaining timers
-            state.timers = remaining_timers;
+/// Runs the immediates queued before this round, in order, ahead of any timer.
+fn run_immediates(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    let limit = state(scope)?.next_timer_id;
+    while let Some((id, callback)) = state(scope)?.immediates.pop_before(limit) {
+        trace!("Running immediate {}", id);
+        let callback = v8::Local::new(scope, &callback);
+        call_callback(scope, callback, &[])?;
+        if state(scope)?.unhandled_error.is_some() {
+            break;
+        }
+        scope.perform_microtask_checkpoint();
+    }
+    Ok(())
+}
 
-            // Execute ready timers
-            for timer in ready_timers {
-                executed_any_timer = true;
+/// Runs every timer that is due at `now`, with a microtask checkpoint after each
+/// one. Timers scheduled by these callbacks wait for the next round, even with a
+/// zero delay.
+fn run_due_timers(scope: &mut v8::PinScope<'_, '_>, now: Instant) -> Result<(), MycoError> {
+    while let Some((id, callback)) = state(scope)?.timers.pop_due(now) {
+        trace!("Running timer {}", id);
+        let callback = v8::Local::new(scope, &callback);
+        call_callback(scope, callback, &[])?;
+        // An uncaught exception ends the process, so later timers do not run
+        if state(scope)?.unhandled_error.is_some() {
+            break;
+        }
+        scope.perform_microtask_checkpoint();
+    }
+    Ok(())
+}
 
-                let callback_local = v8::Local::new(scope, &timer.callback);
-                let global = scope.get_current_context().global(scope);
+/// Reports the promises that were rejected with no handler and still have none once
+/// the microtask queue has drained. Listeners registered with
+/// `Myco.process.onUnhandledRejection` get each one; without any, `strict` fails the
+/// process with the first and `warn` reports them all and carries on.
+fn report_unhandled_rejections(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    let state = state(scope)?;
+    if state.pending_rejections.is_empty() {
+        return Ok(());
+    }
+    let rejections = std::mem::take(&mut state.pending_rejections);
+    let mut listeners: Vec<_> = state
+        .rejection_listeners
+        .iter()
+        .map(|(id, listener)| (*id, listener.clone()))
+        .collect();
+    listeners.sort_by_key(|(id, _)| *id);
+    let mode = state.unhandled_rejections;
+    debug!("{} unhandled promise rejections", rejections.len());
 
-                if callback_local.call(scope, global.into(), &[]).is_none() {
-                    eprintln!("Timer {} callback execution failed", timer.id);
-                }
+    for (promise, reason) in rejections {
+        let promise = v8::Local::new(scope, &promise);
+        let reason = v8::Local::new(scope, &reason);
+        if !listeners.is_empty() {
+            for (_, listener) in &listeners {
+                let listener = v8::Local::new(scope, listener);
+                call_callback(scope, listener, &[reason, promise.into()])?;
             }
+            continue;
         }
 
-        // Process microtasks
-        scope.perform_microtask_checkpoint();
+        let message = get_exception_message_with_stack(scope, reason);
+        match mode {
+            UnhandledRejections::Strict => return Err(MycoError::UnhandledRejection { message }),
+            UnhandledRejections::Warn => {
+                // Shown whatever `--log-level` is, the way a script's own
+                // console output is; a closed pipe is not worth failing over
+                let _ = writeln!(
+                    io::stderr(),
+                    "Warning: Unhandled promise rejection: {}",
+                    message
+                );
+            }
+        }
+    }
+
+    // Whatever the listeners queued runs before the loop goes on
+    scope.perform_microtask_checkpoint();
+    Ok(())
+}
+
+/// Stops the loop once the run has gone over a limit. The error itself is worked out
+/// by the engine, which reports it however the loop ends.
+fn check_limits(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    let exceeded = state(scope)?
+        .limits
+        .as_ref()
+        .is_some_and(|limits| limits.is_exceeded());
+    if exceeded {
+        return Err(MycoError::EventLoop {
+            message: "Run stopped at a limit".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn check_unhandled_error(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    // Recorded by the promise rejection handler
+    if let Some(error_value) = state(scope)?.unhandled_error.take() {
+        let error_value = v8::Local::new(scope, &error_value);
+        let error_message = get_exception_message_with_stack(scope, error_value);
+        return Err(MycoError::UnhandledError {
+            message: error_message,
+        });
+    }
+    Ok(())
+}
+
+pub async fn run_event_loop(scope: &mut v8::PinScope<'_, '_>) -> Result<(), MycoError> {
+    info!("Starting JavaScript event loop");
+    let mut total_rounds = 0;
+
+    // The receivers can only be taken once
+    debug!("Extracting operation receiver from isolate state");
+    let state_ref = state(scope)?;
+    let mut op_receiver = state_ref
+        .op_receiver
+        .take()
+        .ok_or_else(|| MycoError::EventLoop {
+            message: "Op receiver already taken".to_string(),
+        })?;
+    let mut port_receiver = state_ref
+        .port_receiver
+        .take()
+        .ok_or_else(|| MycoError::EventLoop {
+            message: "Port receiver already taken".to_string(),
+        })?;
+    let mut signal_receiver =
+        state_ref
+            .signal_receiver
+            .take()
+            .ok_or_else(|| MycoError::EventLoop {
+                message: "Signal receiver already taken".to_string(),
+            })?;
+    let limits = state_ref.limits.clone();
+    let inspector_activity = state_ref
+        .inspector
+        .as_ref()
+        .map(|inspector| inspector.borrow().activity());
+
+    // Whatever ended the previous wait; it is handled first in the next round
+    let mut wake = None;
+
+    loop {
+        total_rounds += 1;
+        trace!("Event loop round #{}", total_rounds);
 
-        // If we executed timers, processed ops, or processed microtasks, reset the empty counter
-        if executed_any_timer || processed_any_op {
-            consecutive_empty_rounds = 0;
-        } else {
-            consecutive_empty_rounds += 1;
+        check_limits(scope)?;
+        check_unhandled_error(scope)?;
+        deliver_queued_messages(scope)?;
+
+        match wake.take() {
+            Some(Wake::Op(op_result)) => resolve_op(scope, op_result)?,
+            Some(Wake::Signal(signal)) => dispatch_signal(scope, signal)?,
+            Some(Wake::Port(message)) => handle_port_message(scope, message)?,
+            Some(Wake::Timer) | Some(Wake::Inspector) | Some(Wake::Limit) | None => {}
         }
 
-        // If we're in early rounds, assume we're still processing
-        if total_rounds < 50 {
-            consecutive_empty_rounds = 0;
+        // Anything else that completed in the meantime
+        while let Ok(op_result) = op_receiver.try_recv() {
+            resolve_op(scope, op_result)?;
+        }
+        while let Ok(signal) = signal_receiver.try_recv() {
+            dispatch_signal(scope, signal)?;
+        }
+        while let Ok(message) = port_receiver.try_recv() {
+            handle_port_message(scope, message)?;
         }
 
-        // Check if we should continue
-        let has_pending_ops = unsafe {
-            let state_ptr = scope.get_data(0) as *mut MycoState;
-            if !state_ptr.is_null() {
-                let state = &*state_ptr;
-                !state.pending_ops.is_empty()
-            } else {
-                false
+        if let Some(inspector_rc) = &state(scope)?.inspector {
+            if let Err(_e) = inspector_rc.borrow_mut().poll_sessions() {
+                inspector_debug!("Inspector error: {:?}", _e);
             }
-        };
+        }
 
-        let has_pending_timers = unsafe {
-            let state_ptr = scope.get_data(0) as *mut MycoState;
-            if !state_ptr.is_null() {
-                let state = &*state_ptr;
-                !state.timers.is_empty()
-            } else {
-                false
-            }
-        };
+        scope.perform_microtask_checkpoint();
+        run_immediates(scope)?;
+        run_due_timers(scope, Instant::now())?;
+        report_unhandled_rejections(scope)?;
+        check_limits(scope)?;
+        check_unhandled_error(scope)?;
 
-        if consecutive_empty_rounds >= max_empty_rounds && !has_pending_ops && !has_pending_timers {
+        // Stop as soon as the script has asked to exit
+        let state = state(scope)?;
+        if state.exit_requested.is_some() {
+            debug!("Exit requested; stopping event loop");
             break;
         }
 
-        // Small yield to allow other tasks to run
-        tokio::task::yield_now().await;
-
-        // If we have pending timers, sleep until the next one is ready
-        if has_pending_timers {
-            let next_timer_delay = unsafe {
-                let state_ptr = scope.get_data(0) as *mut MycoState;
-                if !state_ptr.is_null() {
-                    let state = &*state_ptr;
-                    state
-                        .timers
-                        .iter()
-                        .map(|t| t.execute_at.saturating_duration_since(now))
-                        .min()
-                        .unwrap_or(Duration::from_millis(1))
-                } else {
-                    Duration::from_millis(1)
-                }
-            };
+        if !is_alive(state) {
+            debug!("Nothing left to wait for; stopping event loop");
+            break;
+        }
 
-            // Limit sleep time to avoid hanging
-            let sleep_time = next_timer_delay.min(Duration::from_millis(10));
-            if sleep_time > Duration::from_millis(0) {
-                tokio::time::sleep(sleep_time).await;
-            }
+        // A handler set this round can take its queued messages straight away, and
+        // immediates queued this round run in the next one without waiting
+        if has_deliverable_messages(state) || !state.immediates.is_empty() {
+            continue;
         }
+
+        // Sleep until the next op completes, signal or worker message arrives,
+        // timer is due or debugger message comes in
+        let next_deadline = state.timers.next_deadline();
+        wake = Some(tokio::select! {
+            Some(op_result) = op_receiver.recv() => Wake::Op(op_result),
+            Some(signal) = signal_receiver.recv() => Wake::Signal(signal),
+            Some(message) = port_receiver.recv() => Wake::Port(message),
+            _ = sleep_until(next_deadline) => Wake::Timer,
+            _ = notified(&inspector_activity) => Wake::Inspector,
+            _ = exceeded(&limits) => Wake::Limit,
+        });
     }
 
     Ok(())
 }
+
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn notified(activity: &Option<std::sync::Arc<tokio::sync::Notify>>) {
+    match activity {
+        Some(activity) => activity.notified().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn exceeded(limits: &Option<std::sync::Arc<Limits>>) {
+    match limits {
+        Some(limits) => limits.exceeded().await,
+        None => std::future::pending().await,
+    }
+}
@@ -8,7 +8,7 @@ use std::thread;
 use rand::Rng;
 use serde_json::json;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 use v8::inspector::V8InspectorClient;
 
 use crate::run::DebugOptions;
@@ -37,6 +37,7 @@ pub struct Inspector {
     address: SocketAddr,
     session_tx: mpsc::Sender<InspectorSessionRequest>,
     session_id: String,
+    activity: Arc<Notify>,
 }
 
 pub struct InspectorSessionRequest {
@@ -52,13 +53,21 @@ impl Inspector {
             address,
             session_tx,
             session_id,
+            activity: Arc::new(Notify::new()),
         }
     }
 
+    /// Notified whenever a session connects or a client sends a message, so the
+    /// event loop can sleep until the inspector has work for it.
+    pub fn activity(&self) -> Arc<Notify> {
+        self.activity.clone()
+    }
+
     pub fn start(&self) {
         let address = self.address;
         let session_tx = self.session_tx.clone();
         let session_id = self.session_id.clone();
+        let activity = self.activity.clone();
 
         thread::spawn(move || {
             let rt = tokio::runtime::Builder::new_current_thread()
@@ -71,6 +80,7 @@ impl Inspector {
                     session_tx,
                     session_id: session_id.clone(),
                     port: address.port(),
+                    activity,
                 });
 
                 let listener = match TcpListener::bind(address).await {
@@ -201,6 +211,7 @@ struct InspectorState {
     session_tx: mpsc::Sender<InspectorSessionRequest>,
     session_id: String,
     port: u16,
+    activity: Arc<Notify>,
 }
 
 async fn handle_websocket_connection(
@@ -235,6 +246,7 @@ async fn handle_websocket_connection(
         return;
     }
 
+    inspector.activity.notify_one();
     inspector_debug!("Inspector session registered with V8 runtime");
 
     let write_task = tokio::spawn(async move {
@@ -256,6 +268,7 @@ async fn handle_websocket_connection(
         }
     });
 
+    let activity = inspector.activity.clone();
     let read_task = tokio::spawn(async move {
         loop {
             match reader.next_message().await {
@@ -265,6 +278,7 @@ async fn handle_websocket_connection(
                         inspector_debug!("Failed to send message to V8, runtime probably stopped");
                         break;
                     }
+                    activity.notify_one();
                 }
                 Ok(Frame::Ping(payload)) => {
                     if out_tx.send(Frame::Pong(payload)).is_err() {
@@ -336,6 +350,7 @@ pub struct MycoInspector {
     v8_inspector: Rc<RefCell<Option<v8::inspector::V8Inspector>>>,
     sessions: Vec<MycoSession>,
     session_requests: mpsc::Receiver<InspectorSessionRequest>,
+    activity: Arc<Notify>,
     flags: RefCell<InspectorFlags>,
     break_on_start: bool,
     wait_for_connection: bool,
@@ -388,6 +403,7 @@ impl MycoInspector {
         isolate: &mut v8::Isolate,
         context: v8::Global<v8::Context>,
         session_requests: mpsc::Receiver<InspectorSessionRequest>,
+        activity: Arc<Notify>,
         break_on_start: bool,
         wait_for_connection: bool,
     ) -> Rc<RefCell<Self>> {
@@ -397,6 +413,7 @@ impl MycoInspector {
             v8_inspector: Default::default(),
             sessions: vec![],
             session_requests,
+            activity,
             flags: Default::default(),
             break_on_start,
             wait_for_connection,
@@ -458,6 +475,11 @@ impl MycoInspector {
         // Remove terminated sessions
         self.sessions.retain(|s| !s.terminated);
 
+        // Come back for the rest of the queued messages on the next round
+        if self.sessions.iter().any(|s| !s.message_queue.is_empty()) {
+            self.activity.notify_one();
+        }
+
         Ok(())
     }
 
@@ -480,6 +502,10 @@ impl MycoInspector {
         inspector_debug!("Debugger session connected.");
     }
 
+    pub fn activity(&self) -> Arc<Notify> {
+        self.activity.clone()
+    }
+
     pub fn should_break_on_start(&self) -> bool {
         self.break_on_start
     }
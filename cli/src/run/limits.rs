@@ -0,0 +1,133 @@
+//! Hard limits on a run: the size of an isolate's heap (`--max-heap-mb`) and how long
+//! the script may run (`--timeout`).
+//!
+//! Going over either limit terminates execution in the isolate, which unwinds
+//! whatever JavaScript is running, and the run then fails with the limit it went over
+//! rather than V8 aborting the process or the script running forever.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, warn};
+use tokio::sync::Notify;
+
+use crate::errors::MycoError;
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+/// The most the heap limit is raised to let a stopped script unwind.
+const HEAP_UNWIND_MB: usize = 64;
+
+/// The limit a run went over.
+#[derive(Debug, Clone, Copy)]
+enum Exceeded {
+    Heap,
+    Timeout(Duration),
+}
+
+/// Shared between an isolate, its near-heap-limit callback and its watchdog thread.
+pub struct Limits {
+    isolate: v8::IsolateHandle,
+    exceeded: Mutex<Option<Exceeded>>,
+    // Whether the heap limit has had its one raise
+    heap_raised: AtomicBool,
+    // Wakes the event loop if it is waiting for something else
+    notify: Notify,
+}
+
+impl Limits {
+    pub fn new(isolate: v8::IsolateHandle) -> Self {
+        Self {
+            isolate,
+            exceeded: Mutex::new(None),
+            heap_raised: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Records the first limit the run goes over and stops the script.
+    fn exceed(&self, exceeded: Exceeded) {
+        let mut current = self.exceeded.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_some() {
+            return;
+        }
+        warn!("Run exceeded a limit: {:?}", exceeded);
+        *current = Some(exceeded);
+        self.isolate.terminate_execution();
+        self.notify.notify_one();
+    }
+
+    fn get(&self) -> Option<Exceeded> {
+        *self.exceeded.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.get().is_some()
+    }
+
+    /// Resolves once a limit has been exceeded.
+    pub async fn exceeded(&self) {
+        if self.get().is_none() {
+            self.notify.notified().await;
+        }
+    }
+
+    /// The error for the limit the run went over, if it went over one.
+    pub fn error(&self, isolate: &mut v8::Isolate) -> Option<MycoError> {
+        match self.get()? {
+            Exceeded::Heap => {
+                let stats = isolate.get_heap_statistics();
+                Some(MycoError::HeapLimit {
+                    used_mb: stats.used_heap_size() as f64 / BYTES_PER_MB,
+                    total_mb: stats.total_heap_size() as f64 / BYTES_PER_MB,
+                    limit_mb: stats.heap_size_limit() as f64 / BYTES_PER_MB,
+                })
+            }
+            Exceeded::Timeout(timeout) => Some(MycoError::Timeout { timeout }),
+        }
+    }
+}
+
+/// Called by V8 when the heap is about to run out, with the `Limits` as its data.
+/// The script is stopped, and the first time the limit is raised once, by at most
+/// `HEAP_UNWIND_MB`, so there is room to unwind it. After that the limit stays put.
+pub unsafe extern "C" fn near_heap_limit_callback(
+    data: *mut std::ffi::c_void,
+    current_heap_limit: usize,
+    initial_heap_limit: usize,
+) -> usize {
+    let limits = unsafe { &*(data as *const Limits) };
+    debug!("Heap is near its limit of {} bytes", current_heap_limit);
+    limits.exceed(Exceeded::Heap);
+    if limits.heap_raised.swap(true, Ordering::SeqCst) {
+        return current_heap_limit;
+    }
+    let room = (initial_heap_limit / 2).min(HEAP_UNWIND_MB * 1024 * 1024);
+    current_heap_limit + room
+}
+
+/// A thread that stops the script once its time is up. Dropping the watchdog stops
+/// the thread.
+pub struct Watchdog {
+    _cancel: mpsc::Sender<()>,
+}
+
+impl Watchdog {
+    pub fn start(limits: Arc<Limits>, timeout: Duration) -> Result<Self, MycoError> {
+        let (cancel, cancelled) = mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name("myco-watchdog".to_string())
+            .spawn(move || {
+                // The sender only goes away once the run is over
+                if let Err(RecvTimeoutError::Timeout) = cancelled.recv_timeout(timeout) {
+                    limits.exceed(Exceeded::Timeout(timeout));
+                }
+            })
+            .map_err(|e| MycoError::Internal {
+                message: format!("Failed to start watchdog thread: {}", e),
+            })?;
+        Ok(Self { _cancel: cancel })
+    }
+}
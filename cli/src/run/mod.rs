@@ -2,51 +2,67 @@ use crate::errors::MycoError;
 pub use capabilities::*;
 use log::{debug, info, warn};
 
+use crate::deps::InstalledPackages;
+use crate::manifest::import_map::ImportMap;
 use crate::manifest::myco_local::MycoLocalToml;
 use crate::manifest::MycoToml;
 
 // Module declarations
 mod capabilities;
+mod commonjs;
 mod constants;
 mod engine;
 mod errors;
 mod event_loop;
 mod inspector;
+mod limits;
 mod modules;
 mod ops;
+mod sandbox;
 mod stack_trace;
 mod state;
+mod worker;
 
 // Re-export public types from state module
-pub use state::DebugOptions;
+pub use state::{DebugOptions, LogFormat, RunOptions, UnhandledRejections};
 
-pub fn run(
-    myco_toml: &MycoToml,
-    script: &String,
-    debug_options: Option<DebugOptions>,
-) -> Result<i32, MycoError> {
+pub fn run(myco_toml: &MycoToml, script: &String, options: RunOptions) -> Result<i32, MycoError> {
     info!("Running script: {}", script);
-    debug!("Debug options: {:?}", debug_options);
 
     if let Some(run) = &myco_toml.run {
-        debug!("Found run configuration with {} scripts", run.len());
-        if let Some(script_path) = run.get(script) {
+        // Flags win over the limits in `[run]`
+        let options = RunOptions {
+            max_heap_mb: match options.max_heap_mb {
+                Some(max_heap_mb) => Some(max_heap_mb),
+                None => run.max_heap_mb()?,
+            },
+            timeout: match options.timeout {
+                Some(timeout) => Some(timeout),
+                None => run.timeout()?,
+            },
+            ..options
+        };
+        debug!("Run options: {:?}", options);
+
+        debug!("Found run configuration with {} scripts", run.scripts.len());
+        if let Some(script_path) = run.scripts.get(script) {
             info!("Found script '{}' mapping to: {}", script, script_path);
-            run_file(script_path, debug_options)
+            run_file(script_path, options)
         } else {
             debug!(
                 "Script '{}' not found in run configuration, treating as file path",
                 script
             );
-            run_file(script, debug_options)
+            run_file(script, options)
         }
     } else {
+        debug!("Run options: {:?}", options);
         debug!("No run configuration found, treating script as file path");
-        run_file(script, debug_options)
+        run_file(script, options)
     }
 }
 
-pub fn run_file(file_path: &str, debug_options: Option<DebugOptions>) -> Result<i32, MycoError> {
+pub fn run_file(file_path: &str, options: RunOptions) -> Result<i32, MycoError> {
     info!("Running file: {}", file_path);
 
     // Convert to absolute path for better error reporting
@@ -77,17 +93,30 @@ pub fn run_file(file_path: &str, debug_options: Option<DebugOptions>) -> Result<
 
     // The working directory is the nearest myco.toml to the executable
     debug!("Finding nearest myco.toml for working directory");
-    let working_dir = match MycoToml::load_nearest(absolute_path.clone()) {
-        Ok((dir, _)) => {
+    let (working_dir, package_toml) = match MycoToml::load_nearest(absolute_path.clone()) {
+        Ok((dir, myco_toml)) => {
             debug!("Found myco.toml, working directory: {}", dir.display());
-            dir
+            (dir, Some(myco_toml))
         }
         Err(_) => {
             debug!("No myco.toml found, using file directory as working directory");
-            absolute_path.clone()
+            (absolute_path.clone(), None)
         }
     };
 
+    // An import map is declared by the package, so it needs a myco.toml
+    let import_map = match &package_toml {
+        Some(package_toml) => ImportMap::load(&working_dir, package_toml)?,
+        None => None,
+    };
+    debug!("Import map present: {}", import_map.is_some());
+
+    // So are its dependencies, which it imports by name
+    let packages = match &package_toml {
+        Some(package_toml) => Some(InstalledPackages::load(&working_dir, package_toml)?),
+        None => None,
+    };
+
     // Try to load myco-local.toml
     debug!("Loading myco-local.toml");
     let myco_local = MycoLocalToml::load_from_myco_toml_path(working_dir.clone()).ok();
@@ -128,5 +157,11 @@ pub fn run_file(file_path: &str, debug_options: Option<DebugOptions>) -> Result<
         .map_err(|e| MycoError::TokioRuntime { source: e })?;
 
     info!("Starting JavaScript execution");
-    runtime.block_on(engine::run_js(&absolute_path, myco_local, debug_options))
+    runtime.block_on(engine::run_js(
+        &absolute_path,
+        myco_local,
+        import_map,
+        packages,
+        options,
+    ))
 }
@@ -1,24 +1,22 @@
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use log::{debug, info, trace};
 use sourcemap::SourceMap;
-use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 
+use crate::cache::CodeCache;
 use crate::errors::MycoError;
+use crate::run::commonjs::create_commonjs_module;
 use crate::run::errors::get_exception_message_with_stack;
+use crate::run::ops::macros::{create_js_error, throw_js_error};
 use crate::run::state::MycoState;
 
-// Thread-local storage for tracking the current module resolution context
-thread_local! {
-    static MODULE_RESOLUTION_STACK: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
-}
-
 // File type detection for module loading
 #[derive(Debug, PartialEq)]
 pub enum FileType {
     Unknown,
     TypeScript,
     JavaScript,
+    CommonJs,
     Json,
 }
 
@@ -36,10 +34,14 @@ impl FileType {
                         trace!("Detected TypeScript file: {}", path.display());
                         Self::TypeScript
                     }
-                    Some("js") | Some("jsx") | Some("mjs") | Some("cjs") => {
+                    Some("js") | Some("jsx") | Some("mjs") => {
                         trace!("Detected JavaScript file: {}", path.display());
                         Self::JavaScript
                     }
+                    Some("cjs") => {
+                        trace!("Detected CommonJS file: {}", path.display());
+                        Self::CommonJs
+                    }
                     Some("json") => {
                         trace!("Detected JSON file: {}", path.display());
                         Self::Json
@@ -57,6 +59,59 @@ impl FileType {
     }
 }
 
+// The kind of module an import asks for with its `type` attribute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportType {
+    JavaScript,
+    Json,
+}
+
+impl ImportType {
+    /// Reads the `type` attribute off an import's attributes. V8 passes them as a flat
+    /// array of entries `entry_size` long, each starting with a key and its value.
+    fn from_attributes(
+        scope: &mut v8::PinScope<'_, '_>,
+        specifier: &str,
+        attributes: v8::Local<v8::FixedArray>,
+        entry_size: usize,
+    ) -> Result<Self, MycoError> {
+        let attribute_error = |message: String| MycoError::ImportAttributes {
+            specifier: specifier.to_string(),
+            message,
+        };
+
+        let mut import_type = Self::JavaScript;
+        for i in (0..attributes.length()).step_by(entry_size) {
+            let string_at = |scope: &mut v8::PinScope<'_, '_>, index: usize| {
+                attributes
+                    .get(scope, index)
+                    .and_then(|data| v8::Local::<v8::String>::try_from(data).ok())
+                    .map(|string| string.to_rust_string_lossy(scope))
+            };
+            let key = string_at(scope, i).unwrap_or_default();
+            let value = string_at(scope, i + 1).unwrap_or_default();
+
+            match (key.as_str(), value.as_str()) {
+                ("type", "json") => import_type = Self::Json,
+                ("type", other) => {
+                    return Err(attribute_error(format!(
+                        "unsupported module type \"{}\"",
+                        other
+                    )))
+                }
+                (other, _) => {
+                    return Err(attribute_error(format!(
+                        "unsupported import attribute \"{}\"",
+                        other
+                    )))
+                }
+            }
+        }
+
+        Ok(import_type)
+    }
+}
+
 /// Loads the user's entry module, evaluates it, and invokes its default export with
 /// the powerbox.
 ///
@@ -84,13 +139,6 @@ pub fn load_and_run_module(
         user_module_absolute_path.to_string_lossy()
     );
 
-    // Set the current module path context so relative imports from the entry module
-    // resolve against the entry module's directory.
-    debug!("Setting module resolution stack");
-    MODULE_RESOLUTION_STACK.with(|current| {
-        *current.borrow_mut() = vec![user_module_absolute_path.clone()];
-    });
-
     let base_path = user_module_absolute_path
         .parent()
         .map(|p| p.to_path_buf())
@@ -101,6 +149,7 @@ pub fn load_and_run_module(
         scope,
         &user_module_absolute_path.to_string_lossy(),
         &base_path,
+        ImportType::JavaScript,
     )
     .map_err(|e| MycoError::MainModuleCompilation {
         message: e.to_string(),
@@ -109,12 +158,20 @@ pub fn load_and_run_module(
 
     // Instantiate the module - this will trigger module resolution for its imports
     debug!("Instantiating entry module (will trigger module resolution)");
-    let instantiate_result = main_module.instantiate_module(scope, module_resolve_callback);
-    if instantiate_result.is_none() {
-        return Err(MycoError::MainModuleInstantiation {
-            message: "Failed to instantiate main module - likely due to import resolution failure"
-                .to_string(),
-        });
+    {
+        v8::tc_scope!(let scope, scope);
+        let instantiate_result = main_module.instantiate_module(scope, module_resolve_callback);
+        if instantiate_result.is_none() {
+            // An import that was found but rejected throws, so say why
+            let message = match scope.exception() {
+                Some(exception) => get_exception_message_with_stack(scope, exception),
+                None => {
+                    "Failed to instantiate main module - likely due to import resolution failure"
+                        .to_string()
+                }
+            };
+            return Err(MycoError::MainModuleInstantiation { message });
+        }
     }
     debug!("Entry module instantiated successfully");
 
@@ -251,6 +308,28 @@ fn call_default_export<'s>(
     }
 }
 
+/// Writes the code cache for every module that was compiled without one.
+pub fn store_code_caches(scope: &mut v8::PinScope<'_, '_>) {
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    let Some(state) = (unsafe { state_ptr.as_mut() }) else {
+        return;
+    };
+    let misses = std::mem::take(&mut state.code_cache_misses);
+    let Some(code_cache) = state.code_cache.as_ref() else {
+        return;
+    };
+
+    debug!("Storing code cache for {} modules", misses.len());
+    for (key, module) in misses {
+        let module = v8::Local::new(scope, &module);
+        let unbound = module.get_unbound_module_script(scope);
+        match unbound.create_code_cache() {
+            Some(data) => code_cache.store(&key, &data),
+            None => debug!("V8 produced no code cache for {}", key),
+        }
+    }
+}
+
 /// Promise callback: records the entry point's resolved value as the process exit code.
 fn record_exit_code<'s>(
     scope: &mut v8::PinScope<'s, '_>,
@@ -292,175 +371,194 @@ fn record_unhandled_error<'s>(
 pub fn module_resolve_callback<'s>(
     context: v8::Local<'s, v8::Context>,
     specifier: v8::Local<'s, v8::String>,
-    _import_attributes: v8::Local<'s, v8::FixedArray>,
-    _referrer: v8::Local<'s, v8::Module>,
+    import_attributes: v8::Local<'s, v8::FixedArray>,
+    referrer: v8::Local<'s, v8::Module>,
 ) -> Option<v8::Local<'s, v8::Module>> {
     v8::callback_scope!(unsafe let scope, context);
 
     // Get specifier
     let specifier_str = specifier.to_rust_string_lossy(scope);
 
-    // Check if this specifier should be resolved using myco-local.toml
-    let resolved_specifiers = {
-        let state_ptr = scope.get_data(0) as *mut MycoState;
-        if !state_ptr.is_null() {
-            let state = unsafe { &*state_ptr };
-            if let Some(myco_local) = &state.myco_local {
-                // Check for exact match first
-                if let Some(resolved_paths) = myco_local.get_resolve_paths(&specifier_str) {
-                    resolved_paths.clone()
-                } else {
-                    // Check for prefix matches
-                    let mut best_match: Option<(String, Vec<String>)> = None;
-
-                    for (alias, paths) in myco_local.clone_resolve() {
-                        if specifier_str.starts_with(&alias) {
-                            // Check if this is a proper prefix match (either exact or followed by '/')
-                            if specifier_str == alias
-                                || specifier_str.chars().nth(alias.len()) == Some('/')
-                            {
-                                // Found a prefix match, take the longest one
-                                if best_match.is_none()
-                                    || alias.len() > best_match.as_ref().unwrap().0.len()
-                                {
-                                    best_match = Some((alias, paths));
-                                }
-                            }
-                        }
-                    }
+    // Every module V8 asks about was loaded by us, so its file is known
+    let referrer_path = {
+        let referrer = v8::Global::new(scope, referrer);
+        let state_ptr = scope.get_data(0) as *const MycoState;
+        unsafe { state_ptr.as_ref() }.and_then(|state| state.module_paths.get(&referrer).cloned())
+    };
 
-                    if let Some((alias, resolved_paths)) = best_match {
-                        if specifier_str == alias {
-                            // Exact match
-                            resolved_paths
-                        } else {
-                            // Prefix match, append the remaining path to each resolved path
-                            let remaining = &specifier_str[alias.len()..];
-                            resolved_paths
-                                .into_iter()
-                                .map(|path| format!("{}{}", path, remaining))
-                                .collect()
-                        }
-                    } else {
-                        vec![specifier_str.clone()]
-                    }
-                }
-            } else {
-                vec![specifier_str.clone()]
-            }
-        } else {
-            vec![specifier_str.clone()]
+    // Static imports pass each attribute as a key, a value and its source location
+    let module = ImportType::from_attributes(scope, &specifier_str, import_attributes, 3).and_then(
+        |import_type| resolve_module(scope, &specifier_str, referrer_path.as_deref(), import_type),
+    );
+    match module {
+        Ok(module) => Some(module),
+        Err(e) => {
+            throw_js_error(scope, &e.to_string());
+            None
         }
-    };
+    }
+}
 
-    // Determine the base path from the current module context
-    let base_path = MODULE_RESOLUTION_STACK.with(|stack| {
-        if let Some(current_path) = stack.borrow().last() {
-            if let Some(parent) = current_path.parent() {
-                parent.to_path_buf()
-            } else {
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+/// Resolves an import for static and dynamic imports alike: through the package's
+/// import map, then the aliases in myco-local.toml, then the installed packages, then
+/// relative to the importing module, or to the working directory when the import does
+/// not come from a module.
+fn resolve_module<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    specifier: &str,
+    referrer: Option<&Path>,
+    import_type: ImportType,
+) -> Result<v8::Local<'s, v8::Module>, MycoError> {
+    let base_path = referrer
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+    // Only an import that names a package may reach into it
+    let (candidates, by_package_name) =
+        match import_map_candidate(scope, specifier, &base_path, referrer)? {
+            Some(candidate) => (vec![candidate], false),
+            None => match alias_candidates(scope, specifier) {
+                Some(candidates) => (candidates, false),
+                None => match package_candidate(scope, specifier, referrer)? {
+                    Some(candidate) => (vec![candidate], true),
+                    None => (vec![specifier.to_string()], false),
+                },
+            },
+        };
+
+    // Try each resolved specifier until one exists
+    let mut not_found = None;
+    for resolved_specifier in candidates {
+        if !by_package_name {
+            let path = resolved_specifier
+                .strip_prefix("file://")
+                .unwrap_or(&resolved_specifier);
+            check_package_boundary(scope, &base_path.join(path), referrer)?;
+        }
+        match load_and_compile_module(scope, &resolved_specifier, &base_path, import_type) {
+            Ok(module) => return Ok(module),
+            Err(e @ MycoError::ModuleNotFound { .. }) => {
+                debug!("Import candidate {} not found", resolved_specifier);
+                not_found = Some(e);
             }
-        } else {
-            std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+            // The module was found but cannot be loaded, so the other paths are moot
+            Err(e) => return Err(e),
         }
-    });
-
-    // Try each resolved specifier until one works
-    for resolved_specifier in resolved_specifiers {
-        // Load and compile the module using the resolved specifier
-        match load_and_compile_module(scope, &resolved_specifier, &base_path) {
-            Ok(module) => {
-                // Get the module path for the stack
-                let module_url = format!(
-                    "file://{}",
-                    if resolved_specifier.starts_with("file://") {
-                        PathBuf::from(&resolved_specifier[7..])
-                    } else {
-                        let path = PathBuf::from(&resolved_specifier);
-                        if path.is_absolute() {
-                            path
-                        } else {
-                            let normalized_path = if let Ok(stripped) = path.strip_prefix("./") {
-                                stripped.to_path_buf()
-                            } else {
-                                path
-                            };
-                            base_path.join(normalized_path)
-                        }
-                    }
-                    .to_string_lossy()
-                );
-
-                // Get the absolute path from the state mapping
-                let module_path = {
-                    let state_ptr = scope.get_data(0) as *mut MycoState;
-                    if !state_ptr.is_null() {
-                        let state = unsafe { &*state_ptr };
-                        state.module_url_to_path.get(&module_url).cloned()
-                    } else {
-                        None
-                    }
-                };
+    }
 
-                // Push the module path onto the resolution stack before instantiation
-                if let Some(abs_path) = module_path {
-                    MODULE_RESOLUTION_STACK.with(|stack| {
-                        stack.borrow_mut().push(abs_path);
-                    });
-                }
+    Err(not_found.unwrap_or_else(|| MycoError::ModuleNotFound {
+        specifier: specifier.to_string(),
+        resolved_path: base_path.join(specifier).display().to_string(),
+    }))
+}
 
-                // Use TryCatch to capture exceptions during instantiation
-                v8::tc_scope!(let scope, scope);
-
-                // Instantiate the module recursively
-                let result = match module.instantiate_module(scope, module_resolve_callback) {
-                    Some(_) => Some(module),
-                    None => {
-                        // Check if there was an exception during instantiation
-                        let error_detail = if scope.has_caught() {
-                            if let Some(exception) = scope.exception() {
-                                get_exception_message_with_stack(scope, exception)
-                            } else {
-                                "Unknown exception occurred".to_string()
-                            }
-                        } else {
-                            "Unknown instantiation error".to_string()
-                        };
-                        eprintln!(
-                            "Failed to instantiate module '{}': {}",
-                            specifier_str, error_detail
-                        );
-                        None
-                    }
-                };
+/// The file the import map resolves an import to, if the package has an import map
+/// and it applies.
+fn import_map_candidate(
+    scope: &mut v8::PinScope<'_, '_>,
+    specifier: &str,
+    base_path: &Path,
+    referrer: Option<&Path>,
+) -> Result<Option<String>, MycoError> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    let Some(import_map) =
+        unsafe { state_ptr.as_ref() }.and_then(|state| state.import_map.as_ref())
+    else {
+        return Ok(None);
+    };
 
-                // Pop the module path from the resolution stack after instantiation
-                MODULE_RESOLUTION_STACK.with(|stack| {
-                    stack.borrow_mut().pop();
-                });
+    let referrer_url = match referrer {
+        Some(path) => url::Url::from_file_path(path),
+        None => url::Url::from_directory_path(base_path),
+    };
+    let Ok(referrer_url) = referrer_url else {
+        return Ok(None);
+    };
 
-                return result;
-            }
-            Err(_e) => {
-                // Try the next resolved specifier if this one failed
-                continue;
-            }
+    match import_map.resolve(specifier, &referrer_url)? {
+        Some(url) => match url.to_file_path() {
+            Ok(path) => Ok(Some(path.to_string_lossy().to_string())),
+            Err(_) => Err(MycoError::ModuleNotFound {
+                specifier: specifier.to_string(),
+                resolved_path: format!("{} (only files can be imported)", url),
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The file an installed package exports for an import that names it, if it does.
+fn package_candidate(
+    scope: &mut v8::PinScope<'_, '_>,
+    specifier: &str,
+    referrer: Option<&Path>,
+) -> Result<Option<String>, MycoError> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    let Some(packages) = unsafe { state_ptr.as_ref() }.and_then(|state| state.packages.as_ref())
+    else {
+        return Ok(None);
+    };
+
+    Ok(packages
+        .resolve(specifier, referrer)?
+        .map(|path| path.to_string_lossy().to_string()))
+}
+
+/// Checks that a module imported by its path does not cross into or out of an
+/// installed package.
+pub fn check_package_boundary(
+    scope: &mut v8::PinScope<'_, '_>,
+    path: &Path,
+    referrer: Option<&Path>,
+) -> Result<(), MycoError> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    match unsafe { state_ptr.as_ref() }.and_then(|state| state.packages.as_ref()) {
+        Some(packages) => packages.check_boundary(path, referrer),
+        None => Ok(()),
+    }
+}
+
+/// The paths myco-local.toml aliases an import to, in the order to try them, if any.
+fn alias_candidates(scope: &mut v8::PinScope<'_, '_>, specifier: &str) -> Option<Vec<String>> {
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    let myco_local = unsafe { state_ptr.as_ref() }.and_then(|state| state.myco_local.as_ref())?;
+
+    // Check for exact match first
+    if let Some(resolved_paths) = myco_local.get_resolve_paths(specifier) {
+        return Some(resolved_paths.clone());
+    }
+
+    // Check for prefix matches
+    let mut best_match: Option<(String, Vec<String>)> = None;
+    for (alias, paths) in myco_local.clone_resolve() {
+        // Check if this is a proper prefix match (either exact or followed by '/')
+        let is_prefix = specifier == alias
+            || (specifier.starts_with(&alias) && specifier[alias.len()..].starts_with('/'));
+        // Take the longest one
+        let is_longer = best_match
+            .as_ref()
+            .is_none_or(|(best, _)| alias.len() > best.len());
+        if is_prefix && is_longer {
+            best_match = Some((alias, paths));
         }
     }
 
-    // If we get here, none of the resolved specifiers worked
-    eprintln!(
-        "Failed to load and compile module '{}' from any resolved paths",
-        specifier_str
-    );
-    None
+    // Prefix match, append the remaining path to each resolved path
+    best_match.map(|(alias, resolved_paths)| {
+        let remaining = &specifier[alias.len()..];
+        resolved_paths
+            .into_iter()
+            .map(|path| format!("{}{}", path, remaining))
+            .collect()
+    })
 }
 
 pub fn load_and_compile_module<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     specifier: &str,
     base_path: &Path,
+    import_type: ImportType,
 ) -> Result<v8::Local<'s, v8::Module>, MycoError> {
     // Convert file:// URL to path
     let path = if specifier.starts_with("file://") {
@@ -518,14 +616,76 @@ pub fn load_and_compile_module<'s>(
         });
     };
 
-    let file_type = FileType::from_path(&final_absolute_path);
+    // A package's `.js` files are CommonJS when it says so, as with "type": "commonjs"
+    // in Node
+    let state_ptr = scope.get_data(0) as *const MycoState;
+    let is_commonjs_package = unsafe { state_ptr.as_ref() }
+        .and_then(|state| state.packages.as_ref())
+        .is_some_and(|packages| packages.is_commonjs(&final_absolute_path));
+    let file_type = match FileType::from_path(&final_absolute_path) {
+        FileType::JavaScript
+            if is_commonjs_package
+                && final_absolute_path.extension().is_some_and(|e| e == "js") =>
+        {
+            FileType::CommonJs
+        }
+        file_type => file_type,
+    };
+
+    // A module is only ever loaded as the type its import asked for
+    match (&file_type, import_type) {
+        (FileType::Json, ImportType::JavaScript) => {
+            return Err(MycoError::ImportAttributes {
+                specifier: specifier.to_string(),
+                message: "JSON modules must be imported with { type: \"json\" }".to_string(),
+            });
+        }
+        (FileType::Json, ImportType::Json) | (_, ImportType::JavaScript) => {}
+        (_, ImportType::Json) => {
+            return Err(MycoError::ImportAttributes {
+                specifier: specifier.to_string(),
+                message: format!("{} is not a JSON file", final_absolute_path.display()),
+            });
+        }
+    }
+
+    // Every import of a file shares the module compiled the first time it was loaded
+    let module_url = format!("file://{}", final_absolute_path.to_string_lossy());
+    let cached =
+        unsafe { state_ptr.as_ref() }.and_then(|state| state.module_cache.get(&module_url));
+    if let Some(module) = cached {
+        trace!("Reusing loaded module {}", module_url);
+        return Ok(v8::Local::new(scope, module));
+    }
+
+    if file_type == FileType::Json {
+        if let Some(state) = unsafe { state_ptr.as_ref() } {
+            confine_json_module(state, &final_absolute_path, base_path)?;
+        }
+        let module = create_json_module(scope, &module_url, &final_absolute_path)?;
+        register_module(scope, module_url, module, final_absolute_path);
+        return Ok(module);
+    }
+
+    if file_type == FileType::CommonJs {
+        let module = create_commonjs_module(scope, &module_url, &final_absolute_path)?;
+        register_module(scope, module_url, module, final_absolute_path);
+        return Ok(module);
+    }
 
     // Determine if we need to transpile
     let should_transpile = matches!(file_type, FileType::TypeScript);
 
     let (final_code, source_map_content) = if should_transpile {
-        // Use the existing transpilation logic and capture source map
-        match util::transpile::parse_and_gen_path(&final_absolute_path) {
+        // Transpile (or reuse cached output) and capture the source map
+        let state_ptr = scope.get_data(0) as *const MycoState;
+        let transpile_cache =
+            unsafe { state_ptr.as_ref() }.and_then(|state| state.transpile_cache.as_ref());
+        let transpiled = match transpile_cache {
+            Some(cache) => cache.transpile(&final_absolute_path),
+            None => util::transpile::parse_and_gen_path(&final_absolute_path),
+        };
+        match transpiled {
             Ok(transpiled) => {
                 let source_map_content = String::from_utf8(transpiled.source_map)
                     .map_err(|e| MycoError::InvalidSourceMapUtf8 { source: e })?;
@@ -564,9 +724,6 @@ pub fn load_and_compile_module<'s>(
         (content, None)
     };
 
-    // Create V8 module using the absolute path as the URL for proper referrer resolution
-    let module_url = format!("file://{}", final_absolute_path.to_string_lossy());
-
     // Store source map if we have one
     let source_map_url = if let Some(ref source_map) = source_map_content {
         // Create a data URL for the source map so V8 can access it synchronously
@@ -594,27 +751,178 @@ pub fn load_and_compile_module<'s>(
 
     let source_text = v8::String::new(scope, &final_code).ok_or(MycoError::V8StringCreation)?;
     let origin = create_module_origin_for_scope(scope, &module_url, source_map_url.as_deref())?;
-    let mut source = v8::script_compiler::Source::new(source_text, Some(&origin));
 
-    let module = v8::script_compiler::compile_module(scope, &mut source).ok_or_else(|| {
-        MycoError::ModuleCompilation {
-            specifier: specifier.to_string(),
-            resolved_path: final_absolute_path.display().to_string(),
+    // Reuse V8's compiled code from an earlier run when there is some
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    let code_cache = unsafe { state_ptr.as_ref() }.and_then(|state| state.code_cache.as_ref());
+    let cache_key = code_cache.map(|_| CodeCache::key(&module_url, &final_code));
+    let cached_code = code_cache
+        .zip(cache_key.as_deref())
+        .and_then(|(cache, key)| cache.get(key));
+
+    let (module, cache_accepted) = if let Some(cached_code) = &cached_code {
+        let cached_data = v8::script_compiler::CachedData::new(cached_code);
+        let mut source = v8::script_compiler::Source::new_with_cached_data(
+            source_text,
+            Some(&origin),
+            cached_data,
+        );
+        let module = v8::script_compiler::compile_module2(
+            scope,
+            &mut source,
+            v8::script_compiler::CompileOptions::ConsumeCodeCache,
+            v8::script_compiler::NoCacheReason::NoReason,
+        );
+        let rejected = source.get_cached_data().is_some_and(|data| data.rejected());
+        if rejected {
+            debug!("V8 rejected the code cache for {}", module_url);
+        } else {
+            debug!("Consumed the code cache for {}", module_url);
         }
+        (module, !rejected)
+    } else {
+        if cache_key.is_some() {
+            debug!("No code cache for {}", module_url);
+        }
+        let mut source = v8::script_compiler::Source::new(source_text, Some(&origin));
+        (
+            v8::script_compiler::compile_module(scope, &mut source),
+            false,
+        )
+    };
+
+    let module = module.ok_or_else(|| MycoError::ModuleCompilation {
+        specifier: specifier.to_string(),
+        resolved_path: final_absolute_path.display().to_string(),
     })?;
 
-    // Store the module URL to path mapping in the isolate state
+    if let (Some(key), false) = (cache_key, cache_accepted) {
+        let module = v8::Global::new(scope, module);
+        if let Some(state) = unsafe { state_ptr.as_mut() } {
+            state.code_cache_misses.push((key, module));
+        }
+    }
+
+    register_module(scope, module_url, module, final_absolute_path);
+    Ok(module)
+}
+
+/// Records a newly loaded module, so later imports of its URL reuse it and its own
+/// imports resolve against its path.
+fn register_module(
+    scope: &mut v8::PinScope<'_, '_>,
+    module_url: String,
+    module: v8::Local<v8::Module>,
+    path: PathBuf,
+) {
+    let module = v8::Global::new(scope, module);
     let state_ptr = scope.get_data(0) as *mut MycoState;
-    if !state_ptr.is_null() {
-        let state = unsafe { &mut *state_ptr };
+    if let Some(state) = unsafe { state_ptr.as_mut() } {
+        state.module_cache.insert(module_url, module.clone());
+        state.module_paths.insert(module, path);
+    }
+}
+
+/// JSON modules are data that no read token covers, so they can only be loaded from
+/// inside the project, or from inside the installed package of the module in
+/// `referrer_dir` that loads them.
+pub fn confine_json_module(
+    state: &MycoState,
+    path: &Path,
+    referrer_dir: &Path,
+) -> Result<(), MycoError> {
+    let package_dir = state
+        .packages
+        .as_ref()
+        .and_then(|packages| packages.package_dir(referrer_dir));
+    let root = package_dir
+        .or(state.project_dir.as_deref())
+        .map(|root| root.canonicalize().unwrap_or_else(|_| root.to_path_buf()));
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+    match root {
+        Some(root) if path.starts_with(&root) => Ok(()),
+        root => Err(MycoError::JsonOutsideRoot {
+            path: path.display().to_string(),
+            root: root
+                .map(|root| root.display().to_string())
+                .unwrap_or_else(|| "the project".to_string()),
+        }),
+    }
+}
+
+/// Creates a synthetic module whose default export is the parsed contents of a JSON
+/// file. The file is parsed here so that invalid JSON fails the import.
+fn create_json_module<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    module_url: &str,
+    path: &Path,
+) -> Result<v8::Local<'s, v8::Module>, MycoError> {
+    let content = std::fs::read_to_string(path).map_err(|e| MycoError::ReadFile {
+        path: path.display().to_string(),
+        source: e,
+    })?;
+
+    let value = {
+        v8::tc_scope!(let scope, scope);
+        let source = v8::String::new(scope, &content).ok_or(MycoError::V8StringCreation)?;
+        match v8::json::parse(scope, source) {
+            Some(value) => v8::Global::new(scope, value),
+            None => {
+                let message = scope
+                    .exception()
+                    .map(|exception| exception.to_rust_string_lossy(scope))
+                    .unwrap_or_else(|| "Unknown parse error".to_string());
+                return Err(MycoError::JsonModule {
+                    path: path.display().to_string(),
+                    message,
+                });
+            }
+        }
+    };
+
+    let module_name = v8::String::new(scope, module_url).ok_or(MycoError::V8StringCreation)?;
+    let default_name = v8::String::new(scope, "default").ok_or(MycoError::V8StringCreation)?;
+    let module = v8::Module::create_synthetic_module(
+        scope,
+        module_name,
+        &[default_name],
+        evaluate_json_module,
+    );
+
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    if let Some(state) = unsafe { state_ptr.as_mut() } {
         state
-            .module_url_to_path
-            .insert(module_url, final_absolute_path.clone());
+            .json_modules
+            .insert(v8::Global::new(scope, module), value);
     }
 
     Ok(module)
 }
 
+/// Evaluation steps for a JSON module: sets its parsed value as the default export.
+fn evaluate_json_module<'s>(
+    context: v8::Local<'s, v8::Context>,
+    module: v8::Local<'s, v8::Module>,
+) -> Option<v8::Local<'s, v8::Value>> {
+    v8::callback_scope!(unsafe let scope, context);
+
+    let state_ptr = scope.get_data(0) as *mut MycoState;
+    let state = unsafe { state_ptr.as_mut() }?;
+    let value = state.json_modules.remove(&v8::Global::new(scope, module))?;
+
+    let value = v8::Local::new(scope, value);
+    let default_name = v8::String::new(scope, "default")?;
+    module.set_synthetic_module_export(scope, default_name, value)?;
+
+    // Evaluation is asynchronous since top-level await, even though there is
+    // nothing to wait for here
+    let resolver = v8::PromiseResolver::new(scope)?;
+    let undefined = v8::undefined(scope);
+    resolver.resolve(scope, undefined.into())?;
+    Some(resolver.get_promise(scope).into())
+}
+
 fn create_module_origin_for_scope<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     url: &str,
@@ -648,9 +956,9 @@ fn create_module_origin_for_scope<'s>(
 pub fn host_import_module_dynamically_callback<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     _host_defined_options: v8::Local<'s, v8::Data>,
-    _resource_name: v8::Local<'s, v8::Value>,
+    resource_name: v8::Local<'s, v8::Value>,
     specifier: v8::Local<'s, v8::String>,
-    _import_attributes: v8::Local<'s, v8::FixedArray>,
+    import_attributes: v8::Local<'s, v8::FixedArray>,
 ) -> Option<v8::Local<'s, v8::Promise>> {
     let specifier_str = specifier.to_rust_string_lossy(scope);
 
@@ -658,80 +966,70 @@ pub fn host_import_module_dynamically_callback<'s>(
     let resolver = v8::PromiseResolver::new(scope)?;
     let promise = resolver.get_promise(scope);
 
-    // For dynamic imports, we don't have referrer info, so use current working directory
-    let base_path = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    // Try to load and compile the module
-    match load_and_compile_module(scope, &specifier_str, &base_path) {
-        Ok(module) => {
-            // Use TryCatch to capture exceptions during instantiation
-            v8::tc_scope!(let scope, scope);
-
-            // Instantiate the module
-            match module.instantiate_module(scope, module_resolve_callback) {
-                Some(_) => {
-                    // Evaluate the module - this returns a value for the module namespace
-                    match module.evaluate(scope) {
-                        Some(_result) => {
-                            // For dynamic imports, we need to resolve with the module namespace object
-                            let module_namespace = module.get_module_namespace();
-                            resolver.resolve(scope, module_namespace);
-                        }
-                        None => {
-                            // Check for exceptions during evaluation
-                            let error_detail = if scope.has_caught() {
-                                if let Some(exception) = scope.exception() {
-                                    get_exception_message_with_stack(scope, exception)
-                                } else {
-                                    "Unknown exception occurred".to_string()
-                                }
-                            } else {
-                                "Unknown evaluation error".to_string()
-                            };
-                            if let Some(error_msg) = v8::String::new(
-                                scope,
-                                &format!(
-                                    "Failed to evaluate dynamically imported module '{}': {}",
-                                    specifier_str, error_detail
-                                ),
-                            ) {
-                                resolver.reject(scope, error_msg.into());
-                            }
-                        }
-                    }
-                }
-                None => {
-                    // Check for exceptions during instantiation
-                    let error_detail = if scope.has_caught() {
-                        if let Some(exception) = scope.exception() {
-                            get_exception_message_with_stack(scope, exception)
-                        } else {
-                            "Unknown exception occurred".to_string()
-                        }
-                    } else {
-                        "Unknown instantiation error".to_string()
-                    };
-                    if let Some(error_msg) = v8::String::new(
-                        scope,
-                        &format!(
-                            "Failed to instantiate dynamically imported module '{}': {}",
-                            specifier_str, error_detail
-                        ),
-                    ) {
-                        resolver.reject(scope, error_msg.into());
-                    }
-                }
-            }
-        }
+    // A module's resource name is its URL. The runtime script has no name, so imports
+    // from it resolve against the working directory.
+    let resource_name = resource_name.to_rust_string_lossy(scope);
+    let referrer_path = resource_name.strip_prefix("file://").map(PathBuf::from);
+
+    // Dynamic imports pass each attribute as just a key and a value
+    let module = ImportType::from_attributes(scope, &specifier_str, import_attributes, 2).and_then(
+        |import_type| resolve_module(scope, &specifier_str, referrer_path.as_deref(), import_type),
+    );
+    let module = match module {
+        Ok(module) => module,
         Err(e) => {
-            if let Some(error_msg) = v8::String::new(
+            let error = create_js_error(
                 scope,
                 &format!("Failed to load module '{}': {}", specifier_str, e),
-            ) {
-                resolver.reject(scope, error_msg.into());
-            }
+            );
+            resolver.reject(scope, error);
+            return Some(promise);
+        }
+    };
+
+    // Use TryCatch to capture exceptions during instantiation
+    v8::tc_scope!(let scope, scope);
+
+    // Both do nothing for a module that an earlier import already ran
+    let evaluation = module
+        .instantiate_module(scope, module_resolve_callback)
+        .and_then(|_| module.evaluate(scope));
+    let Some(evaluation) = evaluation else {
+        let error = match scope.exception() {
+            Some(exception) => exception,
+            None => create_js_error(
+                scope,
+                &format!("Failed to instantiate module '{}'", specifier_str),
+            ),
+        };
+        resolver.reject(scope, error);
+        return Some(promise);
+    };
+
+    // Evaluation settles once the module's top-level await has, and the import
+    // settles with it: with the namespace, or with the module's error
+    let namespace = module.get_module_namespace();
+    match v8::Local::<v8::Promise>::try_from(evaluation) {
+        Ok(evaluation) => {
+            let to_namespace = v8::Function::builder(return_namespace)
+                .data(namespace)
+                .build(scope)?;
+            let imported = evaluation.then(scope, to_namespace)?;
+            resolver.resolve(scope, imported.into());
+        }
+        Err(_) => {
+            resolver.resolve(scope, namespace);
         }
     }
 
     Some(promise)
 }
+
+/// Promise callback: fulfils a dynamic import with the module namespace in its data.
+fn return_namespace<'s>(
+    _scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    mut rv: v8::ReturnValue<'s>,
+) {
+    rv.set(args.data());
+}
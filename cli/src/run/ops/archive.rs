@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use util::zip::{extract_archive, zip_directory, ZipOptions};
+use v8;
+
+use crate::errors::MycoError;
+use crate::run::ops::filesystem::resolve_path;
+use crate::run::ops::macros::{async_op, get_state, sync_op};
+use crate::run::state::OpResult;
+use crate::{impl_from_v8_struct, register_async_op, register_sync_op};
+
+struct ZipArg {
+    src_token: String,
+    path: String,
+    dst_token: String,
+}
+
+impl_from_v8_struct!(ZipArg {
+    src_token: String,
+    path: String,
+    dst_token: String,
+});
+
+struct UnzipArg {
+    src_token: String,
+    dst_token: String,
+    path: String,
+}
+
+impl_from_v8_struct!(UnzipArg {
+    src_token: String,
+    dst_token: String,
+    path: String,
+});
+
+pub fn register_archive_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_async_op!(scope, myco_ops, "zip", async_op_zip);
+    register_async_op!(scope, myco_ops, "unzip", async_op_unzip);
+
+    register_sync_op!(scope, myco_ops, "zip", sync_op_zip);
+    register_sync_op!(scope, myco_ops, "unzip", sync_op_unzip);
+
+    Ok(())
+}
+
+fn resolve_zip_paths(
+    scope: &mut v8::PinScope<'_, '_>,
+    input: ZipArg,
+) -> Result<(PathBuf, PathBuf), MycoError> {
+    let state = get_state(scope)?;
+    let src = resolve_path(state, &input.src_token, Some(input.path), "read")?;
+    let dst = resolve_path(state, &input.dst_token, None, "write")?;
+    Ok((src, dst))
+}
+
+fn resolve_unzip_paths(
+    scope: &mut v8::PinScope<'_, '_>,
+    input: UnzipArg,
+) -> Result<(PathBuf, PathBuf), MycoError> {
+    let state = get_state(scope)?;
+    let src = resolve_path(state, &input.src_token, None, "read")?;
+    let dst = resolve_path(state, &input.dst_token, Some(input.path), "write")?;
+    Ok((src, dst))
+}
+
+/// Archives `src` into `dst`. Entry names are relative to `src`, and symlinks are
+/// skipped so the archive cannot pick up files from outside the read token.
+fn zip(src: &Path, dst: &Path) -> Result<(), String> {
+    let src_str = src.to_string_lossy().to_string();
+    zip_directory(
+        &src_str,
+        dst.to_string_lossy(),
+        ZipOptions {
+            strip_prefix: Some(src_str.clone()),
+            follow_symlinks: false,
+            ..ZipOptions::default()
+        },
+    )
+    .map_err(|e| format!("Failed to create archive '{}': {}", dst.display(), e))
+}
+
+fn unzip(src: &Path, dst: &Path) -> Result<Vec<String>, String> {
+    let error = |e: String| format!("Failed to extract archive '{}': {}", src.display(), e);
+    std::fs::create_dir_all(dst).map_err(|e| error(e.to_string()))?;
+    let file = std::fs::File::open(src).map_err(|e| error(e.to_string()))?;
+    extract_archive(std::io::BufReader::new(file), dst).map_err(|e| error(e.to_string()))
+}
+
+fn async_op_zip<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: ZipArg| resolve_zip_paths(scope, input),
+        |(src, dst)| async move {
+            let result = tokio::task::spawn_blocking(move || zip(&src, &dst))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()));
+
+            OpResult::Void(result)
+        },
+    );
+}
+
+fn async_op_unzip<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: UnzipArg| resolve_unzip_paths(scope, input),
+        |(src, dst)| async move {
+            let result = tokio::task::spawn_blocking(move || unzip(&src, &dst))
+                .await
+                .unwrap_or_else(|e| Err(e.to_string()))
+                .map(|names| serde_json::to_string(&names).unwrap());
+
+            OpResult::Json(result)
+        },
+    );
+}
+
+fn sync_op_zip<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ZipArg| -> Result<(), MycoError> {
+            let (src, dst) = resolve_zip_paths(scope, input)?;
+            zip(&src, &dst).map_err(|message| MycoError::Internal { message })
+        },
+    );
+}
+
+fn sync_op_unzip<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: UnzipArg| -> Result<Vec<String>, MycoError> {
+            let (src, dst) = resolve_unzip_paths(scope, input)?;
+            unzip(&src, &dst).map_err(|message| MycoError::Internal { message })
+        },
+    );
+}
@@ -1,32 +1,147 @@
 use crate::errors::MycoError;
-use crate::register_sync_op;
-use crate::run::ops::macros::sync_op;
-use crate::run::stack_trace::capture_call_site_stack;
-use crate::{impl_from_v8_struct, impl_from_v8_unit_struct};
+use crate::run::ops::macros::{get_state, sync_op};
+use crate::run::stack_trace::{capture_call_site, capture_call_site_stack};
+use crate::run::state::LogFormat;
+use crate::{impl_from_v8_struct, impl_from_v8_unit_struct, impl_to_v8_struct, register_sync_op};
+use log::{Level, LevelFilter};
+use std::io::{self, IsTerminal, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
 use v8;
 
-struct MessageArg {
+struct WriteArg {
+    level: String,
     message: String,
+    // The call's arguments as a JSON array, for the JSON log format
+    args: Option<String>,
 }
 
-impl_from_v8_struct!(MessageArg { message: String });
+impl_from_v8_struct!(WriteArg {
+    level: String,
+    message: String,
+    args: Option<String>,
+});
+
+struct LevelArg {
+    level: String,
+}
+
+impl_from_v8_struct!(LevelArg { level: String });
+
+struct PromiseArg {
+    promise: v8::Global<v8::Value>,
+}
+
+impl_from_v8_struct!(PromiseArg {
+    promise: v8::Global<v8::Value>,
+});
+
+struct PromiseState {
+    state: String,
+    result: Option<v8::Global<v8::Value>>,
+}
+
+impl_to_v8_struct!(PromiseState { state, result });
 
 struct EmptyArg;
 
 impl_from_v8_unit_struct!(EmptyArg);
 
+/// The stream a console level is written to.
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn for_level(level: &str) -> Result<Self, MycoError> {
+        match level {
+            "log" | "info" | "debug" | "trace" => Ok(Stream::Stdout),
+            "warn" | "error" => Ok(Stream::Stderr),
+            _ => Err(MycoError::Internal {
+                message: format!("Unknown console level '{}'", level),
+            }),
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        match self {
+            Stream::Stdout => io::stdout().is_terminal(),
+            Stream::Stderr => io::stderr().is_terminal(),
+        }
+    }
+
+    fn write_line(&self, line: &str) {
+        // A closed pipe is not the script's problem, so write errors are dropped
+        let _ = match self {
+            Stream::Stdout => writeln!(io::stdout(), "{}", line),
+            Stream::Stderr => writeln!(io::stderr(), "{}", line),
+        };
+    }
+}
+
+/// Whether console output at `level` is shown. Every level is checked against
+/// `--log-level` the way Rust's own logging is, except that the console always
+/// shows at least `info`, so `log` and `trace` output never needs a flag while
+/// `debug` output only shows with `--log-level debug` or `trace`.
+fn console_enabled(level: &str) -> bool {
+    let level = match level {
+        "error" => Level::Error,
+        "warn" => Level::Warn,
+        "debug" => Level::Debug,
+        _ => Level::Info,
+    };
+    level <= log::max_level().max(LevelFilter::Info)
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp with millisecond precision.
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let days = (seconds / 86_400) as i64;
+    let seconds_of_day = seconds % 86_400;
+
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
 pub fn register_console_ops(
     scope: &mut v8::PinScope<'_, '_>,
     myco_ops: &v8::Object,
 ) -> Result<(), MycoError> {
-    register_sync_op!(scope, myco_ops, "print", sync_op_print);
-    register_sync_op!(scope, myco_ops, "eprint", sync_op_eprint);
+    register_sync_op!(scope, myco_ops, "console_write", sync_op_console_write);
+    register_sync_op!(scope, myco_ops, "console_colors", sync_op_console_colors);
+    register_sync_op!(scope, myco_ops, "console_json", sync_op_console_json);
+    register_sync_op!(scope, myco_ops, "promise_state", sync_op_promise_state);
     register_sync_op!(scope, myco_ops, "trace", sync_op_trace);
 
     Ok(())
 }
 
-fn sync_op_print<'s>(
+fn sync_op_console_write<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     args: v8::FunctionCallbackArguments<'s>,
     rv: v8::ReturnValue,
@@ -35,14 +150,36 @@ fn sync_op_print<'s>(
         scope,
         &args,
         rv,
-        |_scope, input: MessageArg| -> Result<(), MycoError> {
-            print!("{}", input.message);
+        |scope, input: WriteArg| -> Result<(), MycoError> {
+            let stream = Stream::for_level(&input.level)?;
+            if !console_enabled(&input.level) {
+                return Ok(());
+            }
+            if get_state(scope)?.log_format == LogFormat::Text {
+                stream.write_line(&input.message);
+                return Ok(());
+            }
+
+            let args = match input.args {
+                Some(args) => serde_json::from_str(&args).map_err(|e| MycoError::Internal {
+                    message: format!("Invalid console arguments: {}", e),
+                })?,
+                None => serde_json::Value::Array(Vec::new()),
+            };
+            let record = serde_json::json!({
+                "level": input.level,
+                "timestamp": format_timestamp(SystemTime::now()),
+                "message": input.message,
+                "args": args,
+                "location": capture_call_site(scope),
+            });
+            stream.write_line(&record.to_string());
             Ok(())
         },
     );
 }
 
-fn sync_op_eprint<'s>(
+fn sync_op_console_colors<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     args: v8::FunctionCallbackArguments<'s>,
     rv: v8::ReturnValue,
@@ -51,9 +188,61 @@ fn sync_op_eprint<'s>(
         scope,
         &args,
         rv,
-        |_scope, input: MessageArg| -> Result<(), MycoError> {
-            eprint!("{}", input.message);
-            Ok(())
+        |scope, input: LevelArg| -> Result<bool, MycoError> {
+            // `--no-color` and NO_COLOR both switch off `colored`'s global setting
+            let stream = Stream::for_level(&input.level)?;
+            Ok(get_state(scope)?.log_format == LogFormat::Text
+                && colored::control::SHOULD_COLORIZE.should_colorize()
+                && stream.is_terminal())
+        },
+    );
+}
+
+fn sync_op_console_json<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, _input: EmptyArg| -> Result<bool, MycoError> {
+            Ok(get_state(scope)?.log_format == LogFormat::Json)
+        },
+    );
+}
+
+fn sync_op_promise_state<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: PromiseArg| -> Result<PromiseState, MycoError> {
+            let value = v8::Local::new(scope, &input.promise);
+            let promise =
+                v8::Local::<v8::Promise>::try_from(value).map_err(|_| MycoError::Internal {
+                    message: "Expected a promise".to_string(),
+                })?;
+            let state = match promise.state() {
+                v8::PromiseState::Pending => {
+                    return Ok(PromiseState {
+                        state: "pending".to_string(),
+                        result: None,
+                    })
+                }
+                v8::PromiseState::Fulfilled => "fulfilled",
+                v8::PromiseState::Rejected => "rejected",
+            };
+            let result = promise.result(scope);
+            Ok(PromiseState {
+                state: state.to_string(),
+                result: Some(v8::Global::new(scope, result)),
+            })
         },
     );
 }
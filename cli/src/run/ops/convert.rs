@@ -57,6 +57,9 @@ pub enum ConvertError {
     #[error("invalid type; expected: string, got: {0}")]
     ExpectedString(&'static str),
 
+    #[error("invalid type; expected: boolean, got: {0}")]
+    ExpectedBoolean(&'static str),
+
     #[error("invalid type; expected: array, got: {0}")]
     ExpectedArray(&'static str),
 
@@ -66,6 +69,9 @@ pub enum ConvertError {
     #[error("invalid type; expected: buffer, got: {0}")]
     ExpectedBuffer(&'static str),
 
+    #[error("invalid type; expected: function, got: {0}")]
+    ExpectedFunction(&'static str),
+
     #[error("unsupported type")]
     UnsupportedType,
 
@@ -286,6 +292,22 @@ impl FromV8 for String {
     }
 }
 
+impl FromV8 for bool {
+    fn from_v8<'s>(
+        _scope: &mut v8::PinScope<'s, '_>,
+        value: v8::Local<'s, v8::Value>,
+    ) -> ConvertResult<Self> {
+        // Deliberate divergence from serde_v8: it coerced any value through
+        // `is_true()`, so a truthy string silently became `false`. No op took a
+        // boolean while serde_v8 was in use, so nothing depends on that.
+        if value.is_boolean() {
+            Ok(value.is_true())
+        } else {
+            Err(ConvertError::ExpectedBoolean(value.type_repr()))
+        }
+    }
+}
+
 impl FromV8 for f64 {
     fn from_v8<'s>(
         _scope: &mut v8::PinScope<'s, '_>,
@@ -372,6 +394,118 @@ impl FromV8 for JsBuffer {
     }
 }
 
+// Functions are held as globals so that an op can keep a callback past the
+// current scope. serde_v8 could not carry functions at all.
+impl FromV8 for v8::Global<v8::Function> {
+    fn from_v8<'s>(
+        scope: &mut v8::PinScope<'s, '_>,
+        value: v8::Local<'s, v8::Value>,
+    ) -> ConvertResult<Self> {
+        let function = v8::Local::<v8::Function>::try_from(value)
+            .map_err(|_| ConvertError::ExpectedFunction(value.type_repr()))?;
+        Ok(v8::Global::new(scope, function))
+    }
+}
+
+// Any value, held as is, for ops that only need to look at it or hand it back.
+impl FromV8 for v8::Global<v8::Value> {
+    fn from_v8<'s>(
+        scope: &mut v8::PinScope<'s, '_>,
+        value: v8::Local<'s, v8::Value>,
+    ) -> ConvertResult<Self> {
+        Ok(v8::Global::new(scope, value))
+    }
+}
+
+/// A value in V8's structured-clone wire format, for messages that cross into
+/// another isolate. Values that cannot be cloned (functions, symbols, ...) are
+/// rejected with V8's own `DataCloneError` message.
+pub struct StructuredClone(pub Vec<u8>);
+
+struct CloneDelegate;
+
+impl v8::ValueSerializerImpl for CloneDelegate {
+    fn throw_data_clone_error<'s>(
+        &self,
+        scope: &mut v8::PinScope<'s, '_>,
+        message: v8::Local<'s, v8::String>,
+    ) {
+        let error = v8::Exception::error(scope, message);
+        scope.throw_exception(error);
+    }
+}
+
+impl v8::ValueDeserializerImpl for CloneDelegate {}
+
+impl FromV8 for StructuredClone {
+    fn from_v8<'s>(
+        scope: &mut v8::PinScope<'s, '_>,
+        value: v8::Local<'s, v8::Value>,
+    ) -> ConvertResult<Self> {
+        use v8::ValueSerializerHelper;
+
+        v8::tc_scope!(let scope, scope);
+        let context = scope.get_current_context();
+        let serializer = v8::ValueSerializer::new(scope, Box::new(CloneDelegate));
+        serializer.write_header();
+        if serializer.write_value(context, value) == Some(true) {
+            return Ok(StructuredClone(serializer.release()));
+        }
+        let message = scope
+            .exception()
+            .map(|exception| exception.to_rust_string_lossy(scope))
+            .unwrap_or_else(|| "value could not be cloned".to_string());
+        Err(ConvertError::Message(message))
+    }
+}
+
+impl StructuredClone {
+    /// Rebuilds the value in the current context.
+    pub fn deserialize<'s>(
+        scope: &mut v8::PinScope<'s, '_>,
+        bytes: &[u8],
+    ) -> Option<v8::Local<'s, v8::Value>> {
+        use v8::ValueDeserializerHelper;
+
+        let context = scope.get_current_context();
+        let deserializer = v8::ValueDeserializer::new(scope, Box::new(CloneDelegate), bytes);
+        deserializer.read_header(context)?;
+        deserializer.read_value(context)
+    }
+}
+
+// String-keyed maps are read from a plain object's own enumerable properties.
+// Keys whose value is `undefined` are dropped, as `serde_json::Value` does
+// above. serde_v8 never converted into a typed map for us, so there is no
+// behaviour to preserve here.
+impl<T: FromV8> FromV8 for std::collections::BTreeMap<String, T> {
+    fn from_v8<'s>(
+        scope: &mut v8::PinScope<'s, '_>,
+        value: v8::Local<'s, v8::Value>,
+    ) -> ConvertResult<Self> {
+        let obj = expect_object(value)?;
+        let names = obj
+            .get_own_property_names(
+                scope,
+                v8::GetPropertyNamesArgsBuilder::new()
+                    .key_conversion(v8::KeyConversionMode::ConvertToString)
+                    .build(),
+            )
+            .ok_or(ConvertError::V8Exception)?;
+        let mut map = std::collections::BTreeMap::new();
+        for i in 0..names.length() {
+            let key = names.get_index(scope, i).ok_or(ConvertError::V8Exception)?;
+            let entry = obj.get(scope, key).ok_or(ConvertError::V8Exception)?;
+            if entry.is_undefined() {
+                continue;
+            }
+            let key = String::from_v8(scope, key)?;
+            map.insert(key, T::from_v8(scope, entry)?);
+        }
+        Ok(map)
+    }
+}
+
 fn check_backing_store(
     buffer: v8::Local<v8::ArrayBuffer>,
     value: v8::Local<v8::Value>,
@@ -540,10 +674,15 @@ fn bigint_to_f64(value: v8::Local<v8::BigInt>) -> f64 {
 // --- Field defaults --------------------------------------------------------
 
 impl Field for String {}
+impl Field for bool {}
 impl Field for f64 {}
 impl Field for JsBuffer {}
 impl Field for serde_json::Value {}
+impl Field for v8::Global<v8::Function> {}
+impl Field for v8::Global<v8::Value> {}
+impl Field for StructuredClone {}
 impl<T: FromV8> Field for Vec<T> {}
+impl<T: FromV8> Field for std::collections::BTreeMap<String, T> {}
 
 impl<T: FromV8> Field for Option<T> {
     fn missing(_name: &'static str) -> ConvertResult<Self> {
@@ -644,6 +783,20 @@ impl<T: ToV8> ToV8 for Vec<T> {
     }
 }
 
+impl ToV8 for v8::Global<v8::Value> {
+    fn to_v8<'s>(self, scope: &mut v8::PinScope<'s, '_>) -> v8::Local<'s, v8::Value> {
+        v8::Local::new(scope, self)
+    }
+}
+
+impl ToV8 for StructuredClone {
+    // Only ever given bytes this runtime serialized itself, so a failure here is a
+    // bug rather than bad input; it surfaces as `undefined`.
+    fn to_v8<'s>(self, scope: &mut v8::PinScope<'s, '_>) -> v8::Local<'s, v8::Value> {
+        StructuredClone::deserialize(scope, &self.0).unwrap_or_else(|| v8::undefined(scope).into())
+    }
+}
+
 impl ToV8 for ToJsBuffer {
     fn to_v8<'s>(self, scope: &mut v8::PinScope<'s, '_>) -> v8::Local<'s, v8::Value> {
         let bytes = self.0.into_boxed_slice();
@@ -0,0 +1,132 @@
+use std::io::Read;
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use v8;
+
+use crate::errors::MycoError;
+use crate::run::ops::convert::{JsBuffer, ToJsBuffer};
+use crate::run::ops::macros::{async_op, sync_op};
+use crate::run::state::OpResult;
+use crate::{impl_from_v8_struct, register_async_op, register_sync_op};
+
+struct DigestArg {
+    algorithm: String,
+    bytes: JsBuffer,
+}
+
+impl_from_v8_struct!(DigestArg {
+    algorithm: String,
+    bytes: JsBuffer,
+});
+
+/// An incremental hasher for one of the supported digest algorithms.
+pub enum Hasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    /// Accepts both the WebCrypto spelling (`SHA-256`) and the short one (`sha256`).
+    pub fn new(algorithm: &str) -> Result<Self, MycoError> {
+        match algorithm.to_ascii_lowercase().replace('-', "").as_str() {
+            "sha1" => Ok(Hasher::Sha1(Sha1::new())),
+            "sha256" => Ok(Hasher::Sha256(Sha256::new())),
+            "sha512" => Ok(Hasher::Sha512(Sha512::new())),
+            "blake3" => Ok(Hasher::Blake3(Box::new(blake3::Hasher::new()))),
+            _ => Err(MycoError::Internal {
+                message: format!("Unsupported digest algorithm '{}'", algorithm),
+            }),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha1(hasher) => hasher.update(bytes),
+            Hasher::Sha256(hasher) => hasher.update(bytes),
+            Hasher::Sha512(hasher) => hasher.update(bytes),
+            Hasher::Blake3(hasher) => {
+                hasher.update(bytes);
+            }
+        }
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        match self {
+            Hasher::Sha1(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha256(hasher) => hasher.finalize().to_vec(),
+            Hasher::Sha512(hasher) => hasher.finalize().to_vec(),
+            Hasher::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        }
+    }
+
+    /// Feeds `reader` through the hasher in fixed-size chunks, so large files are
+    /// never held in memory.
+    pub fn update_reader(&mut self, mut reader: impl Read) -> std::io::Result<()> {
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            self.update(&buffer[..read]);
+        }
+    }
+}
+
+pub fn register_crypto_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_sync_op!(scope, myco_ops, "digest", sync_op_digest);
+    register_async_op!(scope, myco_ops, "digest", async_op_digest);
+
+    Ok(())
+}
+
+fn sync_op_digest<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: DigestArg| -> Result<ToJsBuffer, MycoError> {
+            let mut hasher = Hasher::new(&input.algorithm)?;
+            hasher.update(&input.bytes);
+            Ok(ToJsBuffer::from(hasher.finalize()))
+        },
+    );
+}
+
+fn async_op_digest<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |_scope, input: DigestArg| {
+            let hasher = Hasher::new(&input.algorithm)?;
+            Ok((hasher, input.bytes))
+        },
+        |(mut hasher, bytes)| async move {
+            let result = tokio::task::spawn_blocking(move || {
+                hasher.update(&bytes);
+                hasher.finalize()
+            })
+            .await
+            .map_err(|e| format!("Failed to compute digest: {}", e));
+
+            OpResult::Binary(result)
+        },
+    );
+}
@@ -1,10 +1,23 @@
 use crate::errors::MycoError;
 use crate::impl_from_v8_struct;
 use crate::register_sync_op;
-use crate::run::ops::convert::{JsBuffer, ToJsBuffer};
+use crate::run::ops::convert::{JsBuffer, StructuredClone, ToJsBuffer};
 use crate::run::ops::macros::sync_op;
+use base64::alphabet;
+use base64::engine::general_purpose::{self, GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine as _;
 use v8;
 
+/// Standard base64 that decodes the way `atob` is specified to: padding is
+/// stripped beforehand, and bits left over after the last byte are ignored.
+const FORGIVING_BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::RequireNone)
+        .with_decode_allow_trailing_bits(true),
+);
+
 struct TextArg {
     text: String,
 }
@@ -17,12 +30,34 @@ struct BytesArg {
 
 impl_from_v8_struct!(BytesArg { bytes: JsBuffer });
 
+struct DataArg {
+    data: String,
+}
+
+impl_from_v8_struct!(DataArg { data: String });
+
+struct CloneArg {
+    value: StructuredClone,
+}
+
+impl_from_v8_struct!(CloneArg {
+    value: StructuredClone
+});
+
 pub fn register_encoding_ops(
     scope: &mut v8::PinScope<'_, '_>,
     myco_ops: &v8::Object,
 ) -> Result<(), MycoError> {
     register_sync_op!(scope, myco_ops, "encode_utf8", sync_op_encode_utf8);
     register_sync_op!(scope, myco_ops, "decode_utf8", sync_op_decode_utf8);
+    register_sync_op!(scope, myco_ops, "btoa", sync_op_btoa);
+    register_sync_op!(scope, myco_ops, "atob", sync_op_atob);
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "structured_clone",
+        sync_op_structured_clone
+    );
 
     Ok(())
 }
@@ -62,3 +97,69 @@ fn sync_op_decode_utf8<'s>(
         },
     );
 }
+
+fn sync_op_btoa<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: DataArg| -> Result<Option<String>, MycoError> {
+            // Each character is one byte, so anything outside Latin-1 cannot be
+            // encoded; `null` lets the runtime throw an InvalidCharacterError
+            let bytes: Option<Vec<u8>> = input
+                .data
+                .chars()
+                .map(|c| u8::try_from(u32::from(c)).ok())
+                .collect();
+            Ok(bytes.map(|bytes| general_purpose::STANDARD.encode(bytes)))
+        },
+    );
+}
+
+fn sync_op_atob<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: DataArg| -> Result<Option<String>, MycoError> {
+            let mut data: String = input
+                .data
+                .chars()
+                .filter(|c| !matches!(c, '\t' | '\n' | '\x0C' | '\r' | ' '))
+                .collect();
+            if data.len() % 4 == 0 {
+                for _ in 0..2 {
+                    if data.ends_with('=') {
+                        data.pop();
+                    }
+                }
+            }
+            // Decoded bytes come back as one character each, as Latin-1
+            Ok(FORGIVING_BASE64
+                .decode(&data)
+                .ok()
+                .map(|bytes| bytes.into_iter().map(char::from).collect()))
+        },
+    );
+}
+
+fn sync_op_structured_clone<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: CloneArg| -> Result<StructuredClone, MycoError> { Ok(input.value) },
+    );
+}
@@ -1,15 +1,23 @@
+use std::collections::BTreeMap;
 use std::fs::Metadata;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use rand::{thread_rng, Rng};
 
 use serde::Serialize;
+use tokio::sync::{mpsc, Mutex};
 use v8;
 
 use crate::errors::MycoError;
 use crate::run::ops::convert::{JsBuffer, ToJsBuffer};
+use crate::run::ops::crypto::Hasher;
 use crate::run::ops::macros::{
     async_op, create_rejected_promise, create_resolved_promise_void, get_state, get_string_arg,
     sync_op,
 };
+use crate::run::ops::process::{apply_grants, command, inherited_env, run_command, ExecLimits};
 use crate::run::state::{MycoState, OpResult};
 use crate::Capability;
 use crate::{impl_from_v8_struct, impl_to_v8_struct, register_async_op, register_sync_op};
@@ -38,24 +46,56 @@ struct WriteFileArg {
     token: String,
     contents: JsBuffer,
     path: Option<String>,
+    atomic: Option<bool>,
 }
 
 impl_from_v8_struct!(WriteFileArg {
     token: String,
     contents: JsBuffer,
     path: Option<String>,
+    atomic: Option<bool>,
+});
+
+struct HashFileArg {
+    token: String,
+    path: Option<String>,
+    algorithm: String,
+}
+
+impl_from_v8_struct!(HashFileArg {
+    token: String,
+    path: Option<String>,
+    algorithm: String,
 });
 
+struct TokenArg {
+    token: String,
+}
+
+impl_from_v8_struct!(TokenArg { token: String });
+
 struct ExecFileArg {
     token: String,
     path: Option<String>,
     args: Vec<String>,
+    env: Option<BTreeMap<String, String>>,
+    inherit_env: Option<Vec<String>>,
+    cwd: Option<String>,
+    grant: Option<Vec<String>>,
+    timeout: Option<f64>,
+    max_output: Option<f64>,
 }
 
 impl_from_v8_struct!(ExecFileArg {
     token: String,
     path: Option<String>,
     args: Vec<String>,
+    env: Option<BTreeMap<String, String>>,
+    inherit_env: Option<Vec<String>>,
+    cwd: Option<String>,
+    grant: Option<Vec<String>>,
+    timeout: Option<f64>,
+    max_output: Option<f64>,
 });
 
 struct PathArg {
@@ -64,6 +104,26 @@ struct PathArg {
 
 impl_from_v8_struct!(PathArg { path: String });
 
+struct GlobArg {
+    token: String,
+    path: String,
+    pattern: Option<String>,
+    ignore: Option<Vec<String>>,
+    gitignore: Option<bool>,
+    follow_symlinks: Option<bool>,
+    extensions: Option<Vec<String>>,
+}
+
+impl_from_v8_struct!(GlobArg {
+    token: String,
+    path: String,
+    pattern: Option<String>,
+    ignore: Option<Vec<String>>,
+    gitignore: Option<bool>,
+    follow_symlinks: Option<bool>,
+    extensions: Option<Vec<String>>,
+});
+
 pub fn register_filesystem_ops(
     scope: &mut v8::PinScope<'_, '_>,
     myco_ops: &v8::Object,
@@ -113,6 +173,9 @@ pub fn register_filesystem_ops(
     register_async_op!(scope, myco_ops, "rmdir", async_op_rmdir);
     register_async_op!(scope, myco_ops, "rmdir_recursive", async_op_rmdir_recursive);
     register_async_op!(scope, myco_ops, "exec_file", async_op_exec_file);
+    register_async_op!(scope, myco_ops, "glob_next", async_op_glob_next);
+    register_async_op!(scope, myco_ops, "lock_file", async_op_lock_file);
+    register_async_op!(scope, myco_ops, "hash_file", async_op_hash_file);
     register_sync_op!(scope, myco_ops, "read_file", sync_op_read_file);
     register_sync_op!(scope, myco_ops, "write_file", sync_op_write_file);
     register_sync_op!(scope, myco_ops, "remove_file", sync_op_remove_file);
@@ -121,6 +184,13 @@ pub fn register_filesystem_ops(
     register_sync_op!(scope, myco_ops, "mkdirp", sync_op_mkdirp);
     register_sync_op!(scope, myco_ops, "rmdir", sync_op_rmdir);
     register_sync_op!(scope, myco_ops, "exec_file", sync_op_exec_file);
+    register_sync_op!(scope, myco_ops, "glob", sync_op_glob);
+    register_sync_op!(scope, myco_ops, "glob_open", sync_op_glob_open);
+    register_sync_op!(scope, myco_ops, "glob_close", sync_op_glob_close);
+    register_sync_op!(scope, myco_ops, "lock_file", sync_op_lock_file);
+    register_sync_op!(scope, myco_ops, "try_lock_file", sync_op_try_lock_file);
+    register_sync_op!(scope, myco_ops, "unlock_file", sync_op_unlock_file);
+    register_sync_op!(scope, myco_ops, "hash_file", sync_op_hash_file);
     register_sync_op!(scope, myco_ops, "cwd", sync_op_cwd);
     register_sync_op!(scope, myco_ops, "chdir", sync_op_chdir);
     Ok(())
@@ -541,7 +611,7 @@ fn canonical(dir: String, path: String) -> Result<PathBuf, MycoError> {
     }
 }
 
-fn resolve_path(
+pub(crate) fn resolve_path(
     state: &MycoState,
     token: &str,
     path: Option<String>,
@@ -574,6 +644,389 @@ fn resolve_path(
     }
 }
 
+/// Resolves a directory token of any kind to its canonical directory, for
+/// options such as a child's working directory that take a whole directory.
+pub(crate) fn resolve_dir(state: &MycoState, token: &str) -> Result<PathBuf, MycoError> {
+    match state.capabilities.get(token) {
+        Some(Capability::ReadDir(dir))
+        | Some(Capability::WriteDir(dir))
+        | Some(Capability::ExecDir(dir)) => canonical(dir.clone(), "/".to_string()),
+        _ => Err(MycoError::Internal {
+            message: "Invalid token for directory access".to_string(),
+        }),
+    }
+}
+
+fn prepare_exec(
+    scope: &mut v8::PinScope<'_, '_>,
+    input: ExecFileArg,
+) -> Result<(std::process::Command, ExecLimits, PathBuf), MycoError> {
+    let state = get_state(scope)?;
+    let path_buf = resolve_path(state, &input.token, input.path.clone(), "exec")?;
+    let cwd = match &input.cwd {
+        Some(token) => Some(resolve_dir(state, token)?),
+        None => None,
+    };
+    let inherit_env = inherited_env(state, input.inherit_env)?;
+    let mut command = command(&path_buf, &input.args, input.env, inherit_env, cwd);
+    if let Some(grant) = &input.grant {
+        apply_grants(&mut command, state, &path_buf, grant)?;
+    }
+    let limits = ExecLimits::new(input.timeout, input.max_output)?;
+    Ok((command, limits, path_buf))
+}
+
+// Atomic writes and advisory locks
+
+/// Writes `contents` to a temporary sibling of `path`, fsyncs it and renames it
+/// over `path`, so readers see either the old contents or the new ones but never
+/// a truncated file. The existing file's permissions are carried over.
+fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let parent = path.parent().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no parent")
+    })?;
+    let file_name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "path has no file name")
+    })?;
+    let suffix: String = thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(8)
+        .map(char::from)
+        .collect();
+    let temp_path = parent.join(format!(".{}.{}.tmp", file_name.to_string_lossy(), suffix));
+
+    let result = (|| {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        // Persist the rename itself
+        #[cfg(unix)]
+        std::fs::File::open(parent)?.sync_all()?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_file_contents(path: &Path, contents: &[u8], atomic: bool) -> std::io::Result<()> {
+    if atomic {
+        write_atomic(path, contents)
+    } else {
+        std::fs::write(path, contents)
+    }
+}
+
+/// The file a lock on `path` in a write-dir token's directory is taken on:
+/// `<path>.lock`, next to it, which must be in the directory too. Atomic writes
+/// rename a new inode over `path` itself, which would silently drop a lock held on
+/// the old one, but nothing ever renames over the lock file. Single-file tokens
+/// cannot lock, since they do not grant the lock file.
+fn resolve_lock_path(state: &MycoState, token: &str, path: &str) -> Result<PathBuf, MycoError> {
+    match state.capabilities.get(token) {
+        Some(Capability::WriteDir(dir)) => canonical(dir.clone(), format!("{}.lock", path)),
+        _ => Err(MycoError::Internal {
+            message: "Only a write directory token can take a lock".to_string(),
+        }),
+    }
+}
+
+/// Takes an exclusive advisory `flock` on the lock file at `path`, creating it if
+/// needed. Returns `Ok(None)` when `wait` is false and another process holds the
+/// lock.
+///
+/// The lock file is left behind on release, since removing it would race with a
+/// process that has just opened it. If it is removed anyway, we notice that the
+/// path no longer names the inode we locked and start over.
+#[cfg(unix)]
+fn lock_file(path: &Path, wait: bool) -> std::io::Result<Option<std::fs::File>> {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+
+    loop {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let operation = if wait {
+            libc::LOCK_EX
+        } else {
+            libc::LOCK_EX | libc::LOCK_NB
+        };
+        if unsafe { libc::flock(file.as_raw_fd(), operation) } != 0 {
+            let error = std::io::Error::last_os_error();
+            return match error.kind() {
+                std::io::ErrorKind::WouldBlock if !wait => Ok(None),
+                std::io::ErrorKind::Interrupted => continue,
+                _ => Err(error),
+            };
+        }
+
+        let locked = file.metadata()?;
+        match std::fs::metadata(path) {
+            Ok(current) if current.dev() == locked.dev() && current.ino() == locked.ino() => {
+                return Ok(Some(file));
+            }
+            _ => continue,
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn lock_file(_path: &Path, _wait: bool) -> std::io::Result<Option<std::fs::File>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "file locking is not supported on this platform",
+    ))
+}
+
+// Directory walking
+struct GlobOptions {
+    matcher: Option<globset::GlobMatcher>,
+    ignore: globset::GlobSet,
+    gitignore: bool,
+    follow_symlinks: bool,
+    extensions: Option<Vec<String>>,
+}
+
+impl GlobOptions {
+    /// Compiles the patterns up front, so a bad pattern is reported when the walk
+    /// is started rather than part way through it. `.gitignore` files are honoured
+    /// unless the caller opts out.
+    fn from_arg(input: &GlobArg) -> Result<Self, MycoError> {
+        let compile = || -> Result<Self, String> {
+            let matcher = match &input.pattern {
+                Some(pattern) => Some(compile_glob(pattern)?.compile_matcher()),
+                None => None,
+            };
+            let mut ignore_builder = globset::GlobSetBuilder::new();
+            for pattern in input.ignore.iter().flatten() {
+                ignore_builder.add(compile_glob(pattern)?);
+            }
+            let ignore = ignore_builder
+                .build()
+                .map_err(|e| format!("Invalid ignore patterns: {}", e))?;
+            Ok(Self {
+                matcher,
+                ignore,
+                gitignore: input.gitignore.unwrap_or(true),
+                follow_symlinks: input.follow_symlinks.unwrap_or(false),
+                extensions: input
+                    .extensions
+                    .as_ref()
+                    .map(|exts| exts.iter().map(|ext| ext.to_lowercase()).collect()),
+            })
+        };
+        compile().map_err(|message| MycoError::Internal { message })
+    }
+}
+
+fn compile_glob(pattern: &str) -> Result<globset::Glob, String> {
+    globset::GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| format!("Invalid glob pattern '{}': {}", pattern, e))
+}
+
+fn load_gitignore(dir: &Path) -> Option<Gitignore> {
+    let path = dir.join(".gitignore");
+    if !path.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(dir);
+    builder.add(path);
+    builder.build().ok()
+}
+
+fn is_gitignored(gitignores: &[(usize, Gitignore)], path: &Path, is_dir: bool) -> bool {
+    // The innermost .gitignore that has an opinion wins, as in git itself.
+    for (_, gitignore) in gitignores.iter().rev() {
+        match gitignore.matched(path, is_dir) {
+            ignore::Match::Ignore(_) => return true,
+            ignore::Match::Whitelist(_) => return false,
+            ignore::Match::None => {}
+        }
+    }
+    false
+}
+
+/// Walks `root` natively, passing every matching entry below it to `visit` as
+/// it is found, named by its `/`-separated path relative to `root`. The walk is
+/// depth first in file name order: each directory comes right before its own
+/// contents. It stops early when `visit` returns false.
+///
+/// `scope_root` is the directory the token grants. Walking never leaves it:
+/// symlinks are only followed when their target resolves inside it, and
+/// `.gitignore` files are only read between `scope_root` and `root`.
+fn walk_dir(
+    root: &Path,
+    scope_root: &Path,
+    options: &GlobOptions,
+    mut visit: impl FnMut(String, Metadata) -> bool,
+) -> Result<(), String> {
+    // Depth 0 holds the .gitignore files of `root` and its ancestors inside the
+    // token; deeper entries are pushed and popped as the walk enters and leaves
+    // directories.
+    let mut gitignores: Vec<(usize, Gitignore)> = Vec::new();
+    if options.gitignore {
+        let mut ancestors: Vec<&Path> = root
+            .ancestors()
+            .take_while(|dir| dir.starts_with(scope_root))
+            .collect();
+        ancestors.reverse();
+        for dir in ancestors {
+            if let Some(gitignore) = load_gitignore(dir) {
+                gitignores.push((0, gitignore));
+            }
+        }
+    }
+
+    let relative_name = |path: &Path| -> String {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+
+    let walker = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .follow_links(options.follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let depth = entry.depth();
+            while gitignores
+                .last()
+                .is_some_and(|(d, _)| *d != 0 && *d >= depth)
+            {
+                gitignores.pop();
+            }
+
+            let is_dir = entry.file_type().is_dir();
+            if entry.path_is_symlink() && options.follow_symlinks {
+                match entry.path().canonicalize() {
+                    Ok(target) if target.starts_with(scope_root) => {}
+                    _ => return false,
+                }
+            }
+            if options.ignore.is_match(relative_name(entry.path())) {
+                return false;
+            }
+            if options.gitignore {
+                if is_dir && entry.file_name() == ".git" {
+                    return false;
+                }
+                if is_gitignored(&gitignores, entry.path(), is_dir) {
+                    return false;
+                }
+                if is_dir {
+                    if let Some(gitignore) = load_gitignore(entry.path()) {
+                        gitignores.push((depth, gitignore));
+                    }
+                }
+            }
+            true
+        });
+
+    for entry in walker {
+        let entry =
+            entry.map_err(|e| format!("Failed to walk directory '{}': {}", root.display(), e))?;
+        let name = relative_name(entry.path());
+
+        if let Some(matcher) = &options.matcher {
+            if !matcher.is_match(&name) {
+                continue;
+            }
+        }
+        if let Some(extensions) = &options.extensions {
+            let extension = entry
+                .path()
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase());
+            if !extension.is_some_and(|ext| extensions.contains(&ext)) {
+                continue;
+            }
+        }
+
+        let metadata = entry.metadata().map_err(|e| {
+            format!(
+                "Failed to get metadata for '{}': {}",
+                entry.path().display(),
+                e
+            )
+        })?;
+        if !visit(name, metadata) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// How many entries a streaming glob sends to JS at a time.
+const GLOB_BATCH_SIZE: usize = 256;
+
+type GlobBatches = mpsc::Receiver<Result<Vec<FileInfo>, String>>;
+
+/// A glob walk in progress, held in the capability registry under its own token.
+///
+/// The walk runs on a blocking thread and hands batches of entries over a
+/// bounded channel, so it only runs a few batches ahead of the script reading
+/// them. Dropping the capability closes the channel, which stops the walk.
+#[derive(Debug)]
+pub struct GlobWalk {
+    batches: Arc<Mutex<GlobBatches>>,
+}
+
+impl GlobWalk {
+    fn start(
+        runtime: &tokio::runtime::Handle,
+        root: PathBuf,
+        scope_root: PathBuf,
+        options: GlobOptions,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(4);
+        runtime.spawn_blocking(move || {
+            let mut batch = Vec::with_capacity(GLOB_BATCH_SIZE);
+            let result = walk_dir(&root, &scope_root, &options, |name, metadata| {
+                batch.push(FileInfo {
+                    name,
+                    stats: FileStats::from_metadata(metadata),
+                });
+                if batch.len() < GLOB_BATCH_SIZE {
+                    return true;
+                }
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(GLOB_BATCH_SIZE));
+                sender.blocking_send(Ok(full)).is_ok()
+            });
+            if !batch.is_empty() && sender.blocking_send(Ok(batch)).is_err() {
+                return;
+            }
+            if let Err(e) = result {
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+        Self {
+            batches: Arc::new(Mutex::new(receiver)),
+        }
+    }
+}
+
 // Data structures
 pub struct Stats {
     pub is_file: bool,
@@ -742,9 +1195,11 @@ fn sync_op_write_file<'s>(
         |scope, input: WriteFileArg| -> Result<(), MycoError> {
             let state = get_state(scope)?;
             let path_buf = resolve_path(state, &input.token, input.path.clone(), "write")?;
-            std::fs::write(&path_buf, input.contents).map_err(|e| MycoError::Internal {
-                message: format!("Failed to write file '{}': {}", path_buf.display(), e),
-            })
+            write_file_contents(&path_buf, &input.contents, input.atomic.unwrap_or(false)).map_err(
+                |e| MycoError::Internal {
+                    message: format!("Failed to write file '{}': {}", path_buf.display(), e),
+                },
+            )
         },
     );
 }
@@ -884,21 +1339,39 @@ fn sync_op_exec_file<'s>(
         &args,
         rv,
         |scope, input: ExecFileArg| -> Result<ExecResult, MycoError> {
-            let state = get_state(scope)?;
-            let path_buf = resolve_path(state, &input.token, input.path.clone(), "exec")?;
+            let (command, limits, path_buf) = prepare_exec(scope, input)?;
+            run_command(command, limits).map_err(|e| MycoError::Internal {
+                message: format!("Failed to execute command '{}': {}", path_buf.display(), e),
+            })
+        },
+    );
+}
 
-            let output = std::process::Command::new(&path_buf)
-                .args(input.args)
-                .output()
-                .map_err(|e| MycoError::Internal {
-                    message: format!("Failed to execute command '{}': {}", path_buf.display(), e),
-                })?;
+fn sync_op_glob<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: GlobArg| -> Result<Vec<File>, MycoError> {
+            let state = get_state(scope)?;
+            let scope_root = resolve_path(state, &input.token, Some("/".to_string()), "read")?;
+            let path_buf = resolve_path(state, &input.token, Some(input.path.clone()), "read")?;
+            let options = GlobOptions::from_arg(&input)?;
 
-            Ok(ExecResult {
-                stdout: output.stdout,
-                stderr: output.stderr,
-                exit_code: output.status.code().unwrap_or(-1),
+            let mut files = Vec::new();
+            walk_dir(&path_buf, &scope_root, &options, |name, metadata| {
+                files.push(File {
+                    name,
+                    stats: Stats::from_metadata(metadata),
+                });
+                true
             })
+            .map_err(|message| MycoError::Internal { message })?;
+            Ok(files)
         },
     );
 }
@@ -942,9 +1415,16 @@ fn async_op_write_file<'s>(
             Ok((input, path_buf))
         },
         |(input, path_buf)| async move {
-            let result = tokio::fs::write(&path_buf, input.contents)
-                .await
-                .map_err(|e| format!("Failed to write file '{}': {}", path_buf.display(), e));
+            let result = if input.atomic.unwrap_or(false) {
+                let target = path_buf.clone();
+                tokio::task::spawn_blocking(move || write_atomic(&target, &input.contents))
+                    .await
+                    .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+            } else {
+                tokio::fs::write(&path_buf, input.contents).await
+            };
+            let result =
+                result.map_err(|e| format!("Failed to write file '{}': {}", path_buf.display(), e));
 
             OpResult::Void(result)
         },
@@ -1163,21 +1643,11 @@ fn async_op_exec_file<'s>(
         scope,
         rv,
         &args,
-        |scope, input: ExecFileArg| {
-            let state = get_state(scope)?;
-            let path_buf = resolve_path(state, &input.token, input.path.clone(), "exec")?;
-            Ok((input, path_buf))
-        },
-        |(input, path_buf)| async move {
-            let result = tokio::process::Command::new(&path_buf)
-                .args(input.args)
-                .output()
+        |scope, input: ExecFileArg| prepare_exec(scope, input),
+        |(command, limits, path_buf)| async move {
+            let result = tokio::task::spawn_blocking(move || run_command(command, limits))
                 .await
-                .map(|output| ExecResult {
-                    stdout: output.stdout,
-                    stderr: output.stderr,
-                    exit_code: output.status.code().unwrap_or(-1),
-                })
+                .unwrap_or_else(|e| Err(e.to_string()))
                 .map(|result| serde_json::to_string(&result).unwrap())
                 .map_err(|e| format!("Failed to execute command '{}': {}", path_buf.display(), e));
 
@@ -1186,6 +1656,228 @@ fn async_op_exec_file<'s>(
     );
 }
 
+fn sync_op_glob_open<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: GlobArg| -> Result<String, MycoError> {
+            let state = get_state(scope)?;
+            let scope_root = resolve_path(state, &input.token, Some("/".to_string()), "read")?;
+            let path_buf = resolve_path(state, &input.token, Some(input.path.clone()), "read")?;
+            let options = GlobOptions::from_arg(&input)?;
+
+            let walk = GlobWalk::start(&state.runtime_handle, path_buf, scope_root, options);
+            Ok(state.capabilities.register(Capability::GlobWalk(walk)))
+        },
+    );
+}
+
+fn async_op_glob_next<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: TokenArg| {
+            let state = get_state(scope)?;
+            match state.capabilities.get(&input.token) {
+                Some(Capability::GlobWalk(walk)) => Ok(walk.batches.clone()),
+                _ => Err(MycoError::Internal {
+                    message: "Invalid token for glob".to_string(),
+                }),
+            }
+        },
+        |batches| async move {
+            // An empty batch signals the end of the walk
+            let result = match batches.lock().await.recv().await {
+                Some(Ok(files)) => Ok(serde_json::to_string(&files).unwrap()),
+                Some(Err(e)) => Err(e),
+                None => Ok("[]".to_string()),
+            };
+
+            OpResult::Json(result)
+        },
+    );
+}
+
+fn sync_op_glob_close<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TokenArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            if let Some(Capability::GlobWalk(_)) = state.capabilities.get(&input.token) {
+                state.capabilities.unregister(input.token);
+            }
+            Ok(())
+        },
+    );
+}
+
+fn async_op_hash_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: HashFileArg| {
+            let hasher = Hasher::new(&input.algorithm)?;
+            let state = get_state(scope)?;
+            let path_buf = resolve_path(state, &input.token, input.path.clone(), "read")?;
+            Ok((hasher, path_buf))
+        },
+        |(hasher, path_buf)| async move {
+            let target = path_buf.clone();
+            let result = tokio::task::spawn_blocking(move || hash_file(hasher, &target))
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+                .map_err(|e| format!("Failed to hash file '{}': {}", path_buf.display(), e));
+
+            OpResult::Binary(result)
+        },
+    );
+}
+
+fn sync_op_hash_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: HashFileArg| -> Result<ToJsBuffer, MycoError> {
+            let hasher = Hasher::new(&input.algorithm)?;
+            let state = get_state(scope)?;
+            let path_buf = resolve_path(state, &input.token, input.path.clone(), "read")?;
+            hash_file(hasher, &path_buf)
+                .map(ToJsBuffer::from)
+                .map_err(|e| MycoError::Internal {
+                    message: format!("Failed to hash file '{}': {}", path_buf.display(), e),
+                })
+        },
+    );
+}
+
+fn hash_file(mut hasher: Hasher, path: &Path) -> std::io::Result<Vec<u8>> {
+    hasher.update_reader(std::fs::File::open(path)?)?;
+    Ok(hasher.finalize())
+}
+
+fn async_op_lock_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: TokenPathArg| {
+            let state = get_state(scope)?;
+            resolve_lock_path(state, &input.token, &input.path)
+        },
+        |path_buf| async move {
+            let target = path_buf.clone();
+            let result = tokio::task::spawn_blocking(move || lock_file(&target, true))
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+                .map_err(|e| format!("Failed to lock file '{}': {}", path_buf.display(), e))
+                .and_then(|file| {
+                    file.map(Capability::FileLock)
+                        .ok_or_else(|| format!("Failed to lock file '{}'", path_buf.display()))
+                });
+
+            OpResult::Capability(result)
+        },
+    );
+}
+
+fn sync_op_lock_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TokenPathArg| -> Result<Option<String>, MycoError> {
+            lock_file_sync(scope, input, true)
+        },
+    );
+}
+
+fn sync_op_try_lock_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TokenPathArg| -> Result<Option<String>, MycoError> {
+            lock_file_sync(scope, input, false)
+        },
+    );
+}
+
+fn lock_file_sync(
+    scope: &mut v8::PinScope<'_, '_>,
+    input: TokenPathArg,
+    wait: bool,
+) -> Result<Option<String>, MycoError> {
+    let state = get_state(scope)?;
+    let path_buf = resolve_lock_path(state, &input.token, &input.path)?;
+    let file = lock_file(&path_buf, wait).map_err(|e| MycoError::Internal {
+        message: format!("Failed to lock file '{}': {}", path_buf.display(), e),
+    })?;
+    Ok(file.map(|file| state.capabilities.register(Capability::FileLock(file))))
+}
+
+fn sync_op_unlock_file<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TokenArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            match state.capabilities.get(&input.token) {
+                // Dropping the file closes it, which releases the lock
+                Some(Capability::FileLock(_)) => {
+                    state.capabilities.unregister(input.token);
+                    Ok(())
+                }
+                _ => Err(MycoError::Internal {
+                    message: "Invalid token for file lock".to_string(),
+                }),
+            }
+        },
+    );
+}
+
 fn sync_op_cwd<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     args: v8::FunctionCallbackArguments<'s>,
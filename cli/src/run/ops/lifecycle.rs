@@ -0,0 +1,271 @@
+use v8;
+
+use crate::errors::MycoError;
+use crate::run::ops::macros::{get_state, sync_op};
+use crate::run::state::SignalListener;
+use crate::{impl_from_v8_struct, impl_to_v8_struct, register_sync_op};
+
+struct SignalListenArg {
+    signal: String,
+    handler: v8::Global<v8::Function>,
+}
+
+impl_from_v8_struct!(SignalListenArg {
+    signal: String,
+    handler: v8::Global<v8::Function>,
+});
+
+struct SignalUnlistenArg {
+    id: f64,
+}
+
+impl_from_v8_struct!(SignalUnlistenArg { id: f64 });
+
+struct SignalDefaultArg {
+    handler: v8::Global<v8::Function>,
+}
+
+impl_from_v8_struct!(SignalDefaultArg {
+    handler: v8::Global<v8::Function>,
+});
+
+struct RejectionListenArg {
+    handler: v8::Global<v8::Function>,
+}
+
+impl_from_v8_struct!(RejectionListenArg {
+    handler: v8::Global<v8::Function>,
+});
+
+struct RejectionUnlistenArg {
+    id: f64,
+}
+
+impl_from_v8_struct!(RejectionUnlistenArg { id: f64 });
+
+struct ExitArg {
+    code: f64,
+}
+
+impl_from_v8_struct!(ExitArg { code: f64 });
+
+struct ProcessInfo {
+    pid: u32,
+    platform: String,
+    arch: String,
+}
+
+impl_to_v8_struct!(ProcessInfo {
+    pid,
+    platform,
+    arch
+});
+
+pub fn register_lifecycle_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_sync_op!(scope, myco_ops, "signal_listen", sync_op_signal_listen);
+    register_sync_op!(scope, myco_ops, "signal_unlisten", sync_op_signal_unlisten);
+    register_sync_op!(scope, myco_ops, "signal_default", sync_op_signal_default);
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "rejection_listen",
+        sync_op_rejection_listen
+    );
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "rejection_unlisten",
+        sync_op_rejection_unlisten
+    );
+    register_sync_op!(scope, myco_ops, "exit", sync_op_exit);
+    register_sync_op!(scope, myco_ops, "process_info", sync_op_process_info);
+
+    Ok(())
+}
+
+/// Starts forwarding deliveries of `signal` to the event loop. Once a signal is
+/// forwarded its default action no longer applies, so the event loop emulates it
+/// whenever no listener is left.
+#[cfg(unix)]
+fn forward_signal(
+    state: &mut crate::run::state::MycoState,
+    signal: i32,
+    name: &str,
+) -> Result<(), MycoError> {
+    use tokio::signal::unix::{signal as listen, SignalKind};
+
+    if state.forwarded_signals.contains(&signal) {
+        return Ok(());
+    }
+
+    let mut stream = {
+        let _guard = state.runtime_handle.enter();
+        listen(SignalKind::from_raw(signal)).map_err(|e| MycoError::Internal {
+            message: format!("Failed to listen for {}: {}", name, e),
+        })?
+    };
+    let sender = state.signal_sender.clone();
+    state.runtime_handle.spawn(async move {
+        while stream.recv().await.is_some() {
+            if sender.send(signal).is_err() {
+                break;
+            }
+        }
+    });
+    state.forwarded_signals.insert(signal);
+    Ok(())
+}
+
+fn sync_op_signal_listen<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: SignalListenArg| -> Result<u32, MycoError> {
+            #[cfg(unix)]
+            {
+                let signal = crate::run::ops::process::parse_signal(&input.signal)?;
+                let state = get_state(scope)?;
+                // Signals are process-wide, so only the main isolate handles them
+                if state.parent_port.is_some() {
+                    return Err(MycoError::Internal {
+                        message: "Only the main thread can listen for signals".to_string(),
+                    });
+                }
+                forward_signal(state, signal, &input.signal)?;
+
+                let id = state.next_signal_listener_id;
+                state.next_signal_listener_id += 1;
+                state.signal_listeners.insert(
+                    id,
+                    SignalListener {
+                        signal,
+                        name: input.signal,
+                        callback: input.handler,
+                    },
+                );
+                Ok(id)
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = (scope, input.handler);
+                Err(MycoError::Internal {
+                    message: format!("Listening for {} is only supported on Unix", input.signal),
+                })
+            }
+        },
+    );
+}
+
+fn sync_op_signal_unlisten<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: SignalUnlistenArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            state.signal_listeners.remove(&(input.id as u32));
+            Ok(())
+        },
+    );
+}
+
+/// Sets what a signal with no listener left does instead of exiting straight away.
+fn sync_op_signal_default<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: SignalDefaultArg| -> Result<(), MycoError> {
+            get_state(scope)?.signal_default = Some(input.handler);
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_rejection_listen<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: RejectionListenArg| -> Result<u32, MycoError> {
+            let state = get_state(scope)?;
+            let id = state.next_rejection_listener_id;
+            state.next_rejection_listener_id += 1;
+            state.rejection_listeners.insert(id, input.handler);
+            Ok(id)
+        },
+    );
+}
+
+fn sync_op_rejection_unlisten<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: RejectionUnlistenArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            state.rejection_listeners.remove(&(input.id as u32));
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_exit<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ExitArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            state.exit_requested.get_or_insert(input.code as i32);
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_process_info<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, _input: ()| -> Result<ProcessInfo, MycoError> {
+            Ok(ProcessInfo {
+                pid: std::process::id(),
+                platform: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+            })
+        },
+    );
+}
@@ -80,28 +80,15 @@ pub fn get_string_arg<'s>(
     Ok(arg.to_rust_string_lossy(scope))
 }
 
-// Helper function to create a proper JavaScript Error object with stack trace
+// Helper function to create a proper JavaScript Error object with stack trace.
+// This uses the context's intrinsic `Error`, so replacing `globalThis.Error` does
+// not change the errors ops throw.
 pub fn create_js_error<'a>(
     scope: &mut v8::PinScope<'a, '_>,
     message: &str,
 ) -> v8::Local<'a, v8::Value> {
     let message_str = v8::String::new(scope, message).unwrap();
-    let error_key = v8::String::new(scope, "Error").unwrap();
-    let error_constructor = scope
-        .get_current_context()
-        .global(scope)
-        .get(scope, error_key.into())
-        .unwrap();
-
-    if let Ok(error_constructor) = v8::Local::<v8::Function>::try_from(error_constructor) {
-        let args = [message_str.into()];
-        if let Some(error_obj) = error_constructor.new_instance(scope, &args) {
-            return error_obj.into();
-        }
-    }
-
-    // Fallback to plain string if Error constructor fails
-    message_str.into()
+    v8::Exception::error(scope, message_str)
 }
 
 // Helper function to throw a proper JavaScript Error
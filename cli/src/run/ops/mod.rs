@@ -1,11 +1,17 @@
+pub mod archive;
 pub mod console;
 pub mod convert;
+pub mod crypto;
 pub mod encoding;
 pub mod filesystem;
 pub mod http;
+pub mod lifecycle;
 pub mod macros;
+pub mod process;
 pub mod time;
 pub mod toml;
+pub mod url;
+pub mod workers;
 
 use crate::errors::MycoError;
 use log::{debug, info, trace};
@@ -45,10 +51,18 @@ pub fn register_ops<'s>(
     debug!("Registering encoding operations");
     encoding::register_encoding_ops(scope, &myco_ops)?;
 
+    // Register crypto operations
+    debug!("Registering crypto operations");
+    crypto::register_crypto_ops(scope, &myco_ops)?;
+
     // Register TOML operations
     debug!("Registering TOML operations");
     toml::register_toml_ops(scope, &myco_ops)?;
 
+    // Register URL operations
+    debug!("Registering URL operations");
+    url::register_url_ops(scope, &myco_ops)?;
+
     // Register time operations
     debug!("Registering time operations");
     time::register_time_ops(scope, &myco_ops)?;
@@ -57,10 +71,26 @@ pub fn register_ops<'s>(
     debug!("Registering filesystem operations");
     filesystem::register_filesystem_ops(scope, &myco_ops)?;
 
+    // Register process operations
+    debug!("Registering process operations");
+    process::register_process_ops(scope, &myco_ops)?;
+
+    // Register process lifecycle operations
+    debug!("Registering process lifecycle operations");
+    lifecycle::register_lifecycle_ops(scope, &myco_ops)?;
+
+    // Register archive operations
+    debug!("Registering archive operations");
+    archive::register_archive_ops(scope, &myco_ops)?;
+
     // Register HTTP operations
     debug!("Registering HTTP client operations");
     http::client::register_http_client_ops(scope, &myco_ops)?;
 
+    // Register worker operations
+    debug!("Registering worker operations");
+    workers::register_worker_ops(scope, &myco_ops)?;
+
     // Set argv property on Myco object
     debug!("Setting up command line arguments");
     let argv: Vec<String> = std::env::args().collect();
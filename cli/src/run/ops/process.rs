@@ -0,0 +1,766 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+use v8;
+
+use crate::errors::MycoError;
+use crate::run::ops::convert::JsBuffer;
+use crate::run::ops::filesystem::{resolve_dir, resolve_path, ExecResult};
+use crate::run::ops::macros::{async_op, get_state, sync_op};
+use crate::run::sandbox::{sandbox_command, Grant, GrantAccess, Sandbox};
+use crate::run::state::{MycoState, OpResult};
+use crate::Capability;
+use crate::{
+    impl_from_v8_struct, impl_to_v8_struct, register_async_op, register_sync_op, request_op,
+};
+
+/// A spawned child process, held in the capability registry under its own token.
+///
+/// Each pipe sits behind its own lock so a pending read of stdout does not block a
+/// write to stdin or a wait. The child is only ever locked briefly: it is reaped
+/// and signalled with the lock held, so a signal never reaches a recycled pid. The
+/// child is killed if the registry is dropped while it is still running.
+#[derive(Debug)]
+pub struct ChildProcess {
+    pid: u32,
+    child: Arc<std::sync::Mutex<Child>>,
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+    stdout: Arc<Mutex<Option<ChildStdout>>>,
+    stderr: Arc<Mutex<Option<ChildStderr>>>,
+}
+
+struct SpawnArg {
+    token: String,
+    path: Option<String>,
+    args: Vec<String>,
+    env: Option<BTreeMap<String, String>>,
+    inherit_env: Option<Vec<String>>,
+    cwd: Option<String>,
+    grant: Option<Vec<String>>,
+    stdin: Option<String>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+}
+
+impl_from_v8_struct!(SpawnArg {
+    token: String,
+    path: Option<String>,
+    args: Vec<String>,
+    env: Option<BTreeMap<String, String>>,
+    inherit_env: Option<Vec<String>>,
+    cwd: Option<String>,
+    grant: Option<Vec<String>>,
+    stdin: Option<String>,
+    stdout: Option<String>,
+    stderr: Option<String>,
+});
+
+struct ChildArg {
+    token: String,
+}
+
+impl_from_v8_struct!(ChildArg { token: String });
+
+struct ChildReadArg {
+    token: String,
+    stream: String,
+}
+
+impl_from_v8_struct!(ChildReadArg {
+    token: String,
+    stream: String,
+});
+
+struct ChildWriteArg {
+    token: String,
+    bytes: JsBuffer,
+}
+
+impl_from_v8_struct!(ChildWriteArg {
+    token: String,
+    bytes: JsBuffer,
+});
+
+struct ChildKillArg {
+    token: String,
+    signal: Option<String>,
+}
+
+impl_from_v8_struct!(ChildKillArg {
+    token: String,
+    signal: Option<String>,
+});
+
+struct SpawnResult {
+    token: String,
+    pid: u32,
+}
+
+impl_to_v8_struct!(SpawnResult { token, pid });
+
+#[derive(Serialize)]
+struct ChildStatus {
+    exit_code: Option<i32>,
+    signal: Option<i32>,
+}
+
+/// Builds the command for an exec token. Children start with an empty
+/// environment: only the parent variables named in `inherit_env` and the
+/// variables in `env` reach them. `inherit_env` and `cwd` must already be
+/// resolved from env and directory tokens.
+pub fn command(
+    path: &Path,
+    args: &[String],
+    env: Option<BTreeMap<String, String>>,
+    inherit_env: Vec<String>,
+    cwd: Option<PathBuf>,
+) -> std::process::Command {
+    let mut command = std::process::Command::new(path);
+    command.args(args).env_clear();
+    for name in inherit_env {
+        if let Some(value) = std::env::var_os(&name) {
+            command.env(name, value);
+        }
+    }
+    if let Some(env) = env {
+        command.envs(env);
+    }
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    command
+}
+
+/// The names of the environment variables behind `tokens`, which must all be
+/// env tokens: a child only inherits variables the caller may read itself.
+pub fn inherited_env(
+    state: &MycoState,
+    tokens: Option<Vec<String>>,
+) -> Result<Vec<String>, MycoError> {
+    tokens
+        .unwrap_or_default()
+        .iter()
+        .map(|token| match state.capabilities.get(token) {
+            Some(Capability::EnvVar(name)) => Ok(name.clone()),
+            _ => Err(MycoError::Internal {
+                message: "Invalid token in inheritEnv: expected an env token".to_string(),
+            }),
+        })
+        .collect()
+}
+
+/// Confines the child to the capabilities behind `tokens`, plus execute access to
+/// the program itself. Network access is only left open if a network token is
+/// among them.
+pub fn apply_grants(
+    command: &mut std::process::Command,
+    state: &MycoState,
+    program: &Path,
+    tokens: &[String],
+) -> Result<(), MycoError> {
+    let mut grants = vec![Grant {
+        path: program.to_path_buf(),
+        access: GrantAccess::Exec,
+    }];
+    let mut allow_network = false;
+    for token in tokens {
+        let (path, access) = match state.capabilities.get(token) {
+            Some(Capability::ReadFile(path)) | Some(Capability::ReadDir(path)) => {
+                (path, GrantAccess::Read)
+            }
+            Some(Capability::WriteFile(path)) | Some(Capability::WriteDir(path)) => {
+                (path, GrantAccess::Write)
+            }
+            Some(Capability::ExecFile(path)) | Some(Capability::ExecDir(path)) => {
+                (path, GrantAccess::Exec)
+            }
+            Some(Capability::FetchUrl(_))
+            | Some(Capability::FetchPrefix(_))
+            | Some(Capability::TcpListener(_))
+            | Some(Capability::TcpStream(_)) => {
+                allow_network = true;
+                continue;
+            }
+            _ => {
+                return Err(MycoError::Internal {
+                    message: "Invalid token in grant".to_string(),
+                })
+            }
+        };
+        grants.push(Grant {
+            path: PathBuf::from(path),
+            access,
+        });
+    }
+
+    let sandbox = Sandbox::new(&grants, allow_network)?;
+    sandbox_command(command, sandbox);
+    Ok(())
+}
+
+/// Limits applied to a run-to-completion exec.
+pub struct ExecLimits {
+    timeout: Option<Duration>,
+    max_output: Option<usize>,
+}
+
+impl ExecLimits {
+    pub fn new(timeout_ms: Option<f64>, max_output: Option<f64>) -> Result<Self, MycoError> {
+        let invalid = |name: &str| MycoError::Internal {
+            message: format!("Invalid {}: must be a non-negative number", name),
+        };
+        let timeout = match timeout_ms {
+            Some(ms) if ms.is_finite() && ms >= 0.0 => Some(Duration::from_secs_f64(ms / 1000.0)),
+            Some(_) => return Err(invalid("timeout")),
+            None => None,
+        };
+        let max_output = match max_output {
+            Some(bytes) if bytes.is_finite() && bytes >= 0.0 => Some(bytes as usize),
+            Some(_) => return Err(invalid("output limit")),
+            None => None,
+        };
+        Ok(Self {
+            timeout,
+            max_output,
+        })
+    }
+}
+
+/// Runs `command` to completion and collects its output. Without limits this is
+/// just `Command::output`; with a timeout or an output cap the child is killed
+/// as soon as either is exceeded and an error is returned instead of a result.
+pub fn run_command(
+    mut command: std::process::Command,
+    limits: ExecLimits,
+) -> Result<ExecResult, String> {
+    if limits.timeout.is_none() && limits.max_output.is_none() {
+        let output = command.output().map_err(|e| e.to_string())?;
+        return Ok(ExecResult {
+            stdout: output.stdout,
+            stderr: output.stderr,
+            exit_code: output.status.code().unwrap_or(-1),
+        });
+    }
+
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let pid = child.id();
+    let child = Arc::new(std::sync::Mutex::new(child));
+
+    // The readers and the waiter report here, so the deadline is the only thing
+    // waited on besides them.
+    let (events, received) = std::sync::mpsc::channel();
+    let (stdout, stderr) = {
+        let mut child = lock_child(&child);
+        (
+            read_capped(child.stdout.take(), limits.max_output, events.clone()),
+            read_capped(child.stderr.take(), limits.max_output, events.clone()),
+        )
+    };
+    let waiter = {
+        let child = child.clone();
+        std::thread::spawn(move || {
+            let status = wait_for_exit(pid, &child);
+            let _ = events.send(ExecEvent::Exited);
+            status
+        })
+    };
+
+    let deadline = limits.timeout.map(|timeout| Instant::now() + timeout);
+    let event = match deadline {
+        Some(deadline) => received
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .ok(),
+        None => received.recv().ok(),
+    };
+    let error = match event {
+        Some(ExecEvent::Exited) => None,
+        Some(ExecEvent::OutputExceeded) => Some(format!(
+            "output exceeded {} bytes",
+            limits.max_output.unwrap_or_default()
+        )),
+        None => Some(format!(
+            "timed out after {} ms",
+            limits.timeout.unwrap_or_default().as_millis()
+        )),
+    };
+
+    if let Some(error) = error {
+        // Only the waiter reaps, so the pid is still the child's. Once it is
+        // killed the waiter finishes; the readers finish when the last process
+        // holding the pipes, perhaps a grandchild, exits, so they are detached.
+        let _ = lock_child(&child).kill();
+        let _ = waiter.join();
+        drop((stdout, stderr));
+        return Err(error);
+    }
+
+    let status = waiter
+        .join()
+        .map_err(|_| "failed to wait for child process".to_string())?
+        .map_err(|e| e.to_string())?;
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    // The child may have exited between a read crossing the cap and its exit
+    if received
+        .try_iter()
+        .any(|event| matches!(event, ExecEvent::OutputExceeded))
+    {
+        return Err(format!(
+            "output exceeded {} bytes",
+            limits.max_output.unwrap_or_default()
+        ));
+    }
+
+    Ok(ExecResult {
+        stdout,
+        stderr,
+        exit_code: status.code().unwrap_or(-1),
+    })
+}
+
+enum ExecEvent {
+    Exited,
+    OutputExceeded,
+}
+
+fn read_capped(
+    pipe: Option<impl Read + Send + 'static>,
+    max_output: Option<usize>,
+    events: std::sync::mpsc::Sender<ExecEvent>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return output;
+        };
+        let mut buffer = [0; 8192];
+        loop {
+            match pipe.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    output.extend_from_slice(&buffer[..read]);
+                    if max_output.is_some_and(|max| output.len() > max) {
+                        let _ = events.send(ExecEvent::OutputExceeded);
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+        output
+    })
+}
+
+pub fn register_process_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_async_op!(scope, myco_ops, "child_read", async_op_child_read);
+    register_async_op!(scope, myco_ops, "child_write", async_op_child_write);
+    register_async_op!(scope, myco_ops, "child_wait", async_op_child_wait);
+
+    register_async_op!(scope, myco_ops, "request_env", async_op_request_env);
+
+    register_sync_op!(scope, myco_ops, "env_get", sync_op_env_get);
+    register_sync_op!(scope, myco_ops, "spawn_child", sync_op_spawn_child);
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "child_close_stdin",
+        sync_op_child_close_stdin
+    );
+    register_sync_op!(scope, myco_ops, "child_kill", sync_op_child_kill);
+    register_sync_op!(scope, myco_ops, "child_release", sync_op_child_release);
+
+    Ok(())
+}
+
+request_op!(async_op_request_env, EnvVar);
+
+fn sync_op_env_get<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ChildArg| -> Result<Option<String>, MycoError> {
+            let state = get_state(scope)?;
+            match state.capabilities.get(&input.token) {
+                Some(Capability::EnvVar(name)) => Ok(std::env::var(name).ok()),
+                _ => Err(MycoError::Internal {
+                    message: "Invalid token for environment variable".to_string(),
+                }),
+            }
+        },
+    );
+}
+
+fn get_child<'a>(state: &'a MycoState, token: &str) -> Result<&'a ChildProcess, MycoError> {
+    match state.capabilities.get(token) {
+        Some(Capability::Child(child)) => Ok(child),
+        _ => Err(MycoError::Internal {
+            message: "Invalid token for child process".to_string(),
+        }),
+    }
+}
+
+fn stdio(mode: Option<&str>) -> Result<Stdio, MycoError> {
+    match mode.unwrap_or("piped") {
+        "piped" => Ok(Stdio::piped()),
+        "inherit" => Ok(Stdio::inherit()),
+        "null" => Ok(Stdio::null()),
+        other => Err(MycoError::Internal {
+            message: format!("Invalid stdio mode '{}'", other),
+        }),
+    }
+}
+
+#[cfg(unix)]
+pub(crate) fn parse_signal(signal: &str) -> Result<i32, MycoError> {
+    match signal {
+        "SIGTERM" => Ok(libc::SIGTERM),
+        "SIGKILL" => Ok(libc::SIGKILL),
+        "SIGINT" => Ok(libc::SIGINT),
+        "SIGHUP" => Ok(libc::SIGHUP),
+        "SIGQUIT" => Ok(libc::SIGQUIT),
+        "SIGUSR1" => Ok(libc::SIGUSR1),
+        "SIGUSR2" => Ok(libc::SIGUSR2),
+        "SIGSTOP" => Ok(libc::SIGSTOP),
+        "SIGCONT" => Ok(libc::SIGCONT),
+        _ => Err(MycoError::Internal {
+            message: format!("Unsupported signal '{}'", signal),
+        }),
+    }
+}
+
+fn sync_op_spawn_child<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: SpawnArg| -> Result<SpawnResult, MycoError> {
+            let state = get_state(scope)?;
+            let path_buf = resolve_path(state, &input.token, input.path.clone(), "exec")?;
+            let cwd = match &input.cwd {
+                Some(token) => Some(resolve_dir(state, token)?),
+                None => None,
+            };
+            let inherit_env = inherited_env(state, input.inherit_env)?;
+            let mut command = command(&path_buf, &input.args, input.env, inherit_env, cwd);
+            if let Some(grant) = &input.grant {
+                apply_grants(&mut command, state, &path_buf, grant)?;
+            }
+
+            // tokio's process driver must be reachable from the calling thread
+            let _guard = state.runtime_handle.enter();
+            let mut child = tokio::process::Command::from(command)
+                .stdin(stdio(input.stdin.as_deref())?)
+                .stdout(stdio(input.stdout.as_deref())?)
+                .stderr(stdio(input.stderr.as_deref())?)
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| MycoError::Internal {
+                    message: format!("Failed to spawn command '{}': {}", path_buf.display(), e),
+                })?;
+
+            let pid = child.id().unwrap_or(0);
+            let process = ChildProcess {
+                pid,
+                stdin: Arc::new(Mutex::new(child.stdin.take())),
+                stdout: Arc::new(Mutex::new(child.stdout.take())),
+                stderr: Arc::new(Mutex::new(child.stderr.take())),
+                child: Arc::new(std::sync::Mutex::new(child)),
+            };
+            let token = state.capabilities.register(Capability::Child(process));
+
+            Ok(SpawnResult { token, pid })
+        },
+    );
+}
+
+enum OutputPipe {
+    Stdout(Arc<Mutex<Option<ChildStdout>>>),
+    Stderr(Arc<Mutex<Option<ChildStderr>>>),
+}
+
+fn async_op_child_read<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: ChildReadArg| {
+            let state = get_state(scope)?;
+            let child = get_child(state, &input.token)?;
+            match input.stream.as_str() {
+                "stdout" => Ok(OutputPipe::Stdout(child.stdout.clone())),
+                "stderr" => Ok(OutputPipe::Stderr(child.stderr.clone())),
+                other => Err(MycoError::Internal {
+                    message: format!("Invalid output stream '{}'", other),
+                }),
+            }
+        },
+        |pipe| async move {
+            // An empty chunk signals end of stream
+            let mut buffer = vec![0; 64 * 1024];
+            let read = match pipe {
+                OutputPipe::Stdout(pipe) => match pipe.lock().await.as_mut() {
+                    Some(stdout) => stdout.read(&mut buffer).await,
+                    None => Ok(0),
+                },
+                OutputPipe::Stderr(pipe) => match pipe.lock().await.as_mut() {
+                    Some(stderr) => stderr.read(&mut buffer).await,
+                    None => Ok(0),
+                },
+            };
+            let result = read
+                .map(|read| {
+                    buffer.truncate(read);
+                    buffer
+                })
+                .map_err(|e| format!("Failed to read from child process: {}", e));
+
+            OpResult::Binary(result)
+        },
+    );
+}
+
+fn async_op_child_write<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: ChildWriteArg| {
+            let state = get_state(scope)?;
+            let child = get_child(state, &input.token)?;
+            Ok((child.stdin.clone(), input.bytes))
+        },
+        |(stdin, bytes)| async move {
+            let result = match stdin.lock().await.as_mut() {
+                Some(stdin) => match stdin.write_all(&bytes).await {
+                    Ok(()) => stdin.flush().await,
+                    Err(e) => Err(e),
+                },
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "stdin is closed",
+                )),
+            }
+            .map_err(|e| format!("Failed to write to child process: {}", e));
+
+            OpResult::Void(result)
+        },
+    );
+}
+
+/// A child process that can be reaped without blocking: std's or tokio's.
+trait Reap {
+    fn try_reap(&mut self) -> std::io::Result<Option<std::process::ExitStatus>>;
+}
+
+impl Reap for std::process::Child {
+    fn try_reap(&mut self) -> std::io::Result<Option<std::process::ExitStatus>> {
+        self.try_wait()
+    }
+}
+
+impl Reap for Child {
+    fn try_reap(&mut self) -> std::io::Result<Option<std::process::ExitStatus>> {
+        self.try_wait()
+    }
+}
+
+/// Blocks until the child exits, then reaps it. The exit is first observed
+/// without reaping, so the pid stays reserved until the child's lock is held;
+/// whoever signals the child holds the same lock. Only one wait may run at a time.
+#[cfg(unix)]
+fn wait_for_exit<C: Reap>(
+    pid: u32,
+    child: &std::sync::Mutex<C>,
+) -> std::io::Result<std::process::ExitStatus> {
+    loop {
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        let flags = libc::WEXITED | libc::WNOWAIT;
+        if unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, flags) } == 0 {
+            break;
+        }
+        let error = std::io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            // Reaped already by an earlier wait, which kept the status
+            Some(libc::ECHILD) => break,
+            _ => return Err(error),
+        }
+    }
+    lock_child(child)
+        .try_reap()?
+        .ok_or_else(|| std::io::Error::other("child process has not exited"))
+}
+
+#[cfg(not(unix))]
+fn wait_for_exit<C: Reap>(
+    _pid: u32,
+    child: &std::sync::Mutex<C>,
+) -> std::io::Result<std::process::ExitStatus> {
+    loop {
+        if let Some(status) = lock_child(child).try_reap()? {
+            return Ok(status);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn lock_child<C>(child: &std::sync::Mutex<C>) -> std::sync::MutexGuard<'_, C> {
+    child.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn async_op_child_wait<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: ChildArg| {
+            let state = get_state(scope)?;
+            let child = get_child(state, &input.token)?;
+            Ok((child.pid, child.child.clone()))
+        },
+        |(pid, child)| async move {
+            let result = tokio::task::spawn_blocking(move || wait_for_exit(pid, &child))
+                .await
+                .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+                .map(|status| {
+                    #[cfg(unix)]
+                    let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+                    #[cfg(not(unix))]
+                    let signal = None;
+                    let status = ChildStatus {
+                        exit_code: status.code(),
+                        signal,
+                    };
+                    serde_json::to_string(&status).unwrap()
+                })
+                .map_err(|e| format!("Failed to wait for child process: {}", e));
+
+            OpResult::Json(result)
+        },
+    );
+}
+
+/// Removes an exited child from the registry, once the runtime has its status
+/// and everything it wrote.
+fn sync_op_child_release<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ChildArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            get_child(state, &input.token)?;
+            state.capabilities.unregister(input.token);
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_child_close_stdin<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ChildArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            let child = get_child(state, &input.token)?;
+            // A pending write holds the lock; it finishes before the pipe is dropped
+            let stdin = child.stdin.clone();
+            state.runtime_handle.spawn(async move {
+                stdin.lock().await.take();
+            });
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_child_kill<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ChildKillArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            let child = get_child(state, &input.token)?;
+
+            // Children are only reaped with this lock held, and tokio forgets the
+            // pid once it has reaped one, so while we hold the lock a known pid
+            // still belongs to our child, if only as a zombie.
+            #[allow(unused_mut)]
+            let mut guard = lock_child(&child.child);
+            if guard.id().is_none() {
+                return Ok(());
+            }
+
+            #[cfg(unix)]
+            {
+                let signal = parse_signal(input.signal.as_deref().unwrap_or("SIGTERM"))?;
+                if unsafe { libc::kill(child.pid as libc::pid_t, signal) } != 0 {
+                    return Err(MycoError::Internal {
+                        message: format!(
+                            "Failed to signal child process: {}",
+                            std::io::Error::last_os_error()
+                        ),
+                    });
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = input.signal;
+                guard.start_kill()?;
+            }
+
+            Ok(())
+        },
+    );
+}
@@ -6,11 +6,15 @@ use crate::run::state::Timer;
 use std::time::{Duration, Instant};
 use v8;
 
-struct DelayArg {
+struct TimerArg {
+    callback: v8::Global<v8::Function>,
     delay: f64,
 }
 
-impl_from_v8_struct!(DelayArg { delay: f64 });
+impl_from_v8_struct!(TimerArg {
+    callback: v8::Global<v8::Function>,
+    delay: f64,
+});
 
 struct TimerIdArg {
     timer_id: f64,
@@ -18,16 +22,59 @@ struct TimerIdArg {
 
 impl_from_v8_struct!(TimerIdArg { timer_id: f64 });
 
+struct TimerRefArg {
+    timer_id: f64,
+    refed: bool,
+}
+
+impl_from_v8_struct!(TimerRefArg {
+    timer_id: f64,
+    refed: bool,
+});
+
+// Also used by `set_immediate`
+struct MicrotaskArg {
+    callback: v8::Global<v8::Function>,
+}
+
+impl_from_v8_struct!(MicrotaskArg {
+    callback: v8::Global<v8::Function>,
+});
+
 pub fn register_time_ops(
     scope: &mut v8::PinScope<'_, '_>,
     myco_ops: &v8::Object,
 ) -> Result<(), MycoError> {
     register_sync_op!(scope, myco_ops, "set_timeout", sync_op_set_timeout);
+    register_sync_op!(scope, myco_ops, "set_interval", sync_op_set_interval);
+    register_sync_op!(scope, myco_ops, "set_immediate", sync_op_set_immediate);
     register_sync_op!(scope, myco_ops, "clear_timeout", sync_op_clear_timeout);
+    register_sync_op!(scope, myco_ops, "timer_ref", sync_op_timer_ref);
+    register_sync_op!(scope, myco_ops, "queue_microtask", sync_op_queue_microtask);
+    register_sync_op!(scope, myco_ops, "performance_now", sync_op_performance_now);
 
     Ok(())
 }
 
+/// The longest delay a timer accepts, as in browsers and Node.
+const TIMEOUT_MAX: f64 = 2_147_483_647.0;
+
+fn delay_from_ms(delay: f64) -> Duration {
+    // NaN and negative delays count as zero, and ones too long to schedule (up
+    // to Infinity) fire after a millisecond, as they do in Node
+    if delay > TIMEOUT_MAX {
+        return Duration::from_millis(1);
+    }
+    Duration::from_millis(delay.max(0.0) as u64)
+}
+
+fn next_timer_id(scope: &mut v8::PinScope<'_, '_>) -> Result<u32, MycoError> {
+    let state = get_state(scope)?;
+    let timer_id = state.next_timer_id;
+    state.next_timer_id += 1;
+    Ok(timer_id)
+}
+
 fn sync_op_set_timeout<'s>(
     scope: &mut v8::PinScope<'s, '_>,
     args: v8::FunctionCallbackArguments<'s>,
@@ -37,43 +84,56 @@ fn sync_op_set_timeout<'s>(
         scope,
         &args,
         rv,
-        |scope, input: DelayArg| -> Result<u32, MycoError> {
-            let delay_ms = input.delay.max(0.0) as u64;
-            let delay = Duration::from_millis(delay_ms);
-            let execute_at = Instant::now() + delay;
-
-            // Get timer_id first while we can borrow state mutably
-            let timer_id = {
-                let state = get_state(scope)?;
-                let timer_id = state.next_timer_id;
-                state.next_timer_id += 1;
-                timer_id
-            };
-
-            let callback_code = format!(
-                "(function() {{ globalThis.__mycoTimerComplete({}) }})",
-                timer_id
-            );
-            let callback_source = v8::String::new(scope, &callback_code).unwrap();
-            let callback_script = v8::Script::compile(scope, callback_source, None);
-
-            if let Some(script) = callback_script {
-                if let Some(callback_fn_val) = script.run(scope) {
-                    if let Ok(callback_fn) = v8::Local::<v8::Function>::try_from(callback_fn_val) {
-                        let global_callback = v8::Global::new(scope, callback_fn);
-
-                        let timer = Timer::new(timer_id, global_callback, execute_at);
-                        let state = get_state(scope)?;
-                        state.timers.push(timer);
-
-                        return Ok(timer_id);
-                    }
-                }
-            }
-
-            Err(MycoError::Internal {
-                message: "Failed to create timer callback".to_string(),
-            })
+        |scope, input: TimerArg| -> Result<u32, MycoError> {
+            let execute_at = Instant::now() + delay_from_ms(input.delay);
+            let timer_id = next_timer_id(scope)?;
+
+            let state = get_state(scope)?;
+            state
+                .timers
+                .insert(Timer::new(timer_id, input.callback, execute_at));
+            Ok(timer_id)
+        },
+    );
+}
+
+fn sync_op_set_interval<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TimerArg| -> Result<u32, MycoError> {
+            // An interval always waits at least a millisecond, so one that keeps
+            // firing cannot starve the rest of the event loop
+            let interval = delay_from_ms(input.delay).max(Duration::from_millis(1));
+            let timer_id = next_timer_id(scope)?;
+
+            let state = get_state(scope)?;
+            state
+                .timers
+                .insert(Timer::repeating(timer_id, input.callback, interval));
+            Ok(timer_id)
+        },
+    );
+}
+
+fn sync_op_set_immediate<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: MicrotaskArg| -> Result<u32, MycoError> {
+            let timer_id = next_timer_id(scope)?;
+            get_state(scope)?.immediates.push(timer_id, input.callback);
+            Ok(timer_id)
         },
     );
 }
@@ -88,10 +148,61 @@ fn sync_op_clear_timeout<'s>(
         &args,
         rv,
         |scope, input: TimerIdArg| -> Result<(), MycoError> {
-            let timer_id = input.timer_id as u32;
             let state = get_state(scope)?;
-            state.timers.retain(|timer| timer.id != timer_id);
+            state.timers.remove(input.timer_id as u32);
+            state.immediates.remove(input.timer_id as u32);
             Ok(())
         },
     );
 }
+
+fn sync_op_timer_ref<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: TimerRefArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            state.timers.set_ref(input.timer_id as u32, input.refed);
+            state.immediates.set_ref(input.timer_id as u32, input.refed);
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_queue_microtask<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: MicrotaskArg| -> Result<(), MycoError> {
+            let callback = v8::Local::new(scope, &input.callback);
+            scope.enqueue_microtask(callback);
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_performance_now<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, _input: ()| -> Result<f64, MycoError> {
+            let state = get_state(scope)?;
+            Ok(state.time_origin.elapsed().as_secs_f64() * 1000.0)
+        },
+    );
+}
@@ -0,0 +1,211 @@
+use crate::errors::MycoError;
+use crate::run::ops::macros::sync_op;
+use crate::{impl_from_v8_struct, impl_to_v8_struct, register_sync_op};
+use url::{form_urlencoded, quirks, Url};
+use v8;
+
+struct UrlParseArg {
+    url: String,
+    base: Option<String>,
+}
+
+impl_from_v8_struct!(UrlParseArg {
+    url: String,
+    base: Option<String>,
+});
+
+struct UrlSetArg {
+    href: String,
+    part: String,
+    value: String,
+}
+
+impl_from_v8_struct!(UrlSetArg {
+    href: String,
+    part: String,
+    value: String,
+});
+
+struct QueryArg {
+    query: String,
+}
+
+impl_from_v8_struct!(QueryArg { query: String });
+
+struct PairsArg {
+    pairs: Vec<Vec<String>>,
+}
+
+impl_from_v8_struct!(PairsArg {
+    pairs: Vec<Vec<String>>,
+});
+
+/// The components of a parsed URL, as the WHATWG `URL` getters report them.
+struct UrlParts {
+    href: String,
+    origin: String,
+    protocol: String,
+    username: String,
+    password: String,
+    host: String,
+    hostname: String,
+    port: String,
+    pathname: String,
+    search: String,
+    hash: String,
+}
+
+impl_to_v8_struct!(UrlParts {
+    href,
+    origin,
+    protocol,
+    username,
+    password,
+    host,
+    hostname,
+    port,
+    pathname,
+    search,
+    hash,
+});
+
+impl From<&Url> for UrlParts {
+    fn from(url: &Url) -> Self {
+        UrlParts {
+            href: quirks::href(url).to_string(),
+            origin: quirks::origin(url),
+            protocol: quirks::protocol(url).to_string(),
+            username: quirks::username(url).to_string(),
+            password: quirks::password(url).to_string(),
+            host: quirks::host(url).to_string(),
+            hostname: quirks::hostname(url).to_string(),
+            port: quirks::port(url).to_string(),
+            pathname: quirks::pathname(url).to_string(),
+            search: quirks::search(url).to_string(),
+            hash: quirks::hash(url).to_string(),
+        }
+    }
+}
+
+pub fn register_url_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_sync_op!(scope, myco_ops, "url_parse", sync_op_url_parse);
+    register_sync_op!(scope, myco_ops, "url_set", sync_op_url_set);
+    register_sync_op!(scope, myco_ops, "url_query_parse", sync_op_url_query_parse);
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "url_query_stringify",
+        sync_op_url_query_stringify
+    );
+
+    Ok(())
+}
+
+fn sync_op_url_parse<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: UrlParseArg| -> Result<Option<UrlParts>, MycoError> {
+            // An unparseable URL is `null`, so the runtime can throw the TypeError
+            // the URL constructor is specified to throw
+            let parsed = match input.base {
+                Some(base) => Url::parse(&base).and_then(|base| base.join(&input.url)),
+                None => Url::parse(&input.url),
+            };
+            Ok(parsed.ok().as_ref().map(UrlParts::from))
+        },
+    );
+}
+
+fn sync_op_url_set<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: UrlSetArg| -> Result<UrlParts, MycoError> {
+            let mut url = Url::parse(&input.href).map_err(|e| MycoError::Internal {
+                message: format!("Invalid URL '{}': {}", input.href, e),
+            })?;
+            let value = input.value.as_str();
+
+            // Setters ignore values they cannot apply, leaving the URL unchanged
+            let _ = match input.part.as_str() {
+                "protocol" => quirks::set_protocol(&mut url, value),
+                "username" => quirks::set_username(&mut url, value),
+                "password" => quirks::set_password(&mut url, value),
+                "host" => quirks::set_host(&mut url, value),
+                "hostname" => quirks::set_hostname(&mut url, value),
+                "port" => quirks::set_port(&mut url, value),
+                "pathname" => {
+                    quirks::set_pathname(&mut url, value);
+                    Ok(())
+                }
+                "search" => {
+                    quirks::set_search(&mut url, value);
+                    Ok(())
+                }
+                "hash" => {
+                    quirks::set_hash(&mut url, value);
+                    Ok(())
+                }
+                part => {
+                    return Err(MycoError::Internal {
+                        message: format!("Unknown URL component '{}'", part),
+                    })
+                }
+            };
+            Ok(UrlParts::from(&url))
+        },
+    );
+}
+
+fn sync_op_url_query_parse<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: QueryArg| -> Result<Vec<Vec<String>>, MycoError> {
+            let query = input.query.strip_prefix('?').unwrap_or(&input.query);
+            Ok(form_urlencoded::parse(query.as_bytes())
+                .map(|(name, value)| vec![name.into_owned(), value.into_owned()])
+                .collect())
+        },
+    );
+}
+
+fn sync_op_url_query_stringify<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |_scope, input: PairsArg| -> Result<String, MycoError> {
+            let mut serializer = form_urlencoded::Serializer::new(String::new());
+            for pair in &input.pairs {
+                if let [name, value] = pair.as_slice() {
+                    serializer.append_pair(name, value);
+                }
+            }
+            Ok(serializer.finish())
+        },
+    );
+}
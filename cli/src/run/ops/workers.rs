@@ -0,0 +1,342 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
+
+use tokio::sync::mpsc;
+use url::Url;
+use v8;
+
+use crate::errors::MycoError;
+use crate::run::ops::convert::StructuredClone;
+use crate::run::ops::macros::{async_op, get_state, sync_op};
+use crate::run::stack_trace::caller_script_name;
+use crate::run::state::{OpResult, PortMessage, RunOptions, WorkerHandle};
+use crate::run::worker::{self, WorkerInit};
+use crate::{impl_from_v8_struct, register_async_op, register_sync_op};
+
+struct SpawnWorkerArg {
+    path: String,
+    capabilities: Vec<Vec<String>>,
+}
+
+impl_from_v8_struct!(SpawnWorkerArg {
+    path: String,
+    capabilities: Vec<Vec<String>>,
+});
+
+struct WorkerIdArg {
+    id: f64,
+}
+
+impl_from_v8_struct!(WorkerIdArg { id: f64 });
+
+struct WorkerPostArg {
+    id: f64,
+    message: Option<StructuredClone>,
+}
+
+impl_from_v8_struct!(WorkerPostArg {
+    id: f64,
+    message: Option<StructuredClone>,
+});
+
+struct WorkerListenArg {
+    id: f64,
+    handler: Option<v8::Global<v8::Function>>,
+}
+
+impl_from_v8_struct!(WorkerListenArg {
+    id: f64,
+    handler: Option<v8::Global<v8::Function>>,
+});
+
+struct ParentPostArg {
+    message: Option<StructuredClone>,
+}
+
+impl_from_v8_struct!(ParentPostArg {
+    message: Option<StructuredClone>,
+});
+
+struct ParentListenArg {
+    handler: Option<v8::Global<v8::Function>>,
+}
+
+impl_from_v8_struct!(ParentListenArg {
+    handler: Option<v8::Global<v8::Function>>,
+});
+
+pub fn register_worker_ops(
+    scope: &mut v8::PinScope<'_, '_>,
+    myco_ops: &v8::Object,
+) -> Result<(), MycoError> {
+    register_sync_op!(scope, myco_ops, "worker_spawn", sync_op_worker_spawn);
+    register_sync_op!(scope, myco_ops, "worker_post", sync_op_worker_post);
+    register_sync_op!(scope, myco_ops, "worker_listen", sync_op_worker_listen);
+    register_sync_op!(
+        scope,
+        myco_ops,
+        "worker_terminate",
+        sync_op_worker_terminate
+    );
+    register_async_op!(scope, myco_ops, "worker_wait", async_op_worker_wait);
+    register_sync_op!(scope, myco_ops, "parent_post", sync_op_parent_post);
+    register_sync_op!(scope, myco_ops, "parent_listen", sync_op_parent_listen);
+
+    Ok(())
+}
+
+fn unknown_worker(id: u32) -> MycoError {
+    MycoError::Internal {
+        message: format!("Unknown worker {}", id),
+    }
+}
+
+/// Resolves a worker's module path the way a relative import would be: against the
+/// module that spawns it. Without one, it is relative to the working directory.
+fn resolve_worker_path(scope: &mut v8::PinScope<'_, '_>, path: &str) -> Result<PathBuf, MycoError> {
+    let referrer_dir = caller_script_name(scope)
+        .and_then(|referrer| Url::parse(&referrer).ok())
+        .and_then(|referrer| referrer.to_file_path().ok())
+        .and_then(|referrer| referrer.parent().map(Path::to_path_buf));
+    let resolved = match referrer_dir {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    };
+    std::fs::canonicalize(&resolved).map_err(|e| MycoError::Internal {
+        message: format!("Cannot start worker '{}': {}", path, e),
+    })
+}
+
+fn sync_op_worker_spawn<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: SpawnWorkerArg| -> Result<u32, MycoError> {
+            let path = resolve_worker_path(scope, &input.path)?;
+            if !path.is_file() {
+                return Err(MycoError::NotAFile {
+                    path: path.display().to_string(),
+                });
+            }
+
+            let state = get_state(scope)?;
+
+            // Check every token before moving any, so a bad one transfers nothing
+            for token in input.capabilities.iter().flatten() {
+                match state.capabilities.get(token) {
+                    Some(capability) if capability.transfer_kind().is_some() => {}
+                    Some(capability) => {
+                        return Err(MycoError::Internal {
+                            message: format!("Cannot transfer {:?} to a worker", capability),
+                        })
+                    }
+                    None => {
+                        return Err(MycoError::Internal {
+                            message: "Invalid token".to_string(),
+                        })
+                    }
+                }
+            }
+            let capabilities = input
+                .capabilities
+                .into_iter()
+                .map(|group| {
+                    group
+                        .into_iter()
+                        .filter_map(|token| {
+                            let capability = state.capabilities.unregister(token.clone())?;
+                            Some((token, capability))
+                        })
+                        .collect()
+                })
+                .collect();
+
+            let id = state.next_worker_id;
+            state.next_worker_id += 1;
+
+            let (sender, receiver) = mpsc::unbounded_channel();
+            let isolate = Arc::new(OnceLock::new());
+            let terminated = Arc::new(AtomicBool::new(false));
+            let init = WorkerInit {
+                id,
+                capabilities,
+                parent: state.port_sender.clone(),
+                sender: sender.clone(),
+                receiver,
+                isolate: isolate.clone(),
+                // The parent's deadline already covers the worker, and a worker cannot
+                // be debugged
+                options: RunOptions {
+                    debug: None,
+                    log_format: state.log_format,
+                    unhandled_rejections: state.unhandled_rejections,
+                    max_heap_mb: state.max_heap_mb,
+                    timeout: None,
+                },
+                import_map: state.import_map.clone(),
+                myco_local: state.myco_local.clone(),
+                packages: state.packages.clone(),
+            };
+            let exit = worker::spawn(path, init, terminated.clone())?;
+
+            state.workers.insert(
+                id,
+                WorkerHandle {
+                    sender,
+                    isolate,
+                    terminated,
+                    exit: Some(exit),
+                    on_message: None,
+                    queued: Vec::new(),
+                },
+            );
+            Ok(id)
+        },
+    );
+}
+
+fn sync_op_worker_post<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: WorkerPostArg| -> Result<(), MycoError> {
+            let id = input.id as u32;
+            let state = get_state(scope)?;
+            let worker = state.workers.get(&id).ok_or_else(|| unknown_worker(id))?;
+            // A worker that has already stopped just never sees the message
+            let _ = worker
+                .sender
+                .send(PortMessage::FromParent(input.message.map(|m| m.0)));
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_worker_listen<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: WorkerListenArg| -> Result<(), MycoError> {
+            let id = input.id as u32;
+            let state = get_state(scope)?;
+            let worker = state
+                .workers
+                .get_mut(&id)
+                .ok_or_else(|| unknown_worker(id))?;
+            // Messages that arrived before there was a handler are delivered by the
+            // event loop
+            worker.on_message = input.handler;
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_worker_terminate<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: WorkerIdArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            if let Some(worker) = state.workers.get(&(input.id as u32)) {
+                worker.terminate();
+            }
+            Ok(())
+        },
+    );
+}
+
+fn async_op_worker_wait<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    async_op(
+        scope,
+        rv,
+        &args,
+        |scope, input: WorkerIdArg| {
+            let id = input.id as u32;
+            let state = get_state(scope)?;
+            state
+                .workers
+                .get_mut(&id)
+                .and_then(|worker| worker.exit.take())
+                .ok_or_else(|| unknown_worker(id))
+        },
+        |exit| async move {
+            // The sender only goes away without a code if the worker thread panicked
+            let exit_code = exit.await.unwrap_or(1);
+            OpResult::Json(Ok(exit_code.to_string()))
+        },
+    );
+}
+
+fn sync_op_parent_post<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ParentPostArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            let parent = state
+                .parent_port
+                .as_ref()
+                .ok_or_else(|| MycoError::Internal {
+                    message: "Not running in a worker".to_string(),
+                })?;
+            let _ = parent.sender.send(PortMessage::FromWorker {
+                id: parent.id,
+                data: input.message.map(|m| m.0),
+            });
+            Ok(())
+        },
+    );
+}
+
+fn sync_op_parent_listen<'s>(
+    scope: &mut v8::PinScope<'s, '_>,
+    args: v8::FunctionCallbackArguments<'s>,
+    rv: v8::ReturnValue,
+) {
+    sync_op(
+        scope,
+        &args,
+        rv,
+        |scope, input: ParentListenArg| -> Result<(), MycoError> {
+            let state = get_state(scope)?;
+            let parent = state
+                .parent_port
+                .as_mut()
+                .ok_or_else(|| MycoError::Internal {
+                    message: "Not running in a worker".to_string(),
+                })?;
+            parent.on_message = input.handler;
+            Ok(())
+        },
+    );
+}
@@ -0,0 +1,360 @@
+//! Kernel-enforced confinement for child processes.
+//!
+//! A child started with `grant` can only reach the files behind the tokens it was
+//! granted (plus the system directories needed to load a program, and read access
+//! to `/etc`, `/proc` and `/tmp`), enforced by Landlock. Unless a network token is
+//! granted, a seccomp filter also stops it from opening IPv4, IPv6 or Unix domain
+//! sockets. Architectures the filter has not been written for cannot be sandboxed
+//! without a network token at all.
+//!
+//! Everything that allocates or opens files happens in the parent, in
+//! [`Sandbox::new`]. [`Sandbox::apply`] runs between `fork` and `exec`, where only
+//! async-signal-safe calls are allowed, so it is limited to a few raw syscalls.
+
+use std::path::PathBuf;
+
+use crate::errors::MycoError;
+
+/// What a granted token lets the child do beneath its path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrantAccess {
+    Read,
+    Write,
+    Exec,
+}
+
+#[derive(Debug, Clone)]
+pub struct Grant {
+    pub path: PathBuf,
+    pub access: GrantAccess,
+}
+
+/// Paths every sandboxed program gets read and execute access to, so that it can
+/// be loaded at all. Missing paths are skipped.
+#[cfg(target_os = "linux")]
+const SYSTEM_PATHS: &[&str] = &["/usr", "/bin", "/sbin", "/lib", "/lib64"];
+
+/// Paths every sandboxed program may read, since so many programs expect to:
+/// configuration such as `/etc/passwd` and `/etc/resolv.conf`, their own entries
+/// in `/proc`, and other programs' files in `/tmp`. Missing paths are skipped.
+#[cfg(target_os = "linux")]
+const READ_ONLY_PATHS: &[&str] = &["/etc", "/proc", "/tmp"];
+
+/// Devices every sandboxed program may read and write.
+#[cfg(target_os = "linux")]
+const DEVICE_PATHS: &[&str] = &["/dev/null", "/dev/zero", "/dev/urandom"];
+
+pub struct Sandbox {
+    #[cfg(target_os = "linux")]
+    ruleset: std::os::fd::OwnedFd,
+    #[cfg(target_os = "linux")]
+    seccomp: Option<Vec<libc::sock_filter>>,
+}
+
+#[cfg(target_os = "linux")]
+mod landlock {
+    pub const CREATE_RULESET_VERSION: u32 = 1 << 0;
+    pub const RULE_PATH_BENEATH: u32 = 1;
+
+    pub const EXECUTE: u64 = 1 << 0;
+    pub const WRITE_FILE: u64 = 1 << 1;
+    pub const READ_FILE: u64 = 1 << 2;
+    pub const READ_DIR: u64 = 1 << 3;
+    pub const REMOVE_DIR: u64 = 1 << 4;
+    pub const REMOVE_FILE: u64 = 1 << 5;
+    pub const MAKE_DIR: u64 = 1 << 7;
+    pub const MAKE_REG: u64 = 1 << 8;
+    pub const MAKE_SOCK: u64 = 1 << 9;
+    pub const MAKE_FIFO: u64 = 1 << 10;
+    pub const MAKE_SYM: u64 = 1 << 12;
+    pub const REFER: u64 = 1 << 13;
+    pub const TRUNCATE: u64 = 1 << 14;
+
+    /// Rights that may be granted on a file rather than a directory.
+    pub const FILE_ACCESS: u64 = EXECUTE | WRITE_FILE | READ_FILE | TRUNCATE;
+
+    #[repr(C)]
+    pub struct RulesetAttr {
+        pub handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    pub struct PathBeneathAttr {
+        pub allowed_access: u64,
+        pub parent_fd: i32,
+    }
+
+    /// All filesystem rights known to the given ABI version.
+    pub fn handled_access(abi: libc::c_long) -> u64 {
+        let mut access = (1 << 13) - 1;
+        if abi >= 2 {
+            access |= REFER;
+        }
+        if abi >= 3 {
+            access |= TRUNCATE;
+        }
+        access
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl GrantAccess {
+    fn rights(self) -> u64 {
+        use landlock::*;
+        match self {
+            GrantAccess::Read => READ_FILE | READ_DIR,
+            GrantAccess::Write => {
+                WRITE_FILE
+                    | TRUNCATE
+                    | REMOVE_DIR
+                    | REMOVE_FILE
+                    | MAKE_DIR
+                    | MAKE_REG
+                    | MAKE_SYM
+                    | MAKE_FIFO
+                    | MAKE_SOCK
+                    | REFER
+            }
+            GrantAccess::Exec => EXECUTE | READ_FILE | READ_DIR,
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Sandbox {
+    pub fn new(grants: &[Grant], allow_network: bool) -> Result<Self, MycoError> {
+        use std::os::fd::{FromRawFd, OwnedFd};
+
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<landlock::RulesetAttr>(),
+                0usize,
+                landlock::CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            return Err(MycoError::Internal {
+                message: format!(
+                    "Cannot sandbox child process: Landlock is unavailable ({})",
+                    std::io::Error::last_os_error()
+                ),
+            });
+        }
+        let handled = landlock::handled_access(abi);
+
+        let attr = landlock::RulesetAttr {
+            handled_access_fs: handled,
+        };
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const landlock::RulesetAttr,
+                std::mem::size_of::<landlock::RulesetAttr>(),
+                0u32,
+            )
+        };
+        if fd < 0 {
+            return Err(MycoError::Internal {
+                message: format!(
+                    "Cannot sandbox child process: {}",
+                    std::io::Error::last_os_error()
+                ),
+            });
+        }
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        let system = SYSTEM_PATHS.iter().map(|path| (*path, GrantAccess::Exec));
+        let read_only = READ_ONLY_PATHS
+            .iter()
+            .map(|path| (*path, GrantAccess::Read));
+        let devices = DEVICE_PATHS
+            .iter()
+            .flat_map(|path| [(*path, GrantAccess::Read), (*path, GrantAccess::Write)]);
+        for (path, access) in system.chain(read_only).chain(devices) {
+            if std::path::Path::new(path).exists() {
+                add_rule(&ruleset, path.as_ref(), access.rights() & handled)?;
+            }
+        }
+        for grant in grants {
+            add_rule(&ruleset, &grant.path, grant.access.rights() & handled)?;
+        }
+
+        let seccomp = if allow_network {
+            None
+        } else {
+            Some(network_filter()?)
+        };
+
+        Ok(Self { ruleset, seccomp })
+    }
+
+    /// Confines the calling process. Only called in the child between `fork` and
+    /// `exec`, so it must not allocate.
+    pub fn apply(&self) -> std::io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            if libc::syscall(
+                libc::SYS_landlock_restrict_self,
+                self.ruleset.as_raw_fd(),
+                0u32,
+            ) != 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            if let Some(filter) = &self.seccomp {
+                let program = libc::sock_fprog {
+                    len: filter.len() as u16,
+                    filter: filter.as_ptr() as *mut libc::sock_filter,
+                };
+                if libc::prctl(
+                    libc::PR_SET_SECCOMP,
+                    libc::SECCOMP_MODE_FILTER,
+                    &program as *const libc::sock_fprog,
+                ) != 0
+                {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn add_rule(
+    ruleset: &std::os::fd::OwnedFd,
+    path: &std::path::Path,
+    access: u64,
+) -> Result<(), MycoError> {
+    use std::os::fd::AsRawFd;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let error = |e: std::io::Error| MycoError::Internal {
+        message: format!("Cannot grant '{}' to child process: {}", path.display(), e),
+    };
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+        .open(path)
+        .map_err(error)?;
+    let access = if file.metadata().map_err(error)?.is_dir() {
+        access
+    } else {
+        access & landlock::FILE_ACCESS
+    };
+    if access == 0 {
+        return Ok(());
+    }
+
+    let attr = landlock::PathBeneathAttr {
+        allowed_access: access,
+        parent_fd: file.as_raw_fd(),
+    };
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_landlock_add_rule,
+            ruleset.as_raw_fd(),
+            landlock::RULE_PATH_BENEATH,
+            &attr as *const landlock::PathBeneathAttr,
+            0u32,
+        )
+    };
+    if result != 0 {
+        return Err(error(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// The `AUDIT_ARCH_*` value seccomp reports for native syscalls, on the
+/// architectures whose `socket` is a syscall of its own rather than a
+/// `socketcall` multiplexer the filter could not see into.
+#[cfg(target_os = "linux")]
+const AUDIT_ARCH: Option<u32> = if cfg!(target_arch = "x86_64") {
+    Some(0xC000_003E)
+} else if cfg!(target_arch = "aarch64") {
+    Some(0xC000_00B7)
+} else if cfg!(target_arch = "riscv64") {
+    Some(0xC000_00F3)
+} else {
+    None
+};
+
+/// A seccomp program that makes `socket(AF_INET | AF_INET6 | AF_UNIX, ...)` and
+/// `io_uring_setup` (which can open sockets on its own) fail with `EACCES`.
+/// Syscalls made through a foreign ABI are refused outright, since their numbers
+/// mean something else. On an architecture the filter has not been written for,
+/// this fails rather than leave the child unfiltered.
+#[cfg(target_os = "linux")]
+fn network_filter() -> Result<Vec<libc::sock_filter>, MycoError> {
+    const LD_ABS: u16 = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+    const JEQ: u16 = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+    const JGE: u16 = (libc::BPF_JMP | libc::BPF_JGE | libc::BPF_K) as u16;
+    const RET: u16 = (libc::BPF_RET | libc::BPF_K) as u16;
+    const ALLOW: u32 = libc::SECCOMP_RET_ALLOW;
+    const DENY: u32 = libc::SECCOMP_RET_ERRNO | libc::EACCES as u32;
+    // Offsets into `struct seccomp_data`
+    const NR: u32 = 0;
+    const ARCH: u32 = 4;
+    const ARG0: u32 = 16;
+    // x32 syscalls on x86_64 have this bit set; no other syscall number is this high
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    let Some(audit_arch) = AUDIT_ARCH else {
+        return Err(MycoError::Internal {
+            message: format!(
+                "Cannot sandbox child process without network access: sockets cannot be filtered on {}",
+                std::env::consts::ARCH
+            ),
+        });
+    };
+
+    let op = |code: u16, k: u32, jt: u8, jf: u8| libc::sock_filter { code, jt, jf, k };
+    Ok(vec![
+        op(LD_ABS, ARCH, 0, 0),
+        op(JEQ, audit_arch, 1, 0),
+        op(RET, DENY, 0, 0),
+        op(LD_ABS, NR, 0, 0),
+        op(JGE, X32_SYSCALL_BIT, 0, 1),
+        op(RET, DENY, 0, 0),
+        op(JEQ, libc::SYS_io_uring_setup as u32, 0, 1),
+        op(RET, DENY, 0, 0),
+        op(JEQ, libc::SYS_socket as u32, 1, 0),
+        op(RET, ALLOW, 0, 0),
+        op(LD_ABS, ARG0, 0, 0),
+        op(JEQ, libc::AF_INET as u32, 3, 0),
+        op(JEQ, libc::AF_INET6 as u32, 2, 0),
+        op(JEQ, libc::AF_UNIX as u32, 1, 0),
+        op(RET, ALLOW, 0, 0),
+        op(RET, DENY, 0, 0),
+    ])
+}
+
+#[cfg(not(target_os = "linux"))]
+impl Sandbox {
+    pub fn new(_grants: &[Grant], _allow_network: bool) -> Result<Self, MycoError> {
+        Err(MycoError::Internal {
+            message: "Sandboxing child processes is only supported on Linux".to_string(),
+        })
+    }
+
+    pub fn apply(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Confines `command`'s child to `sandbox` once it is spawned.
+pub fn sandbox_command(command: &mut std::process::Command, sandbox: Sandbox) {
+    #[cfg(unix)]
+    unsafe {
+        std::os::unix::process::CommandExt::pre_exec(command, move || sandbox.apply());
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (command, sandbox);
+    }
+}
@@ -56,6 +56,47 @@ pub fn capture_call_site_stack(scope: &mut v8::PinScope<'_, '_>, skip_frames: us
     }
 }
 
+/// The `file:line` of the innermost frame outside the runtime, which is the user code
+/// that called into it.
+pub fn capture_call_site(scope: &mut v8::PinScope<'_, '_>) -> Option<String> {
+    let (script_name, line_number, column_number) = caller_frame(scope)?;
+    let location =
+        match map_location_with_source_maps(scope, &script_name, line_number, column_number) {
+            Some((mapped_file, mapped_line, _)) => format!("{}:{}", mapped_file, mapped_line),
+            None => format!("{}:{}", script_name, line_number),
+        };
+    Some(location)
+}
+
+/// The URL of the module whose code called into the runtime.
+pub fn caller_script_name(scope: &mut v8::PinScope<'_, '_>) -> Option<String> {
+    caller_frame(scope).map(|(script_name, _, _)| script_name)
+}
+
+/// The script name, line and column of the innermost frame outside the runtime. The
+/// runtime script has no name, so its frames are skipped.
+fn caller_frame(scope: &mut v8::PinScope<'_, '_>) -> Option<(String, u32, u32)> {
+    let stack_trace = v8::StackTrace::current_stack_trace(scope, 10)?;
+
+    for i in 0..stack_trace.get_frame_count() {
+        let Some(frame) = stack_trace.get_frame(scope, i) else {
+            continue;
+        };
+        match frame.get_script_name(scope) {
+            Some(name) if name.length() > 0 => {
+                return Some((
+                    name.to_rust_string_lossy(scope),
+                    frame.get_line_number() as u32,
+                    frame.get_column() as u32,
+                ))
+            }
+            _ => continue,
+        }
+    }
+
+    None
+}
+
 /// Generate and format a stack trace from V8 StackTrace with source mapping
 pub fn format_v8_stack_trace_with_source_maps(
     scope: &mut v8::PinScope<'_, '_>,
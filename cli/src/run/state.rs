@@ -1,15 +1,22 @@
+use crate::cache::{CodeCache, TranspileCache};
+use crate::deps::InstalledPackages;
+use crate::manifest::import_map::ImportMap;
 use crate::manifest::myco_local::MycoLocalToml;
 use crate::run::capabilities::CapabilityRegistry;
 use crate::run::inspector;
+use crate::run::limits::Limits;
 use crate::Capability;
 use log::{debug, info, trace, warn};
 use sourcemap::SourceMap;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::time::Instant;
-use tokio::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
 
 #[derive(Debug, Clone)]
 pub struct DebugOptions {
@@ -18,11 +25,48 @@ pub struct DebugOptions {
     pub wait_for_connection: bool,
 }
 
-// Timer structure to track pending timeouts
+/// How console calls are written, chosen with `myco run --log-format`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Formatted lines, as a terminal shows them.
+    #[default]
+    Text,
+    /// One JSON object per call, for log collectors.
+    Json,
+}
+
+/// What happens to a promise rejection nobody handles, chosen with
+/// `myco run --unhandled-rejections` unless the script listens for them itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnhandledRejections {
+    /// Report it and fail the process.
+    #[default]
+    Strict,
+    /// Report it and carry on.
+    Warn,
+}
+
+/// How `myco run` runs a script.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub debug: Option<DebugOptions>,
+    pub log_format: LogFormat,
+    pub unhandled_rejections: UnhandledRejections,
+    /// The most heap each isolate may use, instead of V8's default.
+    pub max_heap_mb: Option<usize>,
+    /// How long the script may run before it is stopped.
+    pub timeout: Option<Duration>,
+}
+
+// Timer structure to track pending timeouts and intervals
 pub struct Timer {
     pub id: u32,
     pub callback: v8::Global<v8::Function>,
     pub execute_at: Instant,
+    // Set for intervals, which are rescheduled every time they fire
+    pub interval: Option<Duration>,
+    // Unref'd timers still fire, but do not keep the event loop alive
+    pub refed: bool,
 }
 
 impl Timer {
@@ -31,20 +75,244 @@ impl Timer {
             id,
             callback,
             execute_at,
+            interval: None,
+            refed: true,
+        }
+    }
+
+    pub fn repeating(id: u32, callback: v8::Global<v8::Function>, interval: Duration) -> Self {
+        Self {
+            interval: Some(interval),
+            ..Self::new(id, callback, Instant::now() + interval)
+        }
+    }
+}
+
+/// Pending timers, ordered by deadline. Timers created with the same deadline fire
+/// in creation order. Cancelled timers leave a stale entry in the heap, which is
+/// skipped once it reaches the top.
+#[derive(Default)]
+pub struct Timers {
+    queue: BinaryHeap<Reverse<(Instant, u32)>>,
+    timers: HashMap<u32, Timer>,
+    refed: usize,
+}
+
+impl Timers {
+    pub fn insert(&mut self, timer: Timer) {
+        self.queue.push(Reverse((timer.execute_at, timer.id)));
+        if timer.refed {
+            self.refed += 1;
+        }
+        if let Some(old) = self.timers.insert(timer.id, timer) {
+            if old.refed {
+                self.refed -= 1;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<Timer> {
+        let timer = self.timers.remove(&id)?;
+        if timer.refed {
+            self.refed -= 1;
+        }
+        Some(timer)
+    }
+
+    /// Marks whether the timer keeps the event loop alive. Unknown ids are ignored.
+    pub fn set_ref(&mut self, id: u32, refed: bool) {
+        if let Some(timer) = self.timers.get_mut(&id) {
+            if timer.refed != refed {
+                timer.refed = refed;
+                if refed {
+                    self.refed += 1;
+                } else {
+                    self.refed -= 1;
+                }
+            }
+        }
+    }
+
+    /// Whether any pending timer keeps the event loop alive.
+    pub fn has_refs(&self) -> bool {
+        self.refed > 0
+    }
+
+    /// The deadline of the earliest live timer.
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        self.discard_stale();
+        self.queue
+            .peek()
+            .map(|Reverse((execute_at, _))| *execute_at)
+    }
+
+    /// Takes the earliest timer if it is due at `now`, returning its id and
+    /// callback. A timeout is removed; an interval is rescheduled before its
+    /// callback runs, so the callback can still clear it.
+    pub fn pop_due(&mut self, now: Instant) -> Option<(u32, v8::Global<v8::Function>)> {
+        match self.next_deadline() {
+            Some(execute_at) if execute_at <= now => {
+                let Reverse((_, id)) = self.queue.pop()?;
+                let timer = self.timers.get_mut(&id)?;
+                match timer.interval {
+                    Some(interval) => {
+                        timer.execute_at = Instant::now() + interval;
+                        let callback = timer.callback.clone();
+                        self.queue.push(Reverse((timer.execute_at, id)));
+                        Some((id, callback))
+                    }
+                    None => self.remove(id).map(|timer| (id, timer.callback)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // A heap entry is stale when its timer was removed or rescheduled.
+    fn discard_stale(&mut self) {
+        while let Some(Reverse((execute_at, id))) = self.queue.peek() {
+            match self.timers.get(id) {
+                Some(timer) if timer.execute_at == *execute_at => break,
+                _ => {
+                    self.queue.pop();
+                }
+            }
         }
     }
 }
 
+/// Callbacks queued with `setImmediate`, in order. They share ids with timers, so
+/// clearing or unref'ing works the same way for both.
+#[derive(Default)]
+pub struct Immediates {
+    queue: VecDeque<(u32, v8::Global<v8::Function>)>,
+    unrefed: HashSet<u32>,
+}
+
+impl Immediates {
+    pub fn push(&mut self, id: u32, callback: v8::Global<v8::Function>) {
+        self.queue.push_back((id, callback));
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        self.queue.retain(|(queued, _)| *queued != id);
+        self.unrefed.remove(&id);
+    }
+
+    /// Marks whether the immediate keeps the event loop alive. Unknown ids are ignored.
+    pub fn set_ref(&mut self, id: u32, refed: bool) {
+        if !self.queue.iter().any(|(queued, _)| *queued == id) {
+            return;
+        }
+        if refed {
+            self.unrefed.remove(&id);
+        } else {
+            self.unrefed.insert(id);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Whether any queued immediate keeps the event loop alive.
+    pub fn has_refs(&self) -> bool {
+        self.queue.iter().any(|(id, _)| !self.unrefed.contains(id))
+    }
+
+    /// Takes the first immediate if it was queued before `limit`, the next id at the
+    /// start of the round, so ones queued while running wait for the next round.
+    pub fn pop_before(&mut self, limit: u32) -> Option<(u32, v8::Global<v8::Function>)> {
+        if self.queue.front()?.0 >= limit {
+            return None;
+        }
+        let (id, callback) = self.queue.pop_front()?;
+        self.unrefed.remove(&id);
+        Some((id, callback))
+    }
+}
+
+/// A JS handler registered with `Myco.process.onSignal`.
+pub struct SignalListener {
+    pub signal: i32,
+    pub name: String,
+    pub callback: v8::Global<v8::Function>,
+}
+
+/// A message between a worker and its parent, carried over the receiving
+/// isolate's port channel. Message bodies are structured-clone bytes, or `None`
+/// for `undefined`.
+pub enum PortMessage {
+    FromWorker { id: u32, data: Option<Vec<u8>> },
+    WorkerExited { id: u32 },
+    FromParent(Option<Vec<u8>>),
+    Terminate,
+}
+
+/// The parent's side of a worker started with `Myco.workers.spawn`.
+pub struct WorkerHandle {
+    pub sender: mpsc::UnboundedSender<PortMessage>,
+    pub isolate: Arc<OnceLock<v8::IsolateHandle>>,
+    pub terminated: Arc<AtomicBool>,
+    pub exit: Option<oneshot::Receiver<i32>>,
+    pub on_message: Option<v8::Global<v8::Function>>,
+    // Messages that arrived before a handler was set
+    pub queued: Vec<Option<Vec<u8>>>,
+}
+
+impl WorkerHandle {
+    /// Stops the worker: interrupts any running JS, and wakes its event loop so
+    /// that it exits.
+    pub fn terminate(&self) {
+        self.terminated.store(true, Ordering::SeqCst);
+        let _ = self.sender.send(PortMessage::Terminate);
+        if let Some(isolate) = self.isolate.get() {
+            isolate.terminate_execution();
+        }
+    }
+}
+
+/// A worker's side of the channel to the isolate that spawned it.
+pub struct ParentPort {
+    pub id: u32,
+    pub sender: mpsc::UnboundedSender<PortMessage>,
+    pub on_message: Option<v8::Global<v8::Function>>,
+    pub queued: Vec<Option<Vec<u8>>>,
+}
+
 // State that gets stored in the V8 isolate
 pub struct MycoState {
     pub capabilities: CapabilityRegistry,
+    // Loaded modules by URL, and the file each was loaded from
     pub module_cache: HashMap<String, v8::Global<v8::Module>>,
-    pub timers: Vec<Timer>,
+    pub timers: Timers,
+    pub immediates: Immediates,
     pub next_timer_id: u32,
-    pub module_url_to_path: HashMap<String, PathBuf>,
+    // What `performance.now()` counts from
+    pub time_origin: Instant,
+    pub module_paths: HashMap<v8::Global<v8::Module>, PathBuf>,
     pub source_maps: HashMap<String, SourceMap>,
     pub inspector: Option<Rc<RefCell<inspector::MycoInspector>>>,
     pub myco_local: Option<MycoLocalToml>,
+    pub import_map: Option<ImportMap>,
+    pub packages: Option<InstalledPackages>,
+    // The canonical project root, which JSON modules must be inside
+    pub project_dir: Option<PathBuf>,
+    pub log_format: LogFormat,
+    pub max_heap_mb: Option<usize>,
+    pub limits: Option<Arc<Limits>>,
+    pub transpile_cache: Option<TranspileCache>,
+    pub code_cache: Option<CodeCache>,
+    // Modules compiled without usable cached code, with their cache keys. Their code
+    // is cached once the event loop finishes, so it includes lazily compiled functions.
+    pub code_cache_misses: Vec<(String, v8::Global<v8::Module>)>,
+    // Parsed JSON modules waiting to be evaluated, which sets them as the default export
+    pub json_modules: HashMap<v8::Global<v8::Module>, v8::Global<v8::Value>>,
+    // The `module` object of each CommonJS or required JSON file, by canonical path
+    pub commonjs_modules: HashMap<PathBuf, v8::Global<v8::Object>>,
+    // Imported CommonJS files waiting to be evaluated, with their exports and the
+    // names of their named exports
+    pub commonjs_exports: HashMap<v8::Global<v8::Module>, (v8::Global<v8::Value>, Vec<String>)>,
 
     // Async operation management
     pub runtime_handle: tokio::runtime::Handle,
@@ -53,16 +321,47 @@ pub struct MycoState {
     pub op_sender: mpsc::UnboundedSender<FinalOpResult>,
     pub op_receiver: Option<mpsc::UnboundedReceiver<FinalOpResult>>,
 
+    // Signal handling: one forwarding task per signal number feeds `signal_sender`,
+    // and the event loop dispatches each delivery to the matching listeners. A
+    // signal with none left goes to `signal_default`, which exits the way
+    // `Myco.process.exit` does.
+    pub signal_listeners: HashMap<u32, SignalListener>,
+    pub signal_default: Option<v8::Global<v8::Function>>,
+    pub next_signal_listener_id: u32,
+    pub forwarded_signals: HashSet<i32>,
+    pub signal_sender: mpsc::UnboundedSender<i32>,
+    pub signal_receiver: Option<mpsc::UnboundedReceiver<i32>>,
+
+    // Workers: messages from this isolate's workers, and from its parent if it is
+    // a worker itself, all arrive on `port_receiver`.
+    pub workers: HashMap<u32, WorkerHandle>,
+    pub next_worker_id: u32,
+    pub parent_port: Option<ParentPort>,
+    pub port_sender: mpsc::UnboundedSender<PortMessage>,
+    pub port_receiver: Option<mpsc::UnboundedReceiver<PortMessage>>,
+
     // Result of the user module's default export, recorded by native callbacks on the
     // promise chain rather than via globals.
     pub exit_code: i32,
     pub unhandled_error: Option<v8::Global<v8::Value>>,
+
+    // Promises rejected with no handler, with their reasons. The event loop reports
+    // the ones still unhandled once the microtask queue has drained.
+    pub unhandled_rejections: UnhandledRejections,
+    pub pending_rejections: Vec<(v8::Global<v8::Promise>, v8::Global<v8::Value>)>,
+    pub rejection_listeners: HashMap<u32, v8::Global<v8::Function>>,
+    pub next_rejection_listener_id: u32,
+    // Set by `Myco.process.exit`; the event loop stops as soon as it sees it, and
+    // it takes precedence over the entry point's exit code.
+    pub exit_requested: Option<i32>,
 }
 
 impl MycoState {
     pub fn new(myco_local: Option<MycoLocalToml>, runtime_handle: tokio::runtime::Handle) -> Self {
         debug!("Creating new Myco runtime state");
         let (op_sender, op_receiver) = mpsc::unbounded_channel();
+        let (signal_sender, signal_receiver) = mpsc::unbounded_channel();
+        let (port_sender, port_receiver) = mpsc::unbounded_channel();
 
         let has_myco_local = myco_local.is_some();
         debug!("Myco local configuration present: {}", has_myco_local);
@@ -70,19 +369,49 @@ impl MycoState {
         let state = Self {
             capabilities: CapabilityRegistry::new(),
             module_cache: HashMap::new(),
-            timers: Vec::new(),
+            timers: Timers::default(),
+            immediates: Immediates::default(),
             next_timer_id: 1,
-            module_url_to_path: HashMap::new(),
+            time_origin: Instant::now(),
+            module_paths: HashMap::new(),
             source_maps: HashMap::new(),
             inspector: None,
             myco_local,
+            import_map: None,
+            packages: None,
+            project_dir: None,
+            log_format: LogFormat::default(),
+            max_heap_mb: None,
+            limits: None,
+            transpile_cache: None,
+            code_cache: None,
+            code_cache_misses: Vec::new(),
+            json_modules: HashMap::new(),
+            commonjs_modules: HashMap::new(),
+            commonjs_exports: HashMap::new(),
             runtime_handle,
             pending_ops: HashMap::new(),
             next_op_id: 1,
             op_sender,
             op_receiver: Some(op_receiver),
+            signal_listeners: HashMap::new(),
+            signal_default: None,
+            next_signal_listener_id: 1,
+            forwarded_signals: HashSet::new(),
+            signal_sender,
+            signal_receiver: Some(signal_receiver),
+            workers: HashMap::new(),
+            next_worker_id: 1,
+            parent_port: None,
+            port_sender,
+            port_receiver: Some(port_receiver),
             exit_code: 0,
             unhandled_error: None,
+            unhandled_rejections: UnhandledRejections::default(),
+            pending_rejections: Vec::new(),
+            rejection_listeners: HashMap::new(),
+            next_rejection_listener_id: 1,
+            exit_requested: None,
         };
 
         debug!("Myco runtime state created successfully");
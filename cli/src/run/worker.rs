@@ -0,0 +1,126 @@
+//! Worker isolates started with `Myco.workers.spawn`.
+//!
+//! Each worker runs its module in a fresh isolate, with its own `MycoState`, event
+//! loop and Tokio runtime, on a dedicated thread. The only capabilities it holds are
+//! the ones its parent transferred to it, and it only talks to its parent through
+//! structured-clone messages.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use log::{debug, error};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::deps::InstalledPackages;
+use crate::errors::MycoError;
+use crate::impl_to_v8_struct;
+use crate::manifest::import_map::ImportMap;
+use crate::manifest::myco_local::MycoLocalToml;
+use crate::run::capabilities::{Capability, Token};
+use crate::run::engine;
+use crate::run::state::{MycoState, ParentPort, PortMessage, RunOptions};
+
+/// Everything a worker's isolate is seeded with by its parent.
+pub struct WorkerInit {
+    pub id: u32,
+    /// Transferred capabilities, grouped by the token object they came from.
+    pub capabilities: Vec<Vec<(Token, Capability)>>,
+    pub parent: mpsc::UnboundedSender<PortMessage>,
+    pub sender: mpsc::UnboundedSender<PortMessage>,
+    pub receiver: mpsc::UnboundedReceiver<PortMessage>,
+    pub isolate: Arc<OnceLock<v8::IsolateHandle>>,
+    /// Options carried over from the parent.
+    pub options: RunOptions,
+    /// The parent's import map, aliases and installed packages, so the worker resolves
+    /// imports the same way.
+    pub import_map: Option<ImportMap>,
+    pub myco_local: Option<MycoLocalToml>,
+    pub packages: Option<InstalledPackages>,
+}
+
+/// A transferred native token, as handed to the runtime factory so it can rebuild
+/// the token object.
+pub struct TransferredToken {
+    kind: String,
+    token: String,
+}
+
+impl_to_v8_struct!(TransferredToken { kind, token });
+
+impl WorkerInit {
+    /// Moves the transferred capabilities and the parent's channel into the
+    /// worker's state, returning the token groups for the runtime factory.
+    pub fn install(
+        self,
+        state: &mut MycoState,
+        isolate: &mut v8::Isolate,
+    ) -> Vec<Vec<TransferredToken>> {
+        let _ = self.isolate.set(isolate.thread_safe_handle());
+
+        state.parent_port = Some(ParentPort {
+            id: self.id,
+            sender: self.parent,
+            on_message: None,
+            queued: Vec::new(),
+        });
+        state.port_sender = self.sender;
+        state.port_receiver = Some(self.receiver);
+
+        self.capabilities
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .filter_map(|(token, capability)| {
+                        let kind = capability.transfer_kind()?.to_string();
+                        state.capabilities.adopt(token.clone(), capability);
+                        Some(TransferredToken { kind, token })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Starts `path` in a new worker thread. The returned receiver yields the worker's
+/// exit code once it has stopped.
+pub fn spawn(
+    path: PathBuf,
+    init: WorkerInit,
+    terminated: Arc<AtomicBool>,
+) -> Result<oneshot::Receiver<i32>, MycoError> {
+    let (exit_sender, exit_receiver) = oneshot::channel();
+    let id = init.id;
+    let parent = init.parent.clone();
+
+    std::thread::Builder::new()
+        .name(format!("myco-worker-{}", id))
+        .spawn(move || {
+            debug!("Worker {} starting: {}", id, path.display());
+            let result = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| MycoError::TokioRuntime { source: e })
+                .and_then(|runtime| runtime.block_on(engine::run_worker(&path, init)));
+
+            let exit_code = match result {
+                // A terminated worker always exits with 1, whatever it was doing
+                _ if terminated.load(Ordering::SeqCst) => 1,
+                Ok(exit_code) => exit_code,
+                Err(e) => {
+                    error!("Worker '{}' failed: {}", path.display(), e);
+                    1
+                }
+            };
+            debug!("Worker {} exited with code {}", id, exit_code);
+
+            let _ = parent.send(PortMessage::WorkerExited { id });
+            let _ = exit_sender.send(exit_code);
+        })
+        .map_err(|e| MycoError::Internal {
+            message: format!("Failed to start worker thread: {}", e),
+        })?;
+
+    Ok(exit_receiver)
+}
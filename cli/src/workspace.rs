@@ -164,6 +164,8 @@ pub fn install_workspace(workspace: &Workspace, save: bool) -> Result<(), MycoEr
             registries: workspace.manifest.registries.clone(),
             deps: Some(aggregated_deps),
             tsconfig: workspace.manifest.tsconfig.clone(),
+            imports: None,
+            scopes: None,
         };
 
         // Change to workspace root to generate lockfile there
@@ -440,7 +442,11 @@ pub fn run_workspace_script(
             dir: member.path.display().to_string(),
         })?;
 
-        let exit_code = crate::run::run(&member.manifest, &script.to_string(), None)?;
+        let exit_code = crate::run::run(
+            &member.manifest,
+            &script.to_string(),
+            crate::run::RunOptions::default(),
+        )?;
         debug!(
             "Script '{}' in '{}' exited with code: {}",
             script, member.name, exit_code
@@ -470,7 +476,7 @@ pub fn run_workspace_script(
 /// Check if a workspace member has a script defined
 fn member_has_script(member: &WorkspaceMember, script: &str) -> bool {
     if let Some(run_scripts) = &member.manifest.run {
-        run_scripts.contains_key(script)
+        run_scripts.scripts.contains_key(script)
     } else {
         false
     }
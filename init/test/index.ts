@@ -1,4 +1,4 @@
-import {run, expect} from "vendor/@myco/test";
+import {run, expect} from "@myco/test";
 import {message} from "../src";
 
 export default function () {
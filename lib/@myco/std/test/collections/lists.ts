@@ -1,4 +1,4 @@
-import {TestSuite, expect} from "vendor/@myco/test";
+import {TestSuite, expect} from "@myco/test";
 import {ArrayList} from "../../src/collections";
 
 export const listsTests: TestSuite = {
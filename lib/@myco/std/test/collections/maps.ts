@@ -1,4 +1,4 @@
-import {TestSuite, expect} from "vendor/@myco/test";
+import {TestSuite, expect} from "@myco/test";
 import {HashMap, mapOf} from "../../src/collections";
 
 export const mapsTest: TestSuite = {
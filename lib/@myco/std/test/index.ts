@@ -1,4 +1,4 @@
-import {run} from "vendor/@myco/test";
+import {run} from "@myco/test";
 import {listsTests} from "./collections/lists";
 import {queuesTests} from './collections/queues';
 import {setsTests} from "./collections/sets";
@@ -1,4 +1,4 @@
-import {TestSuite, expect} from "vendor/@myco/test";
+import {TestSuite, expect} from "@myco/test";
 import {listOf} from "../src/collections";
 import {asyncStreamOf, Stream, streamOf} from "../src/streams";
 
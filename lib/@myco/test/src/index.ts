@@ -1,4 +1,4 @@
-import {equals} from "vendor/@myco/std/core";
+import {equals} from "@myco/std/core.ts";
 
 export interface TestSuite {
     [name: string]: TestSuite | (() => void);
@@ -1,4 +1,4 @@
-export default async function ({http, files}: Myco) {
+export default async function ({http, files, env}: Myco) {
     const dir = await files.requestReadWriteDir(".");
 
     await removeRecursively(dir, 'src');
@@ -7,7 +7,7 @@ export default async function ({http, files}: Myco) {
     await dir.write("typescript.tar.gz", tgz);
 
     const echo = await files.requestExec("./extract.sh");
-    const result = await echo.exec();
+    const result = await echo.exec([], { inheritEnv: [await env.request("PATH")] });
 
     if (!result.exit_code) {
         const libs = await dir.list("package/lib");
@@ -1,14 +1,68 @@
 declare interface Myco {
     files: Myco.Files;
     http: Myco.Http;
+    env: Myco.Env;
+    crypto: Myco.Crypto;
+    archive: Myco.Archive;
+    process: Myco.Process;
+    workers: Myco.Workers;
 
     argv: string[];
 
-    setTimeout(callback: () => void, delay: number): number;
-    clearTimeout(timerId: number): void;
+    setTimeout(callback: () => void, delay?: number): Myco.Timeout;
+    clearTimeout(timer: Myco.Timeout | number | undefined): void;
+
+    setInterval(callback: () => void, delay?: number): Myco.Timeout;
+    clearInterval(timer: Myco.Timeout | number | undefined): void;
+
+    /**
+     * Runs `callback` once the current turn of the event loop is over, before any
+     * timer that is due. Immediates queued while immediates run wait for the next turn.
+     */
+    setImmediate(callback: () => void): Myco.Timeout;
+    clearImmediate(timer: Myco.Timeout | number | undefined): void;
+
+    queueMicrotask(callback: () => void): void;
+
+    /**
+     * Formats `value` the way `console.log` does. An object can take over its own
+     * formatting with a `[Myco.inspect.custom](options)` method, returning either
+     * the text to show or another value to inspect in its place.
+     */
+    inspect: Myco.Inspect;
 }
 
 declare namespace Myco {
+    interface Inspect {
+        (value: any, options?: InspectOptions): string;
+
+        readonly custom: unique symbol;
+    }
+
+    interface InspectOptions {
+        /** How many levels of nesting to show; `null` for no limit. Defaults to 2. */
+        depth?: number | null;
+        /** Whether to use ANSI colors. Defaults to false. */
+        colors?: boolean;
+        /** The line length beyond which entries go on separate lines. Defaults to 80. */
+        breakLength?: number;
+        /** How many elements of an array, set or map to show. Defaults to 100. */
+        maxArrayLength?: number;
+        /** How many characters of a string to show. Defaults to 10000. */
+        maxStringLength?: number;
+    }
+
+    /** A pending timer or immediate. It converts to its numeric id. */
+    interface Timeout {
+        /** Lets the timer keep the process alive again after `unref`. */
+        ref(): this;
+        /** The timer still fires, but no longer keeps the process alive on its own. */
+        unref(): this;
+        /** Whether the timer keeps the process alive; `true` until `unref` is called. */
+        hasRef(): boolean;
+        [Symbol.toPrimitive](): number;
+    }
+
     interface Files {
         requestRead(path: string): Promise<Files.ReadToken>;
 
@@ -31,6 +85,99 @@ declare namespace Myco {
         chdir(path: string): void;
     }
 
+    interface Archive {
+        /**
+         * Zips the directory at `path` within `source` into the file behind `destination`.
+         * Entry names are relative to `path`; symlinks are skipped.
+         */
+        zip(source: Files.ReadDirToken, path: string, destination: Files.WriteToken): Promise<void>;
+
+        /**
+         * Extracts the zip file behind `source` into `path` within `destination` (default
+         * the token's root) and returns the extracted entry names. Archives with entries
+         * that would land outside the destination are rejected.
+         */
+        unzip(source: Files.ReadToken, destination: Files.WriteDirToken, path?: string): Promise<string[]>;
+
+        sync: {
+            zip(source: Files.ReadDirToken, path: string, destination: Files.WriteToken): void;
+            unzip(source: Files.ReadToken, destination: Files.WriteDirToken, path?: string): string[];
+        }
+    }
+
+    interface Crypto {
+        /**
+         * Computes the digest of `bytes`. Strings are hashed as UTF-8.
+         */
+        digest(algorithm: Crypto.DigestAlgorithm, bytes: string | Uint8Array): Promise<Uint8Array>;
+
+        sync: {
+            digest(algorithm: Crypto.DigestAlgorithm, bytes: string | Uint8Array): Uint8Array;
+        }
+    }
+
+    interface Process {
+        readonly pid: number;
+
+        /** The operating system, e.g. `linux` or `macos`. */
+        readonly platform: string;
+
+        /** The CPU architecture, e.g. `x86_64` or `aarch64`. */
+        readonly arch: string;
+
+        /**
+         * Calls `handler` each time the process receives `signal`, and returns a function
+         * that removes it. Listeners keep the process alive until they are removed. Once a
+         * signal has been listened for, receiving it with no listener left waits for the
+         * handlers still running, then exits with code 128 + signal as `exit` does, so the
+         * `beforeExit` hooks run. Only the main thread can listen for signals; in a
+         * worker this throws.
+         */
+        onSignal(signal: Process.Signal, handler: (signal: Process.Signal) => void | Promise<void>): () => void;
+
+        /**
+         * Calls `handler` for each promise that is rejected and still has no handler once
+         * the current task's microtasks have run, and returns a function that removes it.
+         * While any handler is registered, unhandled rejections no longer fail the process
+         * or print a warning, whatever `--unhandled-rejections` says.
+         */
+        onUnhandledRejection(handler: (reason: unknown, promise: Promise<unknown>) => void): () => void;
+
+        /**
+         * Registers a hook that `exit` runs, and awaits, before the process exits. Hooks
+         * run in registration order; one that throws is reported and the rest still run.
+         */
+        beforeExit(hook: (code: number) => void | Promise<void>): void;
+
+        /**
+         * Runs the `beforeExit` hooks, then exits with `code` once the current task
+         * finishes. The returned promise never settles, so `await` it to stop the caller.
+         */
+        exit(code?: number): Promise<never>;
+    }
+
+    interface Workers {
+        /**
+         * Runs the module at `modulePath` (relative to the calling module) in a new
+         * isolate on its own thread, calling its default export with a powerbox of its
+         * own. The tokens in `capabilities` move to the worker: they stop working here,
+         * and they are the only capabilities the worker has, since it cannot request any.
+         * Tokens for locks, child processes and sockets cannot be transferred.
+         */
+        spawn(modulePath: string, options?: Workers.SpawnOptions): Workers.Worker;
+
+        /** The parent of this isolate, when it is a worker. */
+        readonly parent?: Workers.Parent;
+    }
+
+    interface Env {
+        /**
+         * Grants access to the environment variable `name`: reading it, and passing it
+         * to children through `inheritEnv`.
+         */
+        request(name: string): Promise<Env.Token>;
+    }
+
     interface Http {
         requestFetch(url: string): Promise<Http.FetchToken>;
         
@@ -63,32 +210,60 @@ declare namespace Myco {
 
             stat(): Promise<Stats | null>;
 
+            /**
+             * Streams the file through `algorithm` (default `sha256`) and returns the hex digest.
+             */
+            hash(algorithm?: Crypto.DigestAlgorithm): Promise<string>;
+
             sync: {
                 read(): string;
                 read<T extends 'utf-8' | 'raw'>(encoding: T): T extends 'raw' ? Uint8Array : string;
                 read(encoding: 'utf-8' | 'raw'): string | Uint8Array;
                 stat(): Stats | null;
+                hash(algorithm?: Crypto.DigestAlgorithm): string;
             }
         }
 
+        interface WriteOptions {
+            /**
+             * Write to a temporary sibling file, fsync it and rename it into place, so the
+             * file is never observed half-written. Defaults to false.
+             */
+            readonly atomic?: boolean;
+        }
+
+        /**
+         * An exclusive advisory lock on a file. The lock is released by `release()`, by
+         * `await using`, or when the isolate exits.
+         */
+        interface FileLock {
+            release(): void;
+        }
+
         interface WriteToken {
-            write(contents: string | Uint8Array): Promise<void>;
+            write(contents: string | Uint8Array, options?: WriteOptions): Promise<void>;
 
             remove(): Promise<void>;
 
             sync: {
-                write(contents: string | Uint8Array): void;
+                write(contents: string | Uint8Array, options?: WriteOptions): void;
                 remove(): void;
             }
         }
 
         interface ExecToken {
-            exec(args?: readonly string[]): Promise<ExecResult>;
+            exec(args?: readonly string[], options?: ExecOptions): Promise<ExecResult>;
+
+            /**
+             * Starts the program without waiting for it to finish. Its output can be read
+             * incrementally, and it is killed when the isolate exits.
+             */
+            spawn(args?: readonly string[], options?: SpawnOptions): Child;
 
             stat(): Promise<Stats | null>;
 
             sync: {
-                exec(args?: readonly string[]): ExecResult;
+                exec(args?: readonly string[], options?: ExecOptions): ExecResult;
                 stat(): Stats | null;
             }
         }
@@ -99,7 +274,10 @@ declare namespace Myco {
 
         interface ListDirOptions {
             /**
-             * Whether to recurse into subdirectories. Defaults to false.
+             * Whether to recurse into subdirectories. Defaults to false. Recursive listings
+             * name entries by their path relative to the listed directory, depth first in
+             * file name order: each directory comes right before its own contents.
+             * `.gitignore` files are not applied.
              */
             readonly recursive?: boolean;
             /**
@@ -120,6 +298,25 @@ declare namespace Myco {
             readonly include_symlinks?: boolean;
         }
 
+        interface GlobOptions {
+            /**
+             * The directory to match from, relative to the token. Defaults to the token's root.
+             */
+            readonly cwd?: string;
+            /**
+             * Glob patterns for paths to skip. A matching directory is not descended into.
+             */
+            readonly ignore?: readonly string[];
+            /**
+             * Whether to honour .gitignore files and skip .git directories. Defaults to true.
+             */
+            readonly gitignore?: boolean;
+            /**
+             * Whether to follow symlinks that stay inside the token's directory. Defaults to false.
+             */
+            readonly followSymlinks?: boolean;
+        }
+
         interface ReadDirToken {
             read(path: string): Promise<string>;
 
@@ -131,17 +328,30 @@ declare namespace Myco {
 
             list(path: string, options?: ListDirOptions): Promise<File[]>;
 
+            /**
+             * Matches `pattern` against paths relative to `options.cwd`. `*` stays within one
+             * path segment and `**` crosses any number of them. Names are relative paths.
+             *
+             * Matches are streamed as the tree is walked, depth first in file name order.
+             * Breaking out of the loop stops the walk.
+             */
+            glob(pattern: string, options?: GlobOptions): AsyncIterableIterator<File>;
+
+            hash(path: string, algorithm?: Crypto.DigestAlgorithm): Promise<string>;
+
             sync: {
                 read(path: string): string;
                 read<T extends 'utf-8' | 'raw'>(path: string, encoding: T): T extends 'raw' ? Uint8Array : string;
                 read(path: string, encoding: 'utf-8' | 'raw'): string | Uint8Array;
                 stat(path: string): Stats | null;
                 list(path: string, options?: ListDirOptions): File[];
+                glob(pattern: string, options?: GlobOptions): File[];
+                hash(path: string, algorithm?: Crypto.DigestAlgorithm): string;
             }
         }
 
         interface WriteDirToken {
-            write(path: string, contents: string | Uint8Array): Promise<void>;
+            write(path: string, contents: string | Uint8Array, options?: WriteOptions): Promise<void>;
 
             remove(path: string): Promise<void>;
 
@@ -151,21 +361,37 @@ declare namespace Myco {
 
             rmdirRecursive(path: string): Promise<void>;
 
+            /**
+             * Waits for an exclusive advisory lock on a file. The lock is held on a
+             * `<name>.lock` file next to it, created if needed, so atomic writes keep it.
+             * Only directory tokens can lock, since the lock file must be granted too.
+             */
+            lock(path: string): Promise<FileLock>;
+
+            /**
+             * Takes the lock if it is free, or returns null if another process holds it.
+             */
+            tryLock(path: string): Promise<FileLock | null>;
+
             sync: {
-                write(path: string, contents: string | Uint8Array): void;
+                write(path: string, contents: string | Uint8Array, options?: WriteOptions): void;
                 remove(path: string): void;
                 mkdirp(path: string): void;
                 rmdir(path: string): void;
+                lock(path: string): FileLock;
+                tryLock(path: string): FileLock | null;
             }
         }
 
         interface ExecDirToken {
-            exec(path: string, args?: readonly string[]): Promise<ExecResult>;
+            exec(path: string, args?: readonly string[], options?: ExecOptions): Promise<ExecResult>;
+
+            spawn(path: string, args?: readonly string[], options?: SpawnOptions): Child;
 
             stat(path: string): Promise<Stats | null>;
 
             sync: {
-                exec(path: string, args?: readonly string[]): ExecResult;
+                exec(path: string, args?: readonly string[], options?: ExecOptions): ExecResult;
                 stat(path: string): Stats | null;
             }
         }
@@ -189,6 +415,171 @@ declare namespace Myco {
 
             stderr(encoding: 'utf-8' | 'raw'): string | Uint8Array;
         }
+
+        type Stdio = 'piped' | 'inherit' | 'null';
+
+        type Signal = 'SIGTERM' | 'SIGKILL' | 'SIGINT' | 'SIGHUP' | 'SIGQUIT' | 'SIGUSR1' | 'SIGUSR2' | 'SIGSTOP' | 'SIGCONT';
+
+        type DirToken = ReadDirToken | WriteDirToken | ExecDirToken;
+
+        interface CommandOptions {
+            /**
+             * The child's entire environment. Children never inherit the parent's
+             * environment, so this defaults to an empty one.
+             */
+            readonly env?: Readonly<Record<string, string>>;
+            /**
+             * This process's environment variables to pass through to the child, such as
+             * `PATH`, one env token each. Variables in `env` take precedence.
+             */
+            readonly inheritEnv?: readonly Env.Token[];
+            /**
+             * The directory the child runs in. Defaults to the current working directory.
+             */
+            readonly cwd?: DirToken;
+            /**
+             * Confine the child to these tokens (Linux only). With `grant` set, the child
+             * can only touch the granted files and directories plus the system paths
+             * needed to run a program, can read but not write `/etc`, `/proc` and `/tmp`,
+             * and cannot open network or Unix domain sockets unless a fetch token is
+             * granted. Fails rather than running unconfined if the kernel lacks Landlock
+             * support, or if sockets cannot be filtered on this architecture.
+             */
+            readonly grant?: readonly object[];
+        }
+
+        interface ExecOptions extends CommandOptions {
+            /**
+             * Kill the child and reject if it runs longer than this many milliseconds.
+             */
+            readonly timeout?: number;
+            /**
+             * Kill the child and reject if stdout or stderr grows beyond this many bytes.
+             */
+            readonly maxOutput?: number;
+        }
+
+        interface SpawnOptions extends CommandOptions {
+            /**
+             * How each standard stream is connected. Defaults to 'piped'.
+             */
+            readonly stdin?: Stdio;
+            readonly stdout?: Stdio;
+            readonly stderr?: Stdio;
+        }
+
+        interface ChildStdin {
+            write(contents: string | Uint8Array): Promise<void>;
+
+            /**
+             * Closes the pipe so the child sees end of input.
+             */
+            close(): void;
+        }
+
+        interface ChildOutput extends AsyncIterable<Uint8Array> {
+            /**
+             * Reads the next chunk of output, or null once the stream has ended.
+             */
+            read(): Promise<Uint8Array | null>;
+
+            /**
+             * Reads the rest of the stream and decodes it as UTF-8.
+             */
+            text(): Promise<string>;
+        }
+
+        interface ChildStatus {
+            /**
+             * The exit code, or null if the child was terminated by a signal.
+             */
+            readonly exit_code: number | null;
+            readonly signal: number | null;
+        }
+
+        /**
+         * A running child process. Its native resources are freed once `wait()` has
+         * seen it exit and its piped output has been read to the end.
+         */
+        interface Child {
+            readonly pid: number;
+            readonly stdin: ChildStdin;
+            readonly stdout: ChildOutput;
+            readonly stderr: ChildOutput;
+
+            kill(signal?: Signal): void;
+
+            wait(): Promise<ChildStatus>;
+        }
+    }
+
+    namespace Workers {
+        type Token =
+            | Files.ReadToken
+            | Files.WriteToken
+            | Files.ReadWriteToken
+            | Files.ExecToken
+            | Files.ReadDirToken
+            | Files.WriteDirToken
+            | Files.ReadWriteDirToken
+            | Files.ExecDirToken
+            | Http.FetchToken
+            | Http.FetchPrefixToken
+            | Env.Token;
+
+        interface SpawnOptions {
+            capabilities?: readonly Token[];
+        }
+
+        /**
+         * Messages are copied with the structured clone algorithm, so they can hold
+         * plain data, typed arrays, maps, sets and dates, but not functions or tokens.
+         */
+        interface Port {
+            postMessage(message: any): void;
+
+            /**
+             * Sets the handler for incoming messages, replacing any previous one, and
+             * returns a function that removes it. Messages that arrive while there is
+             * no handler are kept until one is set.
+             */
+            onMessage(handler: (message: any) => void): () => void;
+        }
+
+        interface Worker extends Port {
+            /** Stops the worker, even in the middle of running code. It exits with 1. */
+            terminate(): void;
+
+            /**
+             * Settles with the worker's exit code. Until it does, the worker keeps this
+             * isolate running.
+             */
+            readonly exited: Promise<number>;
+        }
+
+        /**
+         * A worker stays alive while it has a message handler set. Its powerbox has no
+         * `request*` methods; `capabilities` holds the tokens it was given, rebuilt in
+         * the order they were passed to `spawn`.
+         */
+        interface Parent extends Port {
+            readonly capabilities: readonly Token[];
+        }
+    }
+
+    namespace Process {
+        type Signal = 'SIGINT' | 'SIGTERM' | 'SIGHUP' | 'SIGQUIT' | 'SIGUSR1' | 'SIGUSR2';
+    }
+
+    namespace Crypto {
+        type DigestAlgorithm = 'sha1' | 'sha256' | 'sha512' | 'blake3' | 'SHA-1' | 'SHA-256' | 'SHA-512';
+    }
+
+    namespace Env {
+        interface Token {
+            /** The variable's current value, or undefined when it is not set. */
+            get(): string | undefined;
+        }
     }
 
     namespace Http {
@@ -222,6 +613,12 @@ declare class TextDecoder {
     decode(bytes: Uint8Array): string;
 }
 
+/**
+ * A leading string argument may contain `%s`, `%d`, `%i`, `%f`, `%j`, `%o`, `%O`
+ * and `%c` specifiers; other arguments are formatted as `Myco.inspect` does, and
+ * colored when the output is a terminal. `warn`, `error` and failed assertions
+ * write to stderr, everything else to stdout.
+ */
 declare namespace console {
     function log(...args: any[]): void;
 
@@ -231,11 +628,36 @@ declare namespace console {
 
     function info(...args: any[]): void;
 
+    /** Only shows when run with `--log-level debug` or `trace`. */
     function debug(...args: any[]): void;
 
     function trace(...args: any[]): void;
 
     function assert(condition: any, ...args: any[]): void;
+
+    function dir(value: any, options?: Myco.InspectOptions): void;
+
+    function dirxml(...args: any[]): void;
+
+    /** Prints the rows of `data` as a table, optionally only the given columns. */
+    function table(data: any, properties?: readonly string[]): void;
+
+    /** Indents everything logged until the matching `groupEnd`. */
+    function group(...label: any[]): void;
+
+    function groupCollapsed(...label: any[]): void;
+
+    function groupEnd(): void;
+
+    function count(label?: string): void;
+
+    function countReset(label?: string): void;
+
+    function time(label?: string): void;
+
+    function timeLog(label?: string, ...data: any[]): void;
+
+    function timeEnd(label?: string): void;
 }
 
 declare namespace TOML {
@@ -243,4 +665,172 @@ declare namespace TOML {
     function stringify(value: any): string;
 }
 
+declare class DOMException extends Error {
+    constructor(message?: string, name?: string);
+}
+
+/** Encodes a string of Latin-1 characters as base64. */
+declare function btoa(data: string): string;
+
+/** Decodes base64 into a string with one Latin-1 character per byte. */
+declare function atob(data: string): string;
+
+/**
+ * Deep-copies `value` with the structured clone algorithm, the same way worker
+ * messages are copied. Throws for values that cannot be cloned, such as functions.
+ */
+declare function structuredClone<T>(value: T): T;
+
+declare namespace performance {
+    /** Milliseconds since the isolate started, from a monotonic clock. */
+    function now(): number;
+
+    const timeOrigin: number;
+}
+
+interface EventInit {
+    bubbles?: boolean;
+    cancelable?: boolean;
+}
+
+declare class Event {
+    constructor(type: string, init?: EventInit);
+
+    readonly type: string;
+    readonly bubbles: boolean;
+    readonly cancelable: boolean;
+    readonly defaultPrevented: boolean;
+    readonly target: EventTarget | null;
+    readonly currentTarget: EventTarget | null;
+    readonly timeStamp: number;
+
+    preventDefault(): void;
+    stopPropagation(): void;
+    stopImmediatePropagation(): void;
+}
+
+type EventListenerOrEventListenerObject = ((event: Event) => void) | { handleEvent(event: Event): void };
+
+interface AddEventListenerOptions {
+    once?: boolean;
+    /** Removes the listener when the signal aborts. */
+    signal?: AbortSignal;
+}
+
+declare class EventTarget {
+    addEventListener(type: string, listener: EventListenerOrEventListenerObject | null, options?: boolean | AddEventListenerOptions): void;
+    removeEventListener(type: string, listener: EventListenerOrEventListenerObject | null): void;
+    /** Returns false if a listener called `preventDefault` on a cancelable event. */
+    dispatchEvent(event: Event): boolean;
+}
+
+declare class AbortSignal extends EventTarget {
+    private constructor();
+
+    readonly aborted: boolean;
+    readonly reason: any;
+    onabort: ((event: Event) => void) | null;
+
+    throwIfAborted(): void;
+
+    static abort(reason?: any): AbortSignal;
+    /** Aborts with a `TimeoutError` after `ms`. The pending timeout does not keep the process alive. */
+    static timeout(ms: number): AbortSignal;
+    /** Aborts as soon as any of `signals` does, with its reason. */
+    static any(signals: Iterable<AbortSignal>): AbortSignal;
+}
+
+declare class AbortController {
+    readonly signal: AbortSignal;
+
+    /** Aborts the signal, with an `AbortError` if no reason is given. */
+    abort(reason?: any): void;
+}
+
+type BlobPart = string | ArrayBuffer | ArrayBufferView | Blob;
+
+interface BlobPropertyBag {
+    type?: string;
+}
+
+declare class Blob {
+    constructor(parts?: readonly BlobPart[], options?: BlobPropertyBag);
+
+    readonly size: number;
+    readonly type: string;
+
+    slice(start?: number, end?: number, type?: string): Blob;
+    bytes(): Promise<Uint8Array>;
+    arrayBuffer(): Promise<ArrayBuffer>;
+    text(): Promise<string>;
+}
+
+type HeadersInit = Headers | Iterable<readonly [string, string]> | Record<string, string>;
+
+/** HTTP headers, with case-insensitive names. Iteration yields lowercased names in sorted order. */
+declare class Headers implements Iterable<[string, string]> {
+    constructor(init?: HeadersInit);
+
+    append(name: string, value: string): void;
+    set(name: string, value: string): void;
+    /** All values for `name`, joined with `, `. */
+    get(name: string): string | null;
+    getSetCookie(): string[];
+    has(name: string): boolean;
+    delete(name: string): void;
+    forEach(callback: (value: string, name: string, headers: Headers) => void, thisArg?: any): void;
+    entries(): IterableIterator<[string, string]>;
+    keys(): IterableIterator<string>;
+    values(): IterableIterator<string>;
+    [Symbol.iterator](): IterableIterator<[string, string]>;
+}
+
+type URLSearchParamsInit = URLSearchParams | string | Iterable<readonly [string, string]> | Record<string, string>;
+
+declare class URLSearchParams implements Iterable<[string, string]> {
+    constructor(init?: URLSearchParamsInit);
+
+    readonly size: number;
+
+    append(name: string, value: string): void;
+    delete(name: string, value?: string): void;
+    get(name: string): string | null;
+    getAll(name: string): string[];
+    has(name: string, value?: string): boolean;
+    set(name: string, value: string): void;
+    sort(): void;
+    forEach(callback: (value: string, name: string, params: URLSearchParams) => void, thisArg?: any): void;
+    entries(): IterableIterator<[string, string]>;
+    keys(): IterableIterator<string>;
+    values(): IterableIterator<string>;
+    [Symbol.iterator](): IterableIterator<[string, string]>;
+    /** Serializes as `application/x-www-form-urlencoded`, without a leading `?`. */
+    toString(): string;
+}
+
+/** A WHATWG URL. Throws a TypeError for input that cannot be parsed. */
+declare class URL {
+    constructor(url: string | URL, base?: string | URL);
+
+    static canParse(url: string | URL, base?: string | URL): boolean;
+    static parse(url: string | URL, base?: string | URL): URL | null;
+
+    href: string;
+    readonly origin: string;
+    protocol: string;
+    username: string;
+    password: string;
+    host: string;
+    hostname: string;
+    port: string;
+    pathname: string;
+    search: string;
+    hash: string;
+    /** Live view of the query: changing it updates `search`, and the other way round. */
+    readonly searchParams: URLSearchParams;
+
+    toString(): string;
+    toJSON(): string;
+}
+
 declare function setTimeout(callback: () => void, delay: number): void;
@@ -1,63 +1,572 @@
 // Simple V8-compatible runtime for Myco
 // This will be expanded as we migrate the ops
 
-// The completion value of this script is the factory function below. Rust evaluates
-// the script, takes that function, and calls it with the MycoOps object and the
-// partially-built Myco object. The powerbox it returns is held by Rust and handed
-// directly to the user module's default export - it never touches globalThis.
-(function (MycoOps: MycoOps, existingMyco: any) {
-    if (!MycoOps) {
-        throw new Error("MycoOps was not provided to the Myco runtime factory");
-    }
-
-    // Wrap each MycoOps function in a try/catch and print the stack trace
-    for (const key in MycoOps.async) {
-        if (typeof MycoOps.async[key as keyof typeof MycoOps.async] === 'function') {
-            const originalFn = MycoOps.async[key as keyof typeof MycoOps.async] as Function;
-            const newFn: Function = async function(...args: any[]) {
-                try {
-                    return await originalFn(...args);
-                } catch (e: any) {
-                    let errorMessage = e.toString();
-                    if (errorMessage.includes("Error: ")) {
-                        errorMessage = errorMessage.slice(7);
-                    }
-                    const error = new Error(errorMessage);
-                    // Omit this frame from the stack trace
-                    error.stack = error.stack?.replace(/ +at async <internal> [^\n]*\n/, '');
-                    throw error;
+// The completion value of this script is the factory function at the bottom. The
+// build evaluates the script into the startup snapshot, which installs the ambient
+// globals and keeps the factory as snapshot data. At startup Rust calls the factory
+// with the MycoOps object and the partially-built Myco object. The powerbox it
+// returns is held by Rust and handed directly to the user module's default export -
+// it never touches globalThis. Neither does any runtime plumbing: the only things
+// installed on globalThis are the public ambient APIs (console, TextEncoder, URL, ...).
+(function () {
+    // Assigned by the factory. Everything above the factory runs while the startup
+    // snapshot is built, before any ops exist, so it may only call ops lazily.
+    let MycoOps: MycoOps;
+
+    // Value formatting for the console and `Myco.inspect`, modelled on Node's
+    // util.inspect: depth-limited, cycle-aware, and optionally colored.
+    const customInspect = Symbol.for('myco.inspect.custom');
+
+    interface InspectContext {
+        depth: number;
+        colors: boolean;
+        breakLength: number;
+        maxArrayLength: number;
+        maxStringLength: number;
+        indentation: number;
+        // Level of the object formatted most recently, to tell how deeply the
+        // one being finished nests
+        currentDepth: number;
+        // Objects currently being formatted, to spot cycles
+        seen: object[];
+        circular: Map<object, number>;
+    }
+
+    function inspectContext(options: Myco.InspectOptions | undefined): InspectContext {
+        return {
+            depth: options?.depth === null ? Infinity : options?.depth ?? 2,
+            colors: options?.colors ?? false,
+            breakLength: options?.breakLength ?? 80,
+            maxArrayLength: options?.maxArrayLength ?? 100,
+            maxStringLength: options?.maxStringLength ?? 10000,
+            indentation: 0,
+            currentDepth: 0,
+            seen: [],
+            circular: new Map(),
+        };
+    }
+
+    function inspect(value: any, options?: Myco.InspectOptions): string {
+        return formatValue(inspectContext(options), value, 0);
+    }
+    inspect.custom = customInspect;
+
+    // ANSI codes, as [open, close]
+    const styles = {
+        special: [36, 39],
+        number: [33, 39],
+        bigint: [33, 39],
+        boolean: [33, 39],
+        undefined: [90, 39],
+        null: [1, 22],
+        string: [32, 39],
+        symbol: [32, 39],
+        date: [35, 39],
+        regexp: [31, 39],
+    } as const;
+
+    function stylize(ctx: InspectContext, text: string, style: keyof typeof styles): string {
+        if (!ctx.colors) {
+            return text;
+        }
+        const [open, close] = styles[style];
+        return `\x1b[${open}m${text}\x1b[${close}m`;
+    }
+
+    function visibleLength(text: string): number {
+        return text.replace(/\x1b\[\d+m/g, '').length;
+    }
+
+    const stringEscapes: Record<string, string> = {
+        '\n': '\\n',
+        '\t': '\\t',
+        '\r': '\\r',
+        '\b': '\\b',
+        '\f': '\\f',
+        '\v': '\\v',
+        '\\': '\\\\',
+    };
+
+    function quoteString(text: string): string {
+        // Prefer single quotes, then whichever quote needs no escaping
+        const quote = !text.includes("'") ? "'"
+            : !text.includes('"') ? '"'
+            : !text.includes('`') && !text.includes('${') ? '`'
+            : "'";
+        let escaped = text.replace(/[\x00-\x1f\x7f\\]/g, (c) =>
+            stringEscapes[c] ?? `\\x${c.charCodeAt(0).toString(16).padStart(2, '0')}`);
+        if (quote === "'") {
+            escaped = escaped.replace(/'/g, "\\'");
+        }
+        return quote + escaped + quote;
+    }
+
+    function formatNumber(value: number): string {
+        return Object.is(value, -0) ? '-0' : String(value);
+    }
+
+    function formatPrimitive(ctx: InspectContext, value: any): string {
+        switch (typeof value) {
+            case 'string': {
+                let text = value;
+                let trailer = '';
+                if (text.length > ctx.maxStringLength) {
+                    const remaining = text.length - ctx.maxStringLength;
+                    text = text.slice(0, ctx.maxStringLength);
+                    trailer = `... ${remaining} more character${remaining > 1 ? 's' : ''}`;
                 }
-            };
-            Object.defineProperty(newFn, 'name', {
-                value: 'async <internal>',
-                writable: false,
-                configurable: false,
+                return stylize(ctx, quoteString(text), 'string') + trailer;
+            }
+            case 'number':
+                return stylize(ctx, formatNumber(value), 'number');
+            case 'bigint':
+                return stylize(ctx, `${value}n`, 'bigint');
+            case 'boolean':
+                return stylize(ctx, String(value), 'boolean');
+            case 'undefined':
+                return stylize(ctx, 'undefined', 'undefined');
+            case 'symbol':
+                return stylize(ctx, value.toString(), 'symbol');
+            default:
+                return stylize(ctx, 'null', 'null');
+        }
+    }
+
+    function formatKey(ctx: InspectContext, key: string | symbol): string {
+        if (typeof key === 'symbol') {
+            return `[${stylize(ctx, key.toString(), 'symbol')}]`;
+        }
+        if (/^[a-zA-Z_$][a-zA-Z_$0-9]*$/.test(key)) {
+            return key;
+        }
+        return stylize(ctx, quoteString(key), 'string');
+    }
+
+    function formatValue(ctx: InspectContext, value: any, level: number): string {
+        if (value === null || (typeof value !== 'object' && typeof value !== 'function')) {
+            return formatPrimitive(ctx, value);
+        }
+        if (ctx.seen.includes(value)) {
+            let index = ctx.circular.get(value);
+            if (index === undefined) {
+                index = ctx.circular.size + 1;
+                ctx.circular.set(value, index);
+            }
+            return stylize(ctx, `[Circular *${index}]`, 'special');
+        }
+        if (typeof value[customInspect] === 'function') {
+            const custom = value[customInspect]({
+                depth: ctx.depth - level,
+                colors: ctx.colors,
+                breakLength: ctx.breakLength,
+                maxArrayLength: ctx.maxArrayLength,
+                maxStringLength: ctx.maxStringLength,
             });
-            (MycoOps.async[key as keyof typeof MycoOps.async] as any) = newFn;
+            if (typeof custom === 'string') {
+                return custom.replace(/\n/g, `\n${' '.repeat(ctx.indentation)}`);
+            }
+            if (custom !== value) {
+                return formatValue(ctx, custom, level);
+            }
         }
+        return formatObject(ctx, value, level);
     }
 
-    // Helper function to format multiple arguments like console does
-    function formatArgs(...args: any[]): string {
-        return args.map(arg => {
-            if (typeof arg === 'string') {
-                return arg;
-            } else if (typeof arg === 'number' || typeof arg === 'boolean') {
-                return String(arg);
-            } else if (arg === null) {
-                return 'null';
-            } else if (arg === undefined) {
-                return 'undefined';
-            } else {
-                try {
-                    return JSON.stringify(arg);
-                } catch {
-                    return '[object Object]';
+    function constructorName(value: object): string | null {
+        let proto = Object.getPrototypeOf(value);
+        while (proto !== null) {
+            const descriptor = Object.getOwnPropertyDescriptor(proto, 'constructor');
+            if (typeof descriptor?.value === 'function' && descriptor.value.name !== '') {
+                return descriptor.value.name;
+            }
+            proto = Object.getPrototypeOf(proto);
+        }
+        return null;
+    }
+
+    // The name shown before an object's braces: its class, any differing
+    // Symbol.toStringTag, and whether it has no prototype
+    function objectPrefix(value: object, name: string | null, fallback: string, size?: number): string {
+        const sizeText = size === undefined ? '' : `(${size})`;
+        const tag = (value as any)[Symbol.toStringTag];
+        const tagText = typeof tag === 'string' && tag !== '' && tag !== (name ?? fallback) ? ` [${tag}]` : '';
+        if (name === null) {
+            return `[${fallback}${sizeText}: null prototype]${tagText} `;
+        }
+        return `${name}${sizeText}${tagText} `;
+    }
+
+    function formatFunction(value: Function, name: string | null): string {
+        const source = Function.prototype.toString.call(value);
+        if (source.startsWith('class') && /^class\b/.test(source)) {
+            const superclass = Object.getPrototypeOf(value);
+            const extendsText = superclass?.name ? ` extends ${superclass.name}` : '';
+            return `[class ${value.name || '(anonymous)'}${extendsText}]`;
+        }
+        const type = (value as any)[Symbol.toStringTag] ?? 'Function';
+        const base = `[${type}: ${value.name || '(anonymous)'}]`;
+        return name === null ? `${base.slice(0, -1)} (null prototype)]` : base;
+    }
+
+    function formatError(ctx: InspectContext, value: Error): string {
+        let stack = typeof value.stack === 'string' && value.stack !== ''
+            ? value.stack
+            : Error.prototype.toString.call(value);
+        // Keep the name visible even if the stack was captured under another one
+        const name = value.name ?? 'Error';
+        if (!stack.includes(name)) {
+            stack = `${name}: ${stack}`;
+        }
+        return stack.replace(/\n/g, `\n${' '.repeat(ctx.indentation)}`);
+    }
+
+    function enumerableKeys(value: object): (string | symbol)[] {
+        const symbols = Object.getOwnPropertySymbols(value)
+            .filter((symbol) => Object.prototype.propertyIsEnumerable.call(value, symbol));
+        return [...Object.keys(value), ...symbols];
+    }
+
+    function isIndex(key: string | symbol): boolean {
+        return typeof key === 'string' && /^(0|[1-9][0-9]*)$/.test(key);
+    }
+
+    function formatProperty(ctx: InspectContext, value: any, key: string | symbol, level: number): string {
+        const descriptor = Object.getOwnPropertyDescriptor(value, key);
+        let formatted: string;
+        if (descriptor === undefined || 'value' in descriptor) {
+            try {
+                formatted = formatValue(ctx, value[key], level);
+            } catch {
+                // Module namespaces throw for bindings that are not initialized yet
+                formatted = stylize(ctx, '<uninitialized>', 'special');
+            }
+        } else if (descriptor.get && descriptor.set) {
+            formatted = stylize(ctx, '[Getter/Setter]', 'special');
+        } else if (descriptor.get) {
+            formatted = stylize(ctx, '[Getter]', 'special');
+        } else {
+            formatted = stylize(ctx, '[Setter]', 'special');
+        }
+        return `${formatKey(ctx, key)}: ${formatted}`;
+    }
+
+    function formatListEntries(ctx: InspectContext, value: ArrayLike<any>, level: number): string[] {
+        const output: string[] = [];
+        const length = Math.min(value.length, ctx.maxArrayLength);
+        let holes = 0;
+        const flushHoles = () => {
+            if (holes > 0) {
+                output.push(stylize(ctx, `<${holes} empty item${holes > 1 ? 's' : ''}>`, 'undefined'));
+                holes = 0;
+            }
+        };
+        for (let i = 0; i < length; i++) {
+            if (!Object.prototype.hasOwnProperty.call(value, i)) {
+                holes++;
+                continue;
+            }
+            flushHoles();
+            output.push(formatValue(ctx, value[i], level));
+        }
+        flushHoles();
+        if (value.length > length) {
+            const remaining = value.length - length;
+            output.push(`... ${remaining} more item${remaining > 1 ? 's' : ''}`);
+        }
+        return output;
+    }
+
+    function formatIterableEntries(ctx: InspectContext, entries: Iterable<string>, size: number): string[] {
+        const output: string[] = [];
+        for (const entry of entries) {
+            if (output.length === ctx.maxArrayLength) {
+                const remaining = size - output.length;
+                output.push(`... ${remaining} more item${remaining > 1 ? 's' : ''}`);
+                break;
+            }
+            output.push(entry);
+        }
+        return output;
+    }
+
+    function formatBytes(bytes: Uint8Array): string {
+        const shown = Array.from(bytes.subarray(0, 50), (byte) => byte.toString(16).padStart(2, '0'));
+        const more = bytes.length > 50 ? ` ... ${bytes.length - 50} more byte${bytes.length > 51 ? 's' : ''}` : '';
+        return `<${shown.join(' ')}${more}>`;
+    }
+
+    function formatObject(ctx: InspectContext, value: any, level: number): string {
+        const name = constructorName(value);
+        let keys = enumerableKeys(value);
+        // What the object is shown as, and as past the depth limit
+        let prefix = objectPrefix(value, name, 'Object');
+        let depthLabel = name ?? 'Object';
+        let braces = ['{', '}'];
+        let hasEntries = false;
+        let entries = (): string[] => [];
+        // The elements of an array or typed array
+        let list: ArrayLike<any> | undefined;
+
+        if (typeof value === 'function') {
+            const base = stylize(ctx, formatFunction(value, name), 'special');
+            if (keys.length === 0) {
+                return base;
+            }
+            prefix = `${base} `;
+            depthLabel = formatFunction(value, name).slice(1, -1);
+        } else if (Array.isArray(value)) {
+            keys = keys.filter((key) => !isIndex(key));
+            prefix = name === 'Array' ? '' : objectPrefix(value, name, 'Array', value.length);
+            depthLabel = 'Array';
+            braces = ['[', ']'];
+            hasEntries = value.length > 0;
+            list = value;
+            entries = () => formatListEntries(ctx, value, level + 1);
+        } else if (ArrayBuffer.isView(value) && !(value instanceof DataView)) {
+            list = value as unknown as ArrayLike<any>;
+            keys = keys.filter((key) => !isIndex(key));
+            prefix = objectPrefix(value, name, 'TypedArray', list.length);
+            depthLabel = name ?? 'TypedArray';
+            braces = ['[', ']'];
+            hasEntries = list.length > 0;
+            entries = () => formatListEntries(ctx, list!, level + 1);
+        } else if (value instanceof Map) {
+            prefix = objectPrefix(value, name, 'Map', value.size);
+            hasEntries = value.size > 0;
+            entries = () => formatIterableEntries(ctx, (function* () {
+                for (const [key, entry] of value) {
+                    yield `${formatValue(ctx, key, level + 1)} => ${formatValue(ctx, entry, level + 1)}`;
+                }
+            })(), value.size);
+        } else if (value instanceof Set) {
+            prefix = objectPrefix(value, name, 'Set', value.size);
+            hasEntries = value.size > 0;
+            entries = () => formatIterableEntries(ctx, (function* () {
+                for (const entry of value) {
+                    yield formatValue(ctx, entry, level + 1);
+                }
+            })(), value.size);
+        } else if (value instanceof WeakMap || value instanceof WeakSet) {
+            hasEntries = true;
+            entries = () => [stylize(ctx, '<items unknown>', 'special')];
+        } else if (value instanceof Promise) {
+            hasEntries = true;
+            entries = () => {
+                const { state, result } = MycoOps.sync.promise_state({ promise: value });
+                if (state === 'pending') {
+                    return [stylize(ctx, '<pending>', 'special')];
                 }
+                const formatted = formatValue(ctx, result, level + 1);
+                return [state === 'rejected' ? `${stylize(ctx, '<rejected>', 'special')} ${formatted}` : formatted];
+            };
+        } else if (value instanceof Error) {
+            keys = keys.filter((key) => key !== 'stack' && key !== 'message');
+            const base = formatError(ctx, value);
+            const cause = Object.prototype.hasOwnProperty.call(value, 'cause') && !keys.includes('cause');
+            if (keys.length === 0 && !cause) {
+                return base;
+            }
+            prefix = `${base} `;
+            if (cause) {
+                hasEntries = true;
+                entries = () => [`[cause]: ${formatValue(ctx, value.cause, level + 1)}`];
+            }
+        } else if (value instanceof Date) {
+            const base = stylize(ctx, Number.isNaN(value.getTime()) ? 'Invalid Date' : value.toISOString(), 'date');
+            if (keys.length === 0) {
+                return base;
+            }
+            prefix = `${base} `;
+        } else if (value instanceof RegExp) {
+            const base = stylize(ctx, RegExp.prototype.toString.call(value), 'regexp');
+            keys = keys.filter((key) => key !== 'lastIndex');
+            if (keys.length === 0) {
+                return base;
             }
-        }).join(' ');
+            prefix = `${base} `;
+        } else if (value instanceof ArrayBuffer) {
+            prefix = objectPrefix(value, name, 'ArrayBuffer');
+            hasEntries = true;
+            entries = () => [
+                `[Uint8Contents]: ${formatBytes(new Uint8Array(value))}`,
+                `byteLength: ${formatPrimitive(ctx, value.byteLength)}`,
+            ];
+        } else if (value instanceof Number || value instanceof String || value instanceof Boolean
+            || value instanceof Symbol || value instanceof BigInt) {
+            const primitive = value.valueOf();
+            const type = typeof primitive === 'bigint' ? 'BigInt' : (typeof primitive)[0].toUpperCase() + (typeof primitive).slice(1);
+            if (typeof primitive === 'string') {
+                keys = keys.filter((key) => !isIndex(key));
+            }
+            const base = `[${type}: ${formatPrimitive(ctx, primitive)}]`;
+            if (keys.length === 0) {
+                return base;
+            }
+            prefix = `${base} `;
+        } else if (name === null && Object.prototype.toString.call(value) === '[object Module]') {
+            prefix = '[Module: null prototype] ';
+        } else if (prefix === 'Object ') {
+            prefix = '';
+        }
+
+        if (!hasEntries && keys.length === 0) {
+            return `${prefix}${braces[0]}${braces[1]}`;
+        }
+        if (level > ctx.depth) {
+            return stylize(ctx, `[${depthLabel}]`, 'special');
+        }
+
+        ctx.seen.push(value);
+        ctx.currentDepth = level;
+        ctx.indentation += 2;
+        let output = [...entries(), ...keys.map((key) => formatProperty(ctx, value, key, level + 1))];
+        ctx.indentation -= 2;
+        ctx.seen.pop();
+
+        // Rows of grouped entries are never joined back onto one line
+        const entryCount = output.length;
+        if (list !== undefined && entryCount > 6) {
+            output = groupListEntries(ctx, output, list);
+        }
+        let result = joinEntries(ctx, output, `${prefix}${braces[0]}`, braces[1], level, output.length !== entryCount);
+        const ref = ctx.circular.get(value);
+        if (ref !== undefined) {
+            result = `${stylize(ctx, `<ref *${ref}>`, 'special')} ${result}`;
+        }
+        return result;
+    }
+
+    // Lays out a list's entries in aligned columns, as rows of a few entries each,
+    // when they are short enough for that to read better than one per line
+    function groupListEntries(ctx: InspectContext, output: string[], list: ArrayLike<any>): string[] {
+        const separatorSpace = 2;
+        // A trailing "... more items" is not part of the grid
+        const outputLength = output.length > 0 && output[output.length - 1].startsWith('... ') ? output.length - 1 : output.length;
+        const dataLength = output.slice(0, outputLength).map(visibleLength);
+        const totalLength = dataLength.reduce((total, length) => total + length + separatorSpace, 0);
+        const maxLength = Math.max(0, ...dataLength);
+        const actualMax = maxLength + separatorSpace;
+        if (actualMax * 3 + ctx.indentation >= ctx.breakLength || (totalLength / actualMax <= 5 && maxLength > 6)) {
+            return output;
+        }
+
+        const averageBias = Math.sqrt(actualMax - totalLength / output.length);
+        const biasedMax = Math.max(actualMax - 3 - averageBias, 1);
+        const columns = Math.min(
+            Math.round(Math.sqrt(2.5 * biasedMax * outputLength) / biasedMax),
+            Math.floor((ctx.breakLength - ctx.indentation) / actualMax),
+            12,
+        );
+        if (columns <= 1) {
+            return output;
+        }
+
+        const columnWidths: number[] = [];
+        for (let i = 0; i < columns; i++) {
+            let width = 0;
+            for (let j = i; j < outputLength; j += columns) {
+                width = Math.max(width, dataLength[j]);
+            }
+            columnWidths.push(width + separatorSpace);
+        }
+        // Numbers are right-aligned, anything else left-aligned
+        let alignRight = true;
+        for (let i = 0; i < Math.min(list.length, outputLength); i++) {
+            if (typeof list[i] !== 'number' && typeof list[i] !== 'bigint') {
+                alignRight = false;
+                break;
+            }
+        }
+
+        const rows: string[] = [];
+        for (let i = 0; i < outputLength; i += columns) {
+            const end = Math.min(i + columns, outputLength);
+            let row = '';
+            for (let j = i; j < end; j++) {
+                const last = j === end - 1;
+                const text = last ? output[j] : `${output[j]}, `;
+                const width = columnWidths[j - i] + output[j].length - dataLength[j] - (last ? separatorSpace : 0);
+                row += alignRight ? text.padStart(width) : last ? text : text.padEnd(width);
+            }
+            rows.push(row);
+        }
+        if (outputLength < output.length) {
+            rows.push(output[outputLength]);
+        }
+        return rows;
+    }
+
+    // Puts entries on one line when they fit and the object does not nest too
+    // deeply, otherwise one per line
+    function joinEntries(ctx: InspectContext, output: string[], open: string, close: string, level: number, grouped: boolean): string {
+        if (ctx.currentDepth - level < 3 && !grouped) {
+            const start = output.length + ctx.indentation + visibleLength(open) + 10;
+            const length = output.reduce((total, entry) => total + visibleLength(entry), output.length + start);
+            const joined = output.join(', ');
+            if (length <= ctx.breakLength && !open.includes('\n') && !joined.includes('\n')) {
+                return `${open} ${joined} ${close}`;
+            }
+        }
+        const indent = ' '.repeat(ctx.indentation);
+        return `${open}\n${indent}  ${output.join(`,\n${indent}  `)}\n${indent}${close}`;
+    }
+
+    // Formats console arguments: printf-style specifiers in a leading string,
+    // then every other argument, inspected unless it is a string
+    function formatArgs(args: any[], options?: Myco.InspectOptions): string {
+        const pieces: string[] = [];
+        let index = 0;
+        if (typeof args[0] === 'string' && args.length > 1) {
+            index = 1;
+            pieces.push(args[0].replace(/%([sdifjoOc%])/g, (match: string, specifier: string) => {
+                if (specifier === '%') {
+                    return '%';
+                }
+                if (index >= args.length) {
+                    return match;
+                }
+                const arg = args[index++];
+                switch (specifier) {
+                    case 's':
+                        if (typeof arg === 'string') {
+                            return arg;
+                        }
+                        return (typeof arg === 'object' && arg !== null) || typeof arg === 'function'
+                            ? inspect(arg, { ...options, depth: 0 })
+                            : formatPrimitive(inspectContext(undefined), arg);
+                    case 'd':
+                    case 'i':
+                        if (typeof arg === 'bigint') {
+                            return `${arg}n`;
+                        }
+                        if (typeof arg === 'symbol') {
+                            return 'NaN';
+                        }
+                        return formatNumber(specifier === 'd' ? Number(arg) : parseInt(arg));
+                    case 'f':
+                        return typeof arg === 'symbol' ? 'NaN' : formatNumber(parseFloat(arg));
+                    case 'j':
+                        try {
+                            return JSON.stringify(arg);
+                        } catch {
+                            return '[Circular]';
+                        }
+                    case 'o':
+                        return inspect(arg, { ...options, depth: 4 });
+                    case 'O':
+                        return inspect(arg, options);
+                    default:
+                        // %c takes CSS, which a terminal has no use for
+                        return '';
+                }
+            }));
+        }
+        for (; index < args.length; index++) {
+            const arg = args[index];
+            pieces.push(typeof arg === 'string' ? arg : inspect(arg, options));
+        }
+        return pieces.join(' ');
     }
-    
 
     function maybeDecode<T extends 'utf-8' | 'raw'>(bytes: Uint8Array, encoding: 'utf-8' | 'raw' = 'utf-8'): T extends 'raw' ? Uint8Array : string {
         if (encoding === 'utf-8') {
@@ -112,6 +621,265 @@
         )
     }
 
+    function globArgs(token: string, pattern: string, options: Myco.Files.GlobOptions | undefined) {
+        return {
+            token,
+            path: options?.cwd ?? '.',
+            pattern,
+            ignore: options?.ignore,
+            gitignore: options?.gitignore,
+            follow_symlinks: options?.followSymlinks,
+        };
+    }
+
+    // Streams a native walk in batches. The walk only starts once iteration does,
+    // and stops as soon as iteration ends, early or not.
+    async function* globFiles(args: GlobArgs): AsyncIterableIterator<Myco.Files.File> {
+        const walk = MycoOps.sync.glob_open(args);
+        try {
+            let batch;
+            while ((batch = await MycoOps.async.glob_next({ token: walk })).length > 0) {
+                yield* batch;
+            }
+        } finally {
+            MycoOps.sync.glob_close({ token: walk });
+        }
+    }
+
+    // Token objects handed to user code never expose their native token strings.
+    // Ops that take more than one token (archive) look them up here instead.
+    const readTokens = new WeakMap<object, string>();
+    const writeTokens = new WeakMap<object, string>();
+    const execTokens = new WeakMap<object, string>();
+    const netTokens = new WeakMap<object, string>();
+    const envTokens = new WeakMap<object, string>();
+
+    function bindToken<T extends object>(tokens: WeakMap<object, string>, token: string, tokenObject: T): T {
+        tokens.set(tokenObject, token);
+        return tokenObject;
+    }
+
+    function bindTokens<T extends object>(readToken: object, writeToken: object, tokenObject: T): T {
+        readTokens.set(tokenObject, readTokens.get(readToken)!);
+        writeTokens.set(tokenObject, writeTokens.get(writeToken)!);
+        return tokenObject;
+    }
+
+    function nativeToken(tokens: WeakMap<object, string>, tokenObject: object, kind: string): string {
+        const token = tokens.get(tokenObject);
+        if (token === undefined) {
+            throw new TypeError(`Expected a ${kind} token`);
+        }
+        return token;
+    }
+
+    function nativeDirToken(tokenObject: object): string {
+        // Any directory token will do; Rust rejects file tokens
+        const token = readTokens.get(tokenObject) ?? writeTokens.get(tokenObject) ?? execTokens.get(tokenObject);
+        if (token === undefined) {
+            throw new TypeError('Expected a directory token');
+        }
+        return token;
+    }
+
+    // A read-write token stands for two native tokens, and both are granted
+    function grantTokens(tokenObjects: readonly object[]): string[] {
+        const granted: string[] = [];
+        for (const tokenObject of tokenObjects) {
+            const tokens = [readTokens, writeTokens, execTokens, netTokens, envTokens]
+                .map(tokens => tokens.get(tokenObject))
+                .filter((token): token is string => token !== undefined);
+            if (tokens.length === 0) {
+                throw new TypeError('Expected a token to grant');
+            }
+            granted.push(...tokens);
+        }
+        return granted;
+    }
+
+    function zipArgs(source: object, path: string, destination: object) {
+        return {
+            src_token: nativeToken(readTokens, source, 'read directory'),
+            path,
+            dst_token: nativeToken(writeTokens, destination, 'write'),
+        };
+    }
+
+    function unzipArgs(source: object, destination: object, path: string) {
+        return {
+            src_token: nativeToken(readTokens, source, 'read'),
+            dst_token: nativeToken(writeTokens, destination, 'write directory'),
+            path,
+        };
+    }
+
+    // A child only inherits the variables the caller holds env tokens for
+    function inheritedEnvTokens(tokenObjects: readonly Myco.Env.Token[]): string[] {
+        return tokenObjects.map(tokenObject => {
+            const token = envTokens.get(tokenObject);
+            if (token === undefined) {
+                throw new TypeError('Expected an env token in inheritEnv');
+            }
+            return token;
+        });
+    }
+
+    function spawnArgs(token: string, path: string | undefined, args: readonly string[], options: Myco.Files.SpawnOptions | undefined) {
+        return {
+            token,
+            path,
+            args,
+            env: options?.env,
+            inherit_env: options?.inheritEnv && inheritedEnvTokens(options.inheritEnv),
+            cwd: options?.cwd && nativeDirToken(options.cwd),
+            grant: options?.grant && grantTokens(options.grant),
+            stdin: options?.stdin,
+            stdout: options?.stdout,
+            stderr: options?.stderr,
+        };
+    }
+
+    function execOptions(options: Myco.Files.ExecOptions | undefined) {
+        return {
+            env: options?.env,
+            inherit_env: options?.inheritEnv && inheritedEnvTokens(options.inheritEnv),
+            cwd: options?.cwd && nativeDirToken(options.cwd),
+            grant: options?.grant && grantTokens(options.grant),
+            timeout: options?.timeout,
+            max_output: options?.maxOutput,
+        };
+    }
+
+    // A spawned child stays registered natively until it has exited and each of its
+    // piped outputs has been read to the end, so nothing it wrote is lost.
+    class ChildHandle {
+        #exited = false;
+        #released = false;
+        #open = new Set<'stdout' | 'stderr'>();
+
+        constructor(readonly token: string, options: Myco.Files.SpawnOptions | undefined) {
+            for (const stream of ['stdout', 'stderr'] as const) {
+                if ((options?.[stream] ?? 'piped') === 'piped') {
+                    this.#open.add(stream);
+                }
+            }
+        }
+
+        get released(): boolean {
+            return this.#released;
+        }
+
+        exited(): void {
+            this.#exited = true;
+            this.#release();
+        }
+
+        ended(stream: 'stdout' | 'stderr'): void {
+            this.#open.delete(stream);
+            this.#release();
+        }
+
+        #release(): void {
+            if (this.#exited && this.#open.size === 0 && !this.#released) {
+                this.#released = true;
+                MycoOps.sync.child_release({ token: this.token });
+            }
+        }
+    }
+
+    function childOutput(handle: ChildHandle, stream: 'stdout' | 'stderr'): Myco.Files.ChildOutput {
+        const output: Myco.Files.ChildOutput = {
+            async read(): Promise<Uint8Array | null> {
+                if (handle.released) return null;
+                const chunk = await MycoOps.async.child_read({ token: handle.token, stream });
+                if (chunk.length === 0) {
+                    handle.ended(stream);
+                    return null;
+                }
+                return chunk;
+            },
+            async *[Symbol.asyncIterator]() {
+                let chunk;
+                while ((chunk = await output.read()) !== null) {
+                    yield chunk;
+                }
+            },
+            async text(): Promise<string> {
+                const chunks: Uint8Array[] = [];
+                for await (const chunk of output) {
+                    chunks.push(chunk);
+                }
+                const bytes = new Uint8Array(chunks.reduce((length, chunk) => length + chunk.length, 0));
+                let offset = 0;
+                for (const chunk of chunks) {
+                    bytes.set(chunk, offset);
+                    offset += chunk.length;
+                }
+                return new TextDecoder().decode(bytes);
+            },
+        };
+        return output;
+    }
+
+    function childProcess({ token, pid }: { token: string; pid: number }, options: Myco.Files.SpawnOptions | undefined): Myco.Files.Child {
+        const handle = new ChildHandle(token, options);
+        let status: Promise<Myco.Files.ChildStatus> | undefined;
+        return {
+            pid,
+            stdin: {
+                async write(contents: string | Uint8Array): Promise<void> {
+                    return await MycoOps.async.child_write({ token, bytes: maybeEncode(contents) });
+                },
+                close(): void {
+                    if (handle.released) return;
+                    MycoOps.sync.child_close_stdin({ token });
+                },
+            },
+            stdout: childOutput(handle, 'stdout'),
+            stderr: childOutput(handle, 'stderr'),
+            kill(signal: Myco.Files.Signal = 'SIGTERM'): void {
+                // Once released the child has exited, and there is nothing to signal
+                if (handle.released) return;
+                MycoOps.sync.child_kill({ token, signal });
+            },
+            wait(): Promise<Myco.Files.ChildStatus> {
+                status ??= MycoOps.async.child_wait({ token }).then((status) => {
+                    handle.exited();
+                    return status;
+                });
+                return status;
+            },
+        };
+    }
+
+    function toHex(bytes: Uint8Array): string {
+        let hex = '';
+        for (const byte of bytes) {
+            hex += byte.toString(16).padStart(2, '0');
+        }
+        return hex;
+    }
+
+    function fileLock(lockToken: string): Myco.Files.FileLock {
+        let released = false;
+        const lock: Myco.Files.FileLock = {
+            release() {
+                if (released) return;
+                released = true;
+                MycoOps.sync.unlock_file({ token: lockToken });
+            },
+        };
+        const asyncDispose = (Symbol as any).asyncDispose;
+        if (asyncDispose) {
+            (lock as any)[asyncDispose] = async () => lock.release();
+        }
+        return lock;
+    }
+
+    function maybeFileLock(lockToken: string | null): Myco.Files.FileLock | null {
+        return lockToken === null ? null : fileLock(lockToken);
+    }
+
     // Helper function to check truthiness like JavaScript
     function isTruthy(value: any): boolean {
         if (typeof value === 'boolean') return value;
@@ -121,61 +889,237 @@
         return true; // Objects are truthy
     }
     
-    // Create console object using MycoOps
+    // Create console object using MycoOps. Every call is written at a level,
+    // which decides the stream: warnings and errors go to stderr.
+    type ConsoleLevel = 'log' | 'info' | 'debug' | 'warn' | 'error' | 'trace';
+
+    let groupIndentation = '';
+    const consoleCounts = new Map<string, number>();
+    const consoleTimers = new Map<string, number>();
+
+    // Converts a console argument to something JSON can represent, for the JSON log
+    // format. Whatever JSON has no form for is logged as its inspected string.
+    function toLogValue(value: any, seen: Set<object>): any {
+        if (typeof value === 'string' || typeof value === 'boolean' || value === null) {
+            return value;
+        }
+        if (typeof value === 'number') {
+            return Number.isFinite(value) ? value : inspect(value);
+        }
+        if (typeof value !== 'object') {
+            return inspect(value);
+        }
+        if (seen.has(value)) {
+            return '[Circular]';
+        }
+        seen.add(value);
+        try {
+            if (value instanceof Error) {
+                return { name: value.name, message: value.message, stack: value.stack ?? null };
+            }
+            if (typeof value.toJSON === 'function') {
+                return toLogValue(value.toJSON(), seen);
+            }
+            if (Array.isArray(value)) {
+                return value.map((item) => toLogValue(item, seen));
+            }
+            if (value instanceof Map || value instanceof Set || ArrayBuffer.isView(value)) {
+                return inspect(value, { breakLength: Infinity });
+            }
+            const result: Record<string, any> = {};
+            for (const key of Object.keys(value)) {
+                result[key] = toLogValue(value[key], seen);
+            }
+            return result;
+        } finally {
+            seen.delete(value);
+        }
+    }
+
+    // `args` are the values behind `message`, which the JSON log format records as
+    // well as the formatted text.
+    function consoleWrite(level: ConsoleLevel, message: string, args: any[] = []) {
+        if (MycoOps.sync.console_json({})) {
+            const logArgs = args.map((arg) => toLogValue(arg, new Set()));
+            MycoOps.sync.console_write({ level, message, args: JSON.stringify(logArgs) });
+            return;
+        }
+        if (groupIndentation !== '') {
+            message = message.replace(/^/gm, groupIndentation);
+        }
+        MycoOps.sync.console_write({ level, message });
+    }
+
+    function consoleLog(level: ConsoleLevel, args: any[]) {
+        consoleWrite(level, formatArgs(args, { colors: MycoOps.sync.console_colors({ level }) }), args);
+    }
+
+    function formatDuration(ms: number): string {
+        return ms >= 1000 ? `${(ms / 1000).toFixed(3)}s` : `${ms.toFixed(3)}ms`;
+    }
+
+    function formatTable(data: any, properties?: readonly string[]): string {
+        const cell = (value: any) => inspect(value, { depth: 1, breakLength: Infinity, maxArrayLength: 3 });
+        const isObject = (value: any) => value !== null && (typeof value === 'object' || typeof value === 'function');
+        const rows: [string, any][] = data instanceof Map
+            ? [...data].map(([key, value]) => [cell(key), value])
+            : Object.keys(data).map((key) => [key, data[key]]);
+
+        const columns: string[] = properties ? [...properties] : [];
+        let hasValues = false;
+        for (const [, row] of rows) {
+            if (!isObject(row)) {
+                hasValues = true;
+            } else if (!properties) {
+                for (const key of Object.keys(row)) {
+                    if (!columns.includes(key)) {
+                        columns.push(key);
+                    }
+                }
+            }
+        }
+
+        const header = [data instanceof Map ? '(iteration index)' : '(index)', ...columns, ...(hasValues ? ['Values'] : [])];
+        const body = rows.map(([index, row]) => [
+            index,
+            ...columns.map((column) => isObject(row) && Object.prototype.hasOwnProperty.call(row, column) ? cell(row[column]) : ''),
+            ...(hasValues ? [isObject(row) ? '' : cell(row)] : []),
+        ]);
+
+        const widths = header.map((title, i) => Math.max(title.length, ...body.map((row) => row[i].length)) + 2);
+        const rule = (left: string, middle: string, right: string) =>
+            left + widths.map((width) => '─'.repeat(width)).join(middle) + right;
+        const line = (cells: string[]) => '│' + cells.map((text, i) => {
+            const padding = widths[i] - text.length;
+            const left = Math.floor(padding / 2);
+            return ' '.repeat(left) + text + ' '.repeat(padding - left);
+        }).join('│') + '│';
+        return [
+            rule('┌', '┬', '┐'),
+            line(header),
+            rule('├', '┼', '┤'),
+            ...body.map(line),
+            rule('└', '┴', '┘'),
+        ].join('\n');
+    }
+
     const console = {
         log(...args: any[]) {
-            const message = formatArgs(...args);
-            MycoOps.sync.print({ message: message + '\n' });
+            consoleLog('log', args);
         },
-        
+
         error(...args: any[]) {
-            const message = formatArgs(...args);
-            MycoOps.sync.eprint({ message: message + '\n' });
+            consoleLog('error', args);
         },
-        
+
         warn(...args: any[]) {
-            const message = formatArgs(...args);
-            MycoOps.sync.eprint({ message: message + '\n' });
+            consoleLog('warn', args);
         },
-        
+
         info(...args: any[]) {
-            const message = formatArgs(...args);
-            MycoOps.sync.print({ message: message + '\n' });
+            consoleLog('info', args);
         },
-        
+
         debug(...args: any[]) {
-            const message = formatArgs(...args);
-            MycoOps.sync.print({ message: message + '\n' });
+            consoleLog('debug', args);
         },
-        
+
         trace(...args: any[]) {
             const stackTrace = MycoOps.sync.trace({});
-            if (args.length > 0) {
-                const message = formatArgs(...args);
-                MycoOps.sync.print({ message: message + '\n' });
-            }
-            MycoOps.sync.print({ message: stackTrace + '\n' });
+            const message = args.length > 0
+                ? `${formatArgs(args, { colors: MycoOps.sync.console_colors({ level: 'trace' }) })}\n${stackTrace}`
+                : stackTrace;
+            consoleWrite('trace', message, args);
         },
-        
+
         assert(condition: any, ...args: any[]) {
             if (!isTruthy(condition)) {
-                if (args.length > 0) {
-                    const message = formatArgs(...args);
-                    MycoOps.sync.eprint({ message: 'Assertion failed: ' + message + '\n' });
+                if (typeof args[0] === 'string') {
+                    consoleLog('error', [`Assertion failed: ${args[0]}`, ...args.slice(1)]);
+                } else if (args.length > 0) {
+                    consoleLog('error', ['Assertion failed:', ...args]);
                 } else {
-                    MycoOps.sync.eprint({ message: 'Assertion failed\n' });
+                    consoleWrite('error', 'Assertion failed');
                 }
             }
-        }
-    };
-    
-    // Set console on globalThis
-    (globalThis as any).console = console;
-    
-    // Create TextEncoder class using MycoOps
-    class TextEncoder {
-        constructor(encoding?: 'utf-8') {
-            if (encoding && encoding !== 'utf-8') {
+        },
+
+        dir(value: any, options?: Myco.InspectOptions) {
+            consoleWrite('log', inspect(value, { colors: MycoOps.sync.console_colors({ level: 'log' }), ...options }), [value]);
+        },
+
+        dirxml(...args: any[]) {
+            consoleLog('log', args);
+        },
+
+        table(data: any, properties?: readonly string[]) {
+            if (data === null || typeof data !== 'object') {
+                consoleLog('log', [data]);
+                return;
+            }
+            consoleWrite('log', formatTable(data, properties), [data]);
+        },
+
+        group(...label: any[]) {
+            if (label.length > 0) {
+                consoleLog('log', label);
+            }
+            groupIndentation += '  ';
+        },
+
+        groupCollapsed(...label: any[]) {
+            console.group(...label);
+        },
+
+        groupEnd() {
+            groupIndentation = groupIndentation.slice(2);
+        },
+
+        count(label: string = 'default') {
+            const count = (consoleCounts.get(label) ?? 0) + 1;
+            consoleCounts.set(label, count);
+            consoleWrite('info', `${label}: ${count}`);
+        },
+
+        countReset(label: string = 'default') {
+            if (!consoleCounts.has(label)) {
+                consoleWrite('warn', `Count for '${label}' does not exist`);
+                return;
+            }
+            consoleCounts.set(label, 0);
+        },
+
+        time(label: string = 'default') {
+            if (consoleTimers.has(label)) {
+                consoleWrite('warn', `Timer '${label}' already exists`);
+                return;
+            }
+            consoleTimers.set(label, MycoOps.sync.performance_now({}));
+        },
+
+        timeLog(label: string = 'default', ...data: any[]) {
+            const start = consoleTimers.get(label);
+            if (start === undefined) {
+                consoleWrite('warn', `Timer '${label}' does not exist`);
+                return;
+            }
+            const elapsed = formatDuration(MycoOps.sync.performance_now({}) - start);
+            consoleLog('info', [`${label}: ${elapsed}`, ...data]);
+        },
+
+        timeEnd(label: string = 'default') {
+            console.timeLog(label);
+            consoleTimers.delete(label);
+        },
+    };
+    
+    // Set console on globalThis
+    (globalThis as any).console = console;
+    
+    // Create TextEncoder class using MycoOps
+    class TextEncoder {
+        constructor(encoding?: 'utf-8') {
+            if (encoding && encoding !== 'utf-8') {
                 throw new Error('Only utf-8 encoding is supported');
             }
         }
@@ -215,285 +1159,1170 @@
     
     // Set TOML on globalThis
     (globalThis as any).TOML = TOML;
-    
-    // Timer callback storage
-    const timerCallbacks = new Map<number, () => void>();
-    
-    // Global timer completion handler (called by Rust when timers fire)
-    (globalThis as any).__mycoTimerComplete = function(timerId: number) {
-        const callback = timerCallbacks.get(timerId);
-        if (callback) {
-            timerCallbacks.delete(timerId);
-            callback();
+
+    // Standard web globals. Where the spec is subtle (URL parsing, base64,
+    // structured clone) the work is done by ops; the rest is plain JavaScript.
+    class DOMException extends Error {
+        constructor(message: string = '', name: string = 'Error') {
+            super(message);
+            Object.defineProperty(this, 'name', { value: name, configurable: true, writable: true });
         }
-    };
-    
-    // `existingMyco` is the partially-built Myco object handed in by Rust (argv, etc.)
-    
-    // Create a basic Myco object structure, preserving existing properties
-    const myco: any = {
-        ...(existingMyco || {}), // Preserve any existing properties like argv
-        setTimeout(callback: () => void, delay: number): number {
-            const timerId = MycoOps.sync.set_timeout({ delay });
-            timerCallbacks.set(timerId, callback);
-            return timerId;
+    }
+
+    function invalidCharacter(message: string): DOMException {
+        return new DOMException(message, 'InvalidCharacterError');
+    }
+
+    function btoa(data: string): string {
+        const encoded = MycoOps.sync.btoa({ data: String(data) });
+        if (encoded === null) {
+            throw invalidCharacter('btoa: the string contains characters outside of the Latin1 range');
+        }
+        return encoded;
+    }
+
+    function atob(data: string): string {
+        const decoded = MycoOps.sync.atob({ data: String(data) });
+        if (decoded === null) {
+            throw invalidCharacter('atob: the string is not correctly encoded');
+        }
+        return decoded;
+    }
+
+    function structuredClone<T>(value: T): T {
+        // The op reads its argument as a required field, and these clone to themselves
+        if (value === undefined || value === null) {
+            return value;
+        }
+        return MycoOps.sync.structured_clone({ value });
+    }
+
+    const performance = {
+        now(): number {
+            return MycoOps.sync.performance_now({});
         },
-        clearTimeout(timerId: number): void {
-            timerCallbacks.delete(timerId);
-            MycoOps.sync.clear_timeout({ timer_id: timerId });
+        get timeOrigin(): number {
+            return Date.now() - this.now();
         },
-        http: {
-            async requestFetch(url: string): Promise<Myco.Http.FetchToken> {
-                const token = await MycoOps.async.request_fetch_url(url);
-                return {
-                    async fetch(encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
-                        const raw = await MycoOps.async.fetch_url({ token });
-                        return maybeDecode(raw, encoding);
+    };
+
+    // Hooks into private state, assigned by the classes' static blocks so that
+    // they never show up as members of the globals
+    let isStopped: (event: Event) => boolean;
+    let signalAbort: (signal: AbortSignal, reason: any) => void;
+    let linkSearchParams: (query: string, onChange: (query: string) => void) => URLSearchParams;
+    let resetSearchParams: (params: URLSearchParams, query: string) => void;
+
+    class Event {
+        readonly type: string;
+        readonly bubbles: boolean;
+        readonly cancelable: boolean;
+        readonly timeStamp: number;
+        defaultPrevented = false;
+        target: EventTarget | null = null;
+        currentTarget: EventTarget | null = null;
+        #stopped = false;
+
+        constructor(type: string, init?: EventInit) {
+            this.type = String(type);
+            this.bubbles = !!init?.bubbles;
+            this.cancelable = !!init?.cancelable;
+            this.timeStamp = performance.now();
+        }
+
+        preventDefault(): void {
+            if (this.cancelable) {
+                this.defaultPrevented = true;
+            }
+        }
+
+        stopPropagation(): void {}
+
+        stopImmediatePropagation(): void {
+            this.#stopped = true;
+        }
+
+        static {
+            isStopped = (event) => event.#stopped;
+        }
+    }
+
+    type Listener = ((event: Event) => void) | { handleEvent(event: Event): void };
+
+    interface ListenerEntry {
+        listener: Listener;
+        once: boolean;
+    }
+
+    class EventTarget {
+        #listeners = new Map<string, ListenerEntry[]>();
+
+        addEventListener(type: string, listener: Listener | null, options?: boolean | AddEventListenerOptions): void {
+            if (!listener) {
+                return;
+            }
+            const { once = false, signal = undefined } = typeof options === 'object' ? options : {};
+            if (signal?.aborted) {
+                return;
+            }
+            const entries = this.#listeners.get(type) ?? [];
+            if (entries.some((entry) => entry.listener === listener)) {
+                return;
+            }
+            entries.push({ listener, once });
+            this.#listeners.set(type, entries);
+            signal?.addEventListener('abort', () => this.removeEventListener(type, listener));
+        }
+
+        removeEventListener(type: string, listener: Listener | null): void {
+            const entries = this.#listeners.get(type);
+            const index = entries?.findIndex((entry) => entry.listener === listener) ?? -1;
+            if (index !== -1) {
+                entries!.splice(index, 1);
+            }
+        }
+
+        dispatchEvent(event: Event): boolean {
+            event.target = this;
+            event.currentTarget = this;
+            // Listeners added while dispatching only see later events
+            for (const entry of [...(this.#listeners.get(event.type) ?? [])]) {
+                if (!this.#listeners.get(event.type)?.includes(entry)) {
+                    continue;
+                }
+                if (entry.once) {
+                    this.removeEventListener(event.type, entry.listener);
+                }
+                try {
+                    if (typeof entry.listener === 'function') {
+                        entry.listener.call(this, event);
+                    } else {
+                        entry.listener.handleEvent(event);
                     }
-                };
-            },
-            async requestFetchPrefix(urlPrefix: string): Promise<Myco.Http.FetchPrefixToken> {
-                const token = await MycoOps.async.request_fetch_prefix(urlPrefix);
-                return {
-                    async fetch(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
-                        const raw = await MycoOps.async.fetch_url({ token, path });
-                        return maybeDecode(raw, encoding);
+                } catch (e: any) {
+                    // One failing listener does not stop the others
+                    console.error(`Uncaught error in '${event.type}' event listener: ${e?.stack ?? e}`);
+                }
+                if (isStopped(event)) {
+                    break;
+                }
+            }
+            event.currentTarget = null;
+            return !event.defaultPrevented;
+        }
+    }
+
+    // Only the runtime may construct an AbortSignal
+    const signalKey = Symbol('AbortSignal');
+
+    class AbortSignal extends EventTarget {
+        #aborted = false;
+        #reason: any = undefined;
+        onabort: ((event: Event) => void) | null = null;
+
+        constructor(key: symbol) {
+            if (key !== signalKey) {
+                throw new TypeError('Illegal constructor');
+            }
+            super();
+        }
+
+        get aborted(): boolean {
+            return this.#aborted;
+        }
+
+        get reason(): any {
+            return this.#reason;
+        }
+
+        throwIfAborted(): void {
+            if (this.#aborted) {
+                throw this.#reason;
+            }
+        }
+
+        static abort(reason?: any): AbortSignal {
+            const signal = new AbortSignal(signalKey);
+            signalAbort(signal, reason);
+            return signal;
+        }
+
+        static timeout(ms: number): AbortSignal {
+            const signal = new AbortSignal(signalKey);
+            const callback = () => signalAbort(signal, new DOMException('The operation timed out', 'TimeoutError'));
+            // A pending timeout does not keep the process alive
+            const timerId = MycoOps.sync.set_timeout({ callback, delay: ms });
+            MycoOps.sync.timer_ref({ timer_id: timerId, refed: false });
+            return signal;
+        }
+
+        static any(signals: Iterable<AbortSignal>): AbortSignal {
+            const signal = new AbortSignal(signalKey);
+            for (const source of signals) {
+                if (source.aborted) {
+                    signalAbort(signal, source.reason);
+                    return signal;
+                }
+            }
+            for (const source of signals) {
+                source.addEventListener('abort', () => signalAbort(signal, source.reason), { once: true });
+            }
+            return signal;
+        }
+
+        static {
+            signalAbort = (signal, reason) => {
+                if (signal.#aborted) {
+                    return;
+                }
+                signal.#aborted = true;
+                signal.#reason = reason === undefined ? new DOMException('This operation was aborted', 'AbortError') : reason;
+                const event = new Event('abort');
+                signal.onabort?.call(signal, event);
+                signal.dispatchEvent(event);
+            };
+        }
+    }
+
+    class AbortController {
+        readonly signal = new AbortSignal(signalKey);
+
+        abort(reason?: any): void {
+            signalAbort(this.signal, reason);
+        }
+    }
+
+    type BlobPart = string | ArrayBuffer | ArrayBufferView | Blob;
+
+    class Blob {
+        #bytes: Uint8Array;
+        readonly type: string;
+
+        constructor(parts: readonly BlobPart[] = [], options?: BlobPropertyBag) {
+            const chunks = parts.map((part) => {
+                if (part instanceof Blob) {
+                    return part.#bytes;
+                } else if (part instanceof ArrayBuffer) {
+                    return new Uint8Array(part.slice(0));
+                } else if (ArrayBuffer.isView(part)) {
+                    return new Uint8Array(part.buffer.slice(part.byteOffset, part.byteOffset + part.byteLength));
+                }
+                return new TextEncoder().encode(String(part));
+            });
+            this.#bytes = new Uint8Array(chunks.reduce((size, chunk) => size + chunk.length, 0));
+            let offset = 0;
+            for (const chunk of chunks) {
+                this.#bytes.set(chunk, offset);
+                offset += chunk.length;
+            }
+            const type = options?.type ?? '';
+            this.type = /^[\x20-\x7e]*$/.test(type) ? type.toLowerCase() : '';
+        }
+
+        get size(): number {
+            return this.#bytes.length;
+        }
+
+        slice(start?: number, end?: number, type?: string): Blob {
+            return new Blob([this.#bytes.subarray(start, end)], { type });
+        }
+
+        async bytes(): Promise<Uint8Array> {
+            return this.#bytes.slice();
+        }
+
+        async arrayBuffer(): Promise<ArrayBuffer> {
+            return this.#bytes.slice().buffer;
+        }
+
+        async text(): Promise<string> {
+            return new TextDecoder().decode(this.#bytes);
+        }
+
+        [customInspect](options: Myco.InspectOptions): string {
+            return `Blob ${inspect({ size: this.size, type: this.type }, options)}`;
+        }
+    }
+
+    type HeadersInit = Headers | Iterable<readonly [string, string]> | Record<string, string>;
+
+    function normalizeHeaderName(name: string): string {
+        const normalized = String(name).toLowerCase();
+        if (!/^[!#$%&'*+\-.^_`|~0-9a-z]+$/.test(normalized)) {
+            throw new TypeError(`Invalid header name: '${name}'`);
+        }
+        return normalized;
+    }
+
+    function normalizeHeaderValue(value: string): string {
+        const normalized = String(value).replace(/^[\t\n\r ]+|[\t\n\r ]+$/g, '');
+        if (/[\0\n\r]/.test(normalized)) {
+            throw new TypeError(`Invalid header value: '${value}'`);
+        }
+        return normalized;
+    }
+
+    class Headers {
+        // Lowercased names, each with its values in the order they were appended
+        #entries = new Map<string, string[]>();
+
+        constructor(init?: HeadersInit) {
+            if (init === undefined || init === null) {
+                return;
+            }
+            const pairs = Symbol.iterator in Object(init)
+                ? init as Iterable<readonly [string, string]>
+                : Object.entries(init);
+            for (const pair of pairs) {
+                if (pair.length !== 2) {
+                    throw new TypeError('Header pairs must contain exactly a name and a value');
+                }
+                this.append(pair[0], pair[1]);
+            }
+        }
+
+        append(name: string, value: string): void {
+            const key = normalizeHeaderName(name);
+            const values = this.#entries.get(key) ?? [];
+            values.push(normalizeHeaderValue(value));
+            this.#entries.set(key, values);
+        }
+
+        set(name: string, value: string): void {
+            this.#entries.set(normalizeHeaderName(name), [normalizeHeaderValue(value)]);
+        }
+
+        get(name: string): string | null {
+            return this.#entries.get(normalizeHeaderName(name))?.join(', ') ?? null;
+        }
+
+        getSetCookie(): string[] {
+            return [...(this.#entries.get('set-cookie') ?? [])];
+        }
+
+        has(name: string): boolean {
+            return this.#entries.has(normalizeHeaderName(name));
+        }
+
+        delete(name: string): void {
+            this.#entries.delete(normalizeHeaderName(name));
+        }
+
+        forEach(callback: (value: string, name: string, headers: Headers) => void, thisArg?: any): void {
+            for (const [name, value] of this) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries(): IterableIterator<[string, string]> {
+            for (const name of [...this.#entries.keys()].sort()) {
+                if (name === 'set-cookie') {
+                    for (const value of this.#entries.get(name)!) {
+                        yield [name, value];
                     }
-                };
+                } else {
+                    yield [name, this.get(name)!];
+                }
             }
-        },
-        files: {
-            async requestRead(path: string): Promise<Myco.Files.ReadToken> {
-                const token = await MycoOps.async.request_read_file({ path });
+        }
+
+        *keys(): IterableIterator<string> {
+            for (const [name] of this) {
+                yield name;
+            }
+        }
+
+        *values(): IterableIterator<string> {
+            for (const [, value] of this) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator](): IterableIterator<[string, string]> {
+            return this.entries();
+        }
+
+        [customInspect](options: Myco.InspectOptions): string {
+            return `Headers ${inspect(Object.fromEntries(this), options)}`;
+        }
+    }
+
+    type URLSearchParamsInit = URLSearchParams | string | Iterable<readonly [string, string]> | Record<string, string>;
+
+    class URLSearchParams {
+        #pairs: [string, string][] = [];
+        // Set when these are a URL's `searchParams`, to write changes back to it
+        #onChange: ((query: string) => void) | undefined;
+
+        constructor(init: URLSearchParamsInit = '') {
+            if (typeof init === 'string') {
+                this.#pairs = MycoOps.sync.url_query_parse({ query: init }) as [string, string][];
+            } else if (Symbol.iterator in Object(init)) {
+                for (const pair of init as Iterable<readonly [string, string]>) {
+                    if (pair.length !== 2) {
+                        throw new TypeError('Query pairs must contain exactly a name and a value');
+                    }
+                    this.#pairs.push([String(pair[0]), String(pair[1])]);
+                }
+            } else {
+                this.#pairs = Object.entries(init).map(([name, value]) => [name, String(value)]);
+            }
+        }
+
+        static {
+            linkSearchParams = (query, onChange) => {
+                const params = new URLSearchParams(query);
+                params.#onChange = onChange;
+                return params;
+            };
+            resetSearchParams = (params, query) => {
+                params.#pairs = MycoOps.sync.url_query_parse({ query }) as [string, string][];
+            };
+        }
+
+        #update(): void {
+            this.#onChange?.(this.toString());
+        }
+
+        get size(): number {
+            return this.#pairs.length;
+        }
+
+        append(name: string, value: string): void {
+            this.#pairs.push([String(name), String(value)]);
+            this.#update();
+        }
+
+        delete(name: string, value?: string): void {
+            this.#pairs = this.#pairs.filter(([n, v]) => n !== name || (value !== undefined && v !== value));
+            this.#update();
+        }
+
+        get(name: string): string | null {
+            return this.#pairs.find(([n]) => n === name)?.[1] ?? null;
+        }
+
+        getAll(name: string): string[] {
+            return this.#pairs.filter(([n]) => n === name).map(([, v]) => v);
+        }
+
+        has(name: string, value?: string): boolean {
+            return this.#pairs.some(([n, v]) => n === name && (value === undefined || v === value));
+        }
+
+        set(name: string, value: string): void {
+            const index = this.#pairs.findIndex(([n]) => n === name);
+            if (index === -1) {
+                this.#pairs.push([String(name), String(value)]);
+            } else {
+                this.#pairs[index] = [String(name), String(value)];
+                this.#pairs = this.#pairs.filter(([n], i) => n !== name || i <= index);
+            }
+            this.#update();
+        }
+
+        sort(): void {
+            // Array.prototype.sort is stable, which the spec requires
+            this.#pairs.sort(([a], [b]) => (a < b ? -1 : a > b ? 1 : 0));
+            this.#update();
+        }
+
+        forEach(callback: (value: string, name: string, params: URLSearchParams) => void, thisArg?: any): void {
+            for (const [name, value] of this.#pairs) {
+                callback.call(thisArg, value, name, this);
+            }
+        }
+
+        *entries(): IterableIterator<[string, string]> {
+            for (const [name, value] of this.#pairs) {
+                yield [name, value];
+            }
+        }
+
+        *keys(): IterableIterator<string> {
+            for (const [name] of this.#pairs) {
+                yield name;
+            }
+        }
+
+        *values(): IterableIterator<string> {
+            for (const [, value] of this.#pairs) {
+                yield value;
+            }
+        }
+
+        [Symbol.iterator](): IterableIterator<[string, string]> {
+            return this.entries();
+        }
+
+        toString(): string {
+            return MycoOps.sync.url_query_stringify({ pairs: this.#pairs });
+        }
+
+        [customInspect](options: Myco.InspectOptions): string {
+            const entries = this.#pairs.map(([name, value]) => `${inspect(name, options)} => ${inspect(value, options)}`);
+            return entries.length === 0 ? 'URLSearchParams {}' : `URLSearchParams { ${entries.join(', ')} }`;
+        }
+    }
+
+    class URL {
+        #parts: UrlParts;
+        #searchParams: URLSearchParams | undefined;
+
+        constructor(url: string | URL, base?: string | URL) {
+            const parts = MycoOps.sync.url_parse({
+                url: String(url),
+                base: base === undefined ? undefined : String(base),
+            });
+            if (parts === null) {
+                throw new TypeError(`Invalid URL: '${url}'`);
+            }
+            this.#parts = parts;
+        }
+
+        static canParse(url: string | URL, base?: string | URL): boolean {
+            return MycoOps.sync.url_parse({
+                url: String(url),
+                base: base === undefined ? undefined : String(base),
+            }) !== null;
+        }
+
+        static parse(url: string | URL, base?: string | URL): URL | null {
+            return URL.canParse(url, base) ? new URL(url, base) : null;
+        }
+
+        #set(part: keyof UrlParts, value: string): void {
+            this.#parts = MycoOps.sync.url_set({ href: this.#parts.href, part, value: String(value) });
+            if (part === 'search' && this.#searchParams) {
+                resetSearchParams(this.#searchParams, this.#parts.search);
+            }
+        }
+
+        get href(): string { return this.#parts.href; }
+        set href(value: string) {
+            const parts = MycoOps.sync.url_parse({ url: String(value) });
+            if (parts === null) {
+                throw new TypeError(`Invalid URL: '${value}'`);
+            }
+            this.#parts = parts;
+            if (this.#searchParams) {
+                resetSearchParams(this.#searchParams, parts.search);
+            }
+        }
+
+        get origin(): string { return this.#parts.origin; }
+
+        get protocol(): string { return this.#parts.protocol; }
+        set protocol(value: string) { this.#set('protocol', value); }
+
+        get username(): string { return this.#parts.username; }
+        set username(value: string) { this.#set('username', value); }
+
+        get password(): string { return this.#parts.password; }
+        set password(value: string) { this.#set('password', value); }
+
+        get host(): string { return this.#parts.host; }
+        set host(value: string) { this.#set('host', value); }
+
+        get hostname(): string { return this.#parts.hostname; }
+        set hostname(value: string) { this.#set('hostname', value); }
+
+        get port(): string { return this.#parts.port; }
+        set port(value: string) { this.#set('port', value); }
+
+        get pathname(): string { return this.#parts.pathname; }
+        set pathname(value: string) { this.#set('pathname', value); }
+
+        get search(): string { return this.#parts.search; }
+        set search(value: string) { this.#set('search', value); }
+
+        get hash(): string { return this.#parts.hash; }
+        set hash(value: string) { this.#set('hash', value); }
+
+        get searchParams(): URLSearchParams {
+            this.#searchParams ??= linkSearchParams(this.#parts.search, (query) => {
+                this.#parts = MycoOps.sync.url_set({ href: this.#parts.href, part: 'search', value: query });
+            });
+            return this.#searchParams;
+        }
+
+        toString(): string {
+            return this.#parts.href;
+        }
+
+        toJSON(): string {
+            return this.#parts.href;
+        }
+
+        [customInspect](options: Myco.InspectOptions): string {
+            return `URL ${inspect({ ...this.#parts, searchParams: this.searchParams }, options)}`;
+        }
+    }
+
+    Object.assign(globalThis, {
+        DOMException,
+        btoa,
+        atob,
+        structuredClone,
+        performance,
+        Event,
+        EventTarget,
+        AbortSignal,
+        AbortController,
+        Blob,
+        Headers,
+        URLSearchParams,
+        URL,
+    });
+    
+    // Token objects, built around native tokens. The builders are shared by the
+    // request methods and by workers, which rebuild the tokens transferred to them.
+    function fetchToken(token: string): Myco.Http.FetchToken {
+        return bindToken(netTokens, token, {
+            async fetch(encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
+                const raw = await MycoOps.async.fetch_url({ token });
+                return maybeDecode(raw, encoding);
+            }
+        });
+    }
+
+    function fetchPrefixToken(token: string): Myco.Http.FetchPrefixToken {
+        return bindToken(netTokens, token, {
+            async fetch(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
+                const raw = await MycoOps.async.fetch_url({ token, path });
+                return maybeDecode(raw, encoding);
+            }
+        });
+    }
+
+    function envToken(token: string): Myco.Env.Token {
+        return bindToken(envTokens, token, {
+            get(): string | undefined {
+                return MycoOps.sync.env_get({ token }) ?? undefined;
+            }
+        });
+    }
+
+    function readFileToken(token: string): Myco.Files.ReadToken {
+        return bindToken(readTokens, token, {
+            async read(encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
+                const raw = await MycoOps.async.read_file({ token });
+                return maybeDecode(raw, encoding);
+            },
+            async stat(): Promise<Myco.Files.Stats | null> {
+                return await MycoOps.async.stat_file({ token });
+            },
+            async hash(algorithm: Myco.Crypto.DigestAlgorithm = 'sha256'): Promise<string> {
+                return toHex(await MycoOps.async.hash_file({ token, algorithm }));
+            },
+            sync: {
+                read(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                    const raw = MycoOps.sync.read_file({ token });
+                    return maybeDecode(raw, encoding);
+                },
+                stat() {
+                    return MycoOps.sync.stat_file({ token });
+                },
+                hash(algorithm: Myco.Crypto.DigestAlgorithm = 'sha256'): string {
+                    return toHex(MycoOps.sync.hash_file({ token, algorithm }));
+                },
+            },
+        });
+    }
+
+    function writeFileToken(token: string): Myco.Files.WriteToken {
+        return bindToken(writeTokens, token, {
+            async write(contents: string | Uint8Array, options?: Myco.Files.WriteOptions) {
+                return await MycoOps.async.write_file({ token, contents: maybeEncode(contents), atomic: options?.atomic });
+            },
+            async remove() {
+                return await MycoOps.async.remove_file({ token });
+            },
+            sync: {
+                write(contents: string | Uint8Array, options?: Myco.Files.WriteOptions) {
+                    return MycoOps.sync.write_file({ token, contents: maybeEncode(contents), atomic: options?.atomic });
+                },
+                remove() {
+                    return MycoOps.sync.remove_file({ token });
+                },
+            },
+        });
+    }
+
+    function execFileToken(token: string): Myco.Files.ExecToken {
+        return bindToken(execTokens, token, {
+            async exec(args: readonly string[] = [], options?: Myco.Files.ExecOptions): Promise<Myco.Files.ExecResult> {
+                const result = await MycoOps.async.exec_file({ token, path: undefined, args, ...execOptions(options) });
                 return {
-                    async read(encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
-                        const raw = await MycoOps.async.read_file({ token });
-                        return maybeDecode(raw, encoding);
+                    exit_code: result.exit_code,
+                    stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                        const stdoutBytes = new Uint8Array(result.stdout);
+                        return maybeDecode(stdoutBytes, encoding);
                     },
-                    async stat(): Promise<Myco.Files.Stats | null> {
-                        return await MycoOps.async.stat_file({ token });
+                    stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                        const stderrBytes = new Uint8Array(result.stderr);
+                        return maybeDecode(stderrBytes, encoding);
                     },
-                    sync: {
-                        read(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                            const raw = MycoOps.sync.read_file({ token });
-                            return maybeDecode(raw, encoding);
+                }
+            },
+            spawn(args: readonly string[] = [], options?: Myco.Files.SpawnOptions): Myco.Files.Child {
+                return childProcess(MycoOps.sync.spawn_child(spawnArgs(token, undefined, args, options)), options);
+            },
+            async stat(): Promise<Myco.Files.Stats | null> {
+                return await MycoOps.async.stat_file({ token });
+            },
+            sync: {
+                exec(args: string[] = [], options?: Myco.Files.ExecOptions): Myco.Files.ExecResult {
+                    const result = MycoOps.sync.exec_file({ token, args, ...execOptions(options) });
+                    return {
+                        exit_code: result.exit_code,
+                        stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                            const stdoutBytes = new Uint8Array(result.stdout);
+                            return maybeDecode(stdoutBytes, encoding);
                         },
-                        stat() {
-                            return MycoOps.sync.stat_file({ token });
-                        }
-                    },
-                };
+                        stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                            const stderrBytes = new Uint8Array(result.stderr);
+                            return maybeDecode(stderrBytes, encoding);
+                        },
+                    }
+                },
+                stat() {
+                    return MycoOps.sync.stat_file({ token });
+                }
             },
-            async requestWrite(path: string): Promise<Myco.Files.WriteToken> {
-                const token = await MycoOps.async.request_write_file({ path });
+        });
+    }
+
+    function readDirToken(rootDir: string): Myco.Files.ReadDirToken {
+        const token: Myco.Files.ReadDirToken = {
+            async read(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
+                const raw = await MycoOps.async.read_file({ token: rootDir, path });
+                return maybeDecode(raw, encoding);
+            },
+            async stat(path: string): Promise<Myco.Files.Stats | null> {
+                return await MycoOps.async.stat_file({ token: rootDir, path });
+            },
+            async list(path: string, options) {
+                // Recursive listings are walked natively rather than with one op per directory
+                if (options?.recursive) {
+                    const list: Myco.Files.File[] = [];
+                    for await (const file of globFiles({ token: rootDir, path, gitignore: false, extensions: options.extensions })) {
+                        list.push(file);
+                    }
+                    return filterListDir(options, list);
+                }
+                return filterListDir(options, await MycoOps.async.list_dir({ token: rootDir, path }));
+            },
+            glob(pattern: string, options?: Myco.Files.GlobOptions) {
+                return globFiles(globArgs(rootDir, pattern, options));
+            },
+            async hash(path: string, algorithm: Myco.Crypto.DigestAlgorithm = 'sha256'): Promise<string> {
+                return toHex(await MycoOps.async.hash_file({ token: rootDir, path, algorithm }));
+            },
+            sync: {
+                read(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                    const raw = MycoOps.sync.read_file({ token: rootDir, path });
+                    return maybeDecode(raw, encoding);
+                },
+                stat(path: string) {
+                    return MycoOps.sync.stat_file({ token: rootDir, path });
+                },
+                list(path: string, options) {
+                    const list = options?.recursive
+                        ? MycoOps.sync.glob({ token: rootDir, path, gitignore: false, extensions: options.extensions })
+                        : MycoOps.sync.list_dir({ token: rootDir, path });
+                    return filterListDir(options, list);
+                },
+                glob(pattern: string, options?: Myco.Files.GlobOptions) {
+                    return MycoOps.sync.glob(globArgs(rootDir, pattern, options));
+                },
+                hash(path: string, algorithm: Myco.Crypto.DigestAlgorithm = 'sha256'): string {
+                    return toHex(MycoOps.sync.hash_file({ token: rootDir, path, algorithm }));
+                },
+            },
+        };
+        return bindToken(readTokens, rootDir, token);
+    }
+
+    function writeDirToken(token: string): Myco.Files.WriteDirToken {
+        return bindToken(writeTokens, token, {
+            async write(path: string, contents: string | Uint8Array, options?: Myco.Files.WriteOptions): Promise<void> {
+                return await MycoOps.async.write_file({ token, contents: maybeEncode(contents), path, atomic: options?.atomic });
+            },
+            async remove(path: string): Promise<void> {
+                return await MycoOps.async.remove_file({ token, path });
+            },
+            async mkdirp(path: string): Promise<void> {
+                return await MycoOps.async.mkdirp({ token, path });
+            },
+            async rmdir(path: string): Promise<void> {
+                return await MycoOps.async.rmdir({ token, path });
+            },
+            async rmdirRecursive(path: string): Promise<void> {
+                return await MycoOps.async.rmdir_recursive({ token, path });
+            },
+            async lock(path: string) {
+                return fileLock(await MycoOps.async.lock_file({ token, path }));
+            },
+            async tryLock(path: string) {
+                return maybeFileLock(MycoOps.sync.try_lock_file({ token, path }));
+            },
+            sync: {
+                write(path: string, contents: string | Uint8Array, options?: Myco.Files.WriteOptions) {
+                    return MycoOps.sync.write_file({ token, contents: maybeEncode(contents), path, atomic: options?.atomic });
+                },
+                remove(path: string) {
+                    return MycoOps.sync.remove_file({ token, path });
+                },
+                mkdirp(path: string) {
+                    return MycoOps.sync.mkdirp({ token, path });
+                },
+                rmdir(path: string) {
+                    return MycoOps.sync.rmdir({ token, path });
+                },
+                lock(path: string) {
+                    return fileLock(MycoOps.sync.lock_file({ token, path }));
+                },
+                tryLock(path: string) {
+                    return maybeFileLock(MycoOps.sync.try_lock_file({ token, path }));
+                },
+            },
+        });
+    }
+
+    function execDirToken(token: string): Myco.Files.ExecDirToken {
+        return bindToken(execTokens, token, {
+            async exec(path: string, args: readonly string[] = [], options?: Myco.Files.ExecOptions): Promise<Myco.Files.ExecResult> {
+                const result = await MycoOps.async.exec_file({ token, path, args, ...execOptions(options) });
                 return {
-                    async write(contents: string | Uint8Array) {
-                        return await MycoOps.async.write_file({ token, contents: maybeEncode(contents) });
+                    exit_code: result.exit_code,
+                    stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                        const stdoutBytes = new Uint8Array(result.stdout);
+                        return maybeDecode(stdoutBytes, encoding);
                     },
-                    async remove() {
-                        return await MycoOps.async.remove_file({ token });
+                    stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                        const stderrBytes = new Uint8Array(result.stderr);
+                        return maybeDecode(stderrBytes, encoding);
                     },
-                    sync: {
-                        write(contents: string | Uint8Array) {
-                            return MycoOps.sync.write_file({ token, contents: maybeEncode(contents) });
+                }
+            },
+            spawn(path: string, args: readonly string[] = [], options?: Myco.Files.SpawnOptions): Myco.Files.Child {
+                return childProcess(MycoOps.sync.spawn_child(spawnArgs(token, path, args, options)), options);
+            },
+            async stat(path: string): Promise<Myco.Files.Stats | null> {
+                return await MycoOps.async.stat_file({ token, path });
+            },
+            sync: {
+                exec(path: string, args: string[] = [], options?: Myco.Files.ExecOptions): Myco.Files.ExecResult {
+                    const result = MycoOps.sync.exec_file({ token, path, args, ...execOptions(options) });
+                    return {
+                        exit_code: result.exit_code,
+                        stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                            const stdoutBytes = new Uint8Array(result.stdout);
+                            return maybeDecode(stdoutBytes, encoding);
                         },
-                        remove() {
-                            return MycoOps.sync.remove_file({ token });
+                        stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
+                            const stderrBytes = new Uint8Array(result.stderr);
+                            return maybeDecode(stderrBytes, encoding);
                         },
-                    },
+                    }
+                },
+                stat(path: string) {
+                    return MycoOps.sync.stat_file({ token, path });
+                }
+            },
+        });
+    }
+    // A read-write token is a read token and a write token in one object
+    function combineTokens(readToken: any, writeToken: any): any {
+        return bindTokens(readToken, writeToken, {
+            ...readToken,
+            ...writeToken,
+            sync: {
+                ...readToken.sync,
+                ...writeToken.sync,
+            }
+        });
+    }
+
+    const tokenBuilders: Record<string, (token: string) => object> = {
+        read: readFileToken,
+        write: writeFileToken,
+        exec: execFileToken,
+        readDir: readDirToken,
+        writeDir: writeDirToken,
+        execDir: execDirToken,
+        fetch: fetchToken,
+        fetchPrefix: fetchPrefixToken,
+        env: envToken,
+    };
+
+    // The native tokens behind each token object, grouped per object so the worker
+    // can rebuild read-write tokens
+    function transferTokens(tokenObjects: readonly object[]): string[][] {
+        return tokenObjects.map(tokenObject => {
+            const tokens = [readTokens, writeTokens, execTokens, netTokens, envTokens]
+                .map(tokens => tokens.get(tokenObject))
+                .filter((token): token is string => token !== undefined);
+            if (tokens.length === 0) {
+                throw new TypeError('Expected a token to transfer');
+            }
+            return tokens;
+        });
+    }
+
+    function rebuildTokens(transferred: Array<Array<{ kind: string; token: string }>>): object[] {
+        return transferred.map(group => {
+            const [first, second] = group.map(({ kind, token }) => tokenBuilders[kind](token));
+            return second ? combineTokens(first, second) : first;
+        });
+    }
+
+    function worker(id: number): Myco.Workers.Worker {
+        // Waiting for the exit from the start is also what keeps this isolate
+        // running for as long as the worker does
+        const exited: Promise<number> = MycoOps.async.worker_wait({ id });
+        let listener: ((message: any) => void) | undefined;
+        return {
+            postMessage(message: any): void {
+                MycoOps.sync.worker_post({ id, message });
+            },
+            onMessage(handler: (message: any) => void): () => void {
+                listener = handler;
+                MycoOps.sync.worker_listen({ id, handler });
+                return () => {
+                    if (listener === handler) {
+                        listener = undefined;
+                        MycoOps.sync.worker_listen({ id, handler: undefined });
+                    }
+                };
+            },
+            terminate(): void {
+                MycoOps.sync.worker_terminate({ id });
+            },
+            exited,
+        };
+    }
+
+    function parentPort(capabilities: object[]): Myco.Workers.Parent {
+        let listener: ((message: any) => void) | undefined;
+        return {
+            capabilities,
+            postMessage(message: any): void {
+                MycoOps.sync.parent_post({ message });
+            },
+            onMessage(handler: (message: any) => void): () => void {
+                listener = handler;
+                MycoOps.sync.parent_listen({ handler });
+                return () => {
+                    if (listener === handler) {
+                        listener = undefined;
+                        MycoOps.sync.parent_listen({ handler: undefined });
+                    }
                 };
             },
+        };
+    }
+
+    // What `setTimeout`, `setInterval` and `setImmediate` return. Converts to its
+    // timer id, so either can be cleared.
+    class Timeout {
+        #id: number;
+        #refed = true;
+
+        constructor(id: number) {
+            this.#id = id;
+        }
+
+        ref(): this {
+            MycoOps.sync.timer_ref({ timer_id: this.#id, refed: true });
+            this.#refed = true;
+            return this;
+        }
+
+        unref(): this {
+            MycoOps.sync.timer_ref({ timer_id: this.#id, refed: false });
+            this.#refed = false;
+            return this;
+        }
+
+        hasRef(): boolean {
+            return this.#refed;
+        }
+
+        [Symbol.toPrimitive](): number {
+            return this.#id;
+        }
+    }
+
+    function clearTimer(timer: Timeout | number | undefined): void {
+        if (timer !== undefined && timer !== null) {
+            MycoOps.sync.clear_timeout({ timer_id: Number(timer) });
+        }
+    }
+
+    // Hooks run by `process.exit`, and the exit in progress, if any
+    const beforeExitHooks: Array<(code: number) => void | Promise<void>> = [];
+    let exiting: Promise<never> | undefined;
+
+    // Signal handlers still running, settled either way
+    const signalHandlers = new Set<Promise<void>>();
+
+    function trackSignalHandler(result: void | Promise<void>): void {
+        if (!(result instanceof Promise)) {
+            return;
+        }
+        const settled = result.then(() => {}, () => {});
+        signalHandlers.add(settled);
+        settled.then(() => signalHandlers.delete(settled));
+        // A failed handler is still reported as an unhandled rejection
+        result.catch(e => { throw e; });
+    }
+
+    // The default action of a signal nobody listens for any more: let the handlers
+    // already running finish, then exit like `process.exit` does
+    async function exitOnSignal(code: number): Promise<void> {
+        await Promise.all([...signalHandlers]);
+        await myco.process.exit(code);
+    }
+
+    // The powerbox, minus what Rust hands the factory (argv, etc.)
+    const myco: any = {
+        inspect,
+        setTimeout(callback: () => void, delay: number = 0): Timeout {
+            return new Timeout(MycoOps.sync.set_timeout({ callback, delay }));
+        },
+        clearTimeout(timer: Timeout | number | undefined): void {
+            clearTimer(timer);
+        },
+        setInterval(callback: () => void, delay: number = 0): Timeout {
+            return new Timeout(MycoOps.sync.set_interval({ callback, delay }));
+        },
+        clearInterval(timer: Timeout | number | undefined): void {
+            clearTimer(timer);
+        },
+        setImmediate(callback: () => void): Timeout {
+            return new Timeout(MycoOps.sync.set_immediate({ callback }));
+        },
+        clearImmediate(timer: Timeout | number | undefined): void {
+            clearTimer(timer);
+        },
+        queueMicrotask(callback: () => void): void {
+            MycoOps.sync.queue_microtask({ callback });
+        },
+        process: {
+            // Read once, when the factory copies the process object
+            get pid() {
+                return MycoOps.sync.process_info({}).pid;
+            },
+            get platform() {
+                return MycoOps.sync.process_info({}).platform;
+            },
+            get arch() {
+                return MycoOps.sync.process_info({}).arch;
+            },
+            onSignal(signal: Myco.Process.Signal, handler: (signal: Myco.Process.Signal) => void | Promise<void>): () => void {
+                const id = MycoOps.sync.signal_listen({
+                    signal,
+                    handler: received => trackSignalHandler(handler(received)),
+                });
+                return () => MycoOps.sync.signal_unlisten({ id });
+            },
+            onUnhandledRejection(handler: (reason: unknown, promise: Promise<unknown>) => void): () => void {
+                const id = MycoOps.sync.rejection_listen({ handler });
+                return () => MycoOps.sync.rejection_unlisten({ id });
+            },
+            beforeExit(hook: (code: number) => void | Promise<void>): void {
+                beforeExitHooks.push(hook);
+            },
+            exit(code: number = 0): Promise<never> {
+                exiting ??= (async () => {
+                    for (const hook of beforeExitHooks.splice(0)) {
+                        try {
+                            await hook(code);
+                        } catch (e: any) {
+                            console.error(`Error in beforeExit hook: ${e}`);
+                        }
+                    }
+                    MycoOps.sync.exit({ code });
+                    return new Promise<never>(() => {});
+                })();
+                return exiting;
+            },
+        },
+        archive: {
+            async zip(source: Myco.Files.ReadDirToken, path: string, destination: Myco.Files.WriteToken): Promise<void> {
+                return await MycoOps.async.zip(zipArgs(source, path, destination));
+            },
+            async unzip(source: Myco.Files.ReadToken, destination: Myco.Files.WriteDirToken, path: string = '.'): Promise<string[]> {
+                return await MycoOps.async.unzip(unzipArgs(source, destination, path));
+            },
+            sync: {
+                zip(source: Myco.Files.ReadDirToken, path: string, destination: Myco.Files.WriteToken): void {
+                    return MycoOps.sync.zip(zipArgs(source, path, destination));
+                },
+                unzip(source: Myco.Files.ReadToken, destination: Myco.Files.WriteDirToken, path: string = '.'): string[] {
+                    return MycoOps.sync.unzip(unzipArgs(source, destination, path));
+                },
+            },
+        },
+        workers: {
+            spawn(modulePath: string, options?: Myco.Workers.SpawnOptions): Myco.Workers.Worker {
+                const capabilities = transferTokens(options?.capabilities ?? []);
+                return worker(MycoOps.sync.worker_spawn({ path: modulePath, capabilities }));
+            },
+        },
+        crypto: {
+            async digest(algorithm: Myco.Crypto.DigestAlgorithm, bytes: string | Uint8Array): Promise<Uint8Array> {
+                return await MycoOps.async.digest({ algorithm, bytes: maybeEncode(bytes) });
+            },
+            sync: {
+                digest(algorithm: Myco.Crypto.DigestAlgorithm, bytes: string | Uint8Array): Uint8Array {
+                    return MycoOps.sync.digest({ algorithm, bytes: maybeEncode(bytes) });
+                },
+            },
+        },
+        env: {
+            async request(name: string): Promise<Myco.Env.Token> {
+                return envToken(await MycoOps.async.request_env(name));
+            },
+        },
+        http: {
+            async requestFetch(url: string): Promise<Myco.Http.FetchToken> {
+                return fetchToken(await MycoOps.async.request_fetch_url(url));
+            },
+            async requestFetchPrefix(urlPrefix: string): Promise<Myco.Http.FetchPrefixToken> {
+                return fetchPrefixToken(await MycoOps.async.request_fetch_prefix(urlPrefix));
+            }
+        },
+        files: {
+            async requestRead(path: string): Promise<Myco.Files.ReadToken> {
+                return readFileToken(await MycoOps.async.request_read_file({ path }));
+            },
+            async requestWrite(path: string): Promise<Myco.Files.WriteToken> {
+                return writeFileToken(await MycoOps.async.request_write_file({ path }));
+            },
             async requestReadWrite(path: string): Promise<Myco.Files.ReadWriteToken> {
                 const readToken = await this.requestRead(path);
                 const writeToken = await this.requestWrite(path);
-                return {
-                    ...readToken,
-                    ...writeToken,
-                    sync: {
-                        ...readToken.sync,
-                        ...writeToken.sync,
-                    }
-                } as Myco.Files.ReadWriteToken;
+                return combineTokens(readToken, writeToken);
             },
             async requestExec(path: string): Promise<Myco.Files.ExecToken> {
-                const token = await MycoOps.async.request_exec_file({ path });
-                return {
-                    async exec(args: readonly string[] = []): Promise<Myco.Files.ExecResult> {
-                        const result = await MycoOps.async.exec_file({ token, path: undefined, args });
-                        return {
-                            exit_code: result.exit_code,
-                            stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                const stdoutBytes = new Uint8Array(result.stdout);
-                                return maybeDecode(stdoutBytes, encoding);
-                            },
-                            stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                const stderrBytes = new Uint8Array(result.stderr);
-                                return maybeDecode(stderrBytes, encoding);
-                            },
-                        }
-                    },
-                    async stat(): Promise<Myco.Files.Stats | null> {
-                        return await MycoOps.async.stat_file({ token });
-                    },
-                    sync: {
-                        exec(args: string[] = []): Myco.Files.ExecResult {
-                            const result = MycoOps.sync.exec_file({ token, args });
-                            return {
-                                exit_code: result.exit_code,
-                                stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                    const stdoutBytes = new Uint8Array(result.stdout);
-                                    return maybeDecode(stdoutBytes, encoding);
-                                },
-                                stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                    const stderrBytes = new Uint8Array(result.stderr);
-                                    return maybeDecode(stderrBytes, encoding);
-                                },
-                            }
-                        },
-                        stat() {
-                            return MycoOps.sync.stat_file({ token });
-                        }
-                    },
-                };
+                return execFileToken(await MycoOps.async.request_exec_file({ path }));
             },
             async requestReadDir(path: string): Promise<Myco.Files.ReadDirToken> {
-                const rootDir = await MycoOps.async.request_read_dir({ path });
-                const token: Myco.Files.ReadDirToken = {
-                    async read(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): Promise<any> {
-                        const raw = await MycoOps.async.read_file({ token: rootDir, path });
-                        return maybeDecode(raw, encoding);
-                    },
-                    async stat(path: string): Promise<Myco.Files.Stats | null> {
-                        return await MycoOps.async.stat_file({ token: rootDir, path });
-                    },
-                    async list(path: string, options) {
-                        let list = await MycoOps.async.list_dir({ token: rootDir, path });
-                        if (options?.recursive) {
-                            const subdirs = list.filter((file) => file.stats.is_dir);
-                            for (const subdir of subdirs) {
-                                const subPath = `${path}/${subdir.name}`;
-                                const subFiles = (await this.list(subPath, options)).map((file) => ({
-                                    ...file,
-                                    name: `${subdir.name}/${file.name}`,
-                                }));
-                                list.push(...subFiles);
-                            }
-                        }
-                        return filterListDir(options, list);
-                    },
-                    sync: {
-                        read(path: string, encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                            const raw = MycoOps.sync.read_file({ token: rootDir, path });
-                            return maybeDecode(raw, encoding);
-                        },
-                        stat(path: string) {
-                            return MycoOps.sync.stat_file({ token: rootDir, path });
-                        },
-                        list(path: string, options) {
-                            let list = MycoOps.sync.list_dir({ token: rootDir, path });
-                            if (options?.recursive) {
-                                const subdirs = list.filter((file) => file.stats.is_dir);
-                                for (const subdir of subdirs) {
-                                    const subPath = `${path}/${subdir.name}`;
-                                    const subFiles = this.list(subPath, options).map((file) => ({
-                                        ...file,
-                                        name: `${subdir.name}/${file.name}`,
-                                    }));
-                                    list.push(...subFiles);
-                                }
-                            }
-                            return filterListDir(options, list);
-                        },
-                    },
-                };
-                return token;
+                return readDirToken(await MycoOps.async.request_read_dir({ path }));
             },
             async requestWriteDir(path: string): Promise<Myco.Files.WriteDirToken> {
-                const token = await MycoOps.async.request_write_dir({ path });
-                return {
-                    async write(path: string, contents: string | Uint8Array): Promise<void> {
-                        return await MycoOps.async.write_file({ token, contents: maybeEncode(contents), path });
-                    },
-                    async remove(path: string): Promise<void> {
-                        return await MycoOps.async.remove_file({ token, path });
-                    },
-                    async mkdirp(path: string): Promise<void> {
-                        return await MycoOps.async.mkdirp({ token, path });
-                    },
-                    async rmdir(path: string): Promise<void> {
-                        return await MycoOps.async.rmdir({ token, path });
-                    },
-                    async rmdirRecursive(path: string): Promise<void> {
-                        return await MycoOps.async.rmdir_recursive({ token, path });
-                    },
-                    sync: {
-                        write(path: string, contents: string | Uint8Array) {
-                            return MycoOps.sync.write_file({ token, contents: maybeEncode(contents), path });
-                        },
-                        remove(path: string) {
-                            return MycoOps.sync.remove_file({ token, path });
-                        },
-                        mkdirp(path: string) {
-                            return MycoOps.sync.mkdirp({ token, path });
-                        },
-                        rmdir(path: string) {
-                            return MycoOps.sync.rmdir({ token, path });
-                        },
-                    },
-                };
+                return writeDirToken(await MycoOps.async.request_write_dir({ path }));
             },
             async requestReadWriteDir(path: string): Promise<Myco.Files.ReadWriteDirToken> {
-                const readDirToken = await this.requestReadDir(path);
-                const writeDirToken = await this.requestWriteDir(path);
-                return {
-                    ...readDirToken,
-                    ...writeDirToken,
-                    sync: {
-                        ...readDirToken.sync,
-                        ...writeDirToken.sync,
-                    }
-                } as Myco.Files.ReadWriteDirToken;
+                const readToken = await this.requestReadDir(path);
+                const writeToken = await this.requestWriteDir(path);
+                return combineTokens(readToken, writeToken);
             },
             async requestExecDir(path: string): Promise<Myco.Files.ExecDirToken> {
-                const token = await MycoOps.async.request_exec_dir({ path });
-                return {
-                    async exec(path: string, args: readonly string[] = []): Promise<Myco.Files.ExecResult> {
-                        const result = await MycoOps.async.exec_file({ token, path, args });
-                        return {
-                            exit_code: result.exit_code,
-                            stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                const stdoutBytes = new Uint8Array(result.stdout);
-                                return maybeDecode(stdoutBytes, encoding);
-                            },
-                            stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                const stderrBytes = new Uint8Array(result.stderr);
-                                return maybeDecode(stderrBytes, encoding);
-                            },
-                        }
-                    },
-                    async stat(path: string): Promise<Myco.Files.Stats | null> {
-                        return await MycoOps.async.stat_file({ token, path });
-                    },
-                    sync: {
-                        exec(path: string, args: string[] = []): Myco.Files.ExecResult {
-                            const result = MycoOps.sync.exec_file({ token, path, args });
-                            return {
-                                exit_code: result.exit_code,
-                                stdout(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                    const stdoutBytes = new Uint8Array(result.stdout);
-                                    return maybeDecode(stdoutBytes, encoding);
-                                },
-                                stderr(encoding: 'utf-8' | 'raw' = 'utf-8'): any {
-                                    const stderrBytes = new Uint8Array(result.stderr);
-                                    return maybeDecode(stderrBytes, encoding);
-                                },
-                            }
-                        },
-                        stat(path: string) {
-                            return MycoOps.sync.stat_file({ token, path });
-                        }
-                    },
-                };
+                return execDirToken(await MycoOps.async.request_exec_dir({ path }));
             },
             cwd(): string {
                 return MycoOps.sync.cwd({});
@@ -504,6 +2333,63 @@
         }
     };
 
-    // Hand the powerbox back to Rust. It is never installed on globalThis.
-    return myco;
-});
+    return function (ops: MycoOps, existingMyco: any, transferred?: Array<Array<{ kind: string; token: string }>>) {
+        if (!ops) {
+            throw new Error("MycoOps was not provided to the Myco runtime factory");
+        }
+
+        // Wrap each MycoOps function in a try/catch and print the stack trace
+        for (const key in ops.async) {
+            if (typeof ops.async[key as keyof typeof ops.async] === 'function') {
+                const originalFn = ops.async[key as keyof typeof ops.async] as Function;
+                const newFn: Function = async function(...args: any[]) {
+                    try {
+                        return await originalFn(...args);
+                    } catch (e: any) {
+                        let errorMessage = e.toString();
+                        if (errorMessage.includes("Error: ")) {
+                            errorMessage = errorMessage.slice(7);
+                        }
+                        const error = new Error(errorMessage);
+                        // Omit this frame from the stack trace
+                        error.stack = error.stack?.replace(/ +at async <internal> [^\n]*\n/, '');
+                        throw error;
+                    }
+                };
+                Object.defineProperty(newFn, 'name', {
+                    value: 'async <internal>',
+                    writable: false,
+                    configurable: false,
+                });
+                (ops.async[key as keyof typeof ops.async] as any) = newFn;
+            }
+        }
+        MycoOps = ops;
+        MycoOps.sync.signal_default({ handler: exitOnSignal });
+
+        const powerbox = {
+            ...(existingMyco || {}), // Preserve any existing properties like argv
+            ...myco,
+            process: { ...myco.process },
+        };
+        if (transferred) {
+            // A worker holds only the tokens transferred to it, so it gets no way
+            // to request more, nor to change the process-wide working directory
+            powerbox.files = { cwd: myco.files.cwd };
+            powerbox.http = {};
+            powerbox.env = {};
+            // Signals are delivered to the whole process, so only the main thread
+            // listens for them
+            powerbox.process.onSignal = () => {
+                throw new Error("Only the main thread can listen for signals");
+            };
+            powerbox.workers = {
+                spawn: myco.workers.spawn,
+                parent: parentPort(rebuildTokens(transferred)),
+            };
+        }
+
+        // Hand the powerbox back to Rust. It is never installed on globalThis.
+        return powerbox;
+    };
+})();
@@ -1,5 +1,66 @@
 type Token = string;
 
+interface UrlParts {
+    href: string;
+    origin: string;
+    protocol: string;
+    username: string;
+    password: string;
+    host: string;
+    hostname: string;
+    port: string;
+    pathname: string;
+    search: string;
+    hash: string;
+}
+
+interface GlobArgs {
+    token: Token;
+    path: string;
+    pattern?: string;
+    ignore?: readonly string[];
+    gitignore?: boolean;
+    follow_symlinks?: boolean;
+    extensions?: readonly string[];
+}
+
+interface ZipArgs {
+    src_token: Token;
+    path: string;
+    dst_token: Token;
+}
+
+interface UnzipArgs {
+    src_token: Token;
+    dst_token: Token;
+    path: string;
+}
+
+interface ExecArgs {
+    token: Token;
+    path?: string;
+    args: readonly string[];
+    env?: Readonly<Record<string, string>>;
+    inherit_env?: readonly Token[];
+    cwd?: Token;
+    grant?: readonly Token[];
+    timeout?: number;
+    max_output?: number;
+}
+
+interface SpawnArgs {
+    token: Token;
+    path?: string;
+    args: readonly string[];
+    env?: Readonly<Record<string, string>>;
+    inherit_env?: readonly Token[];
+    cwd?: Token;
+    grant?: readonly Token[];
+    stdin?: Myco.Files.Stdio;
+    stdout?: Myco.Files.Stdio;
+    stderr?: Myco.Files.Stdio;
+}
+
 interface ExecResult {
     readonly stdout: Uint8Array;
     readonly stderr: Uint8Array;
@@ -11,11 +72,18 @@ declare global {
         sync: {
             // Filesystem
             read_file(args: { token: Token; path?: string }): Uint8Array;
-            write_file(args: { token: Token; contents: Uint8Array; path?: string }): void;
-            exec_file(args: { token: Token; path?: string; args: readonly string[] }): ExecResult;
+            write_file(args: { token: Token; contents: Uint8Array; path?: string; atomic?: boolean }): void;
+            exec_file(args: ExecArgs): ExecResult;
             remove_file(args: { token: Token; path?: string }): void;
             stat_file(args: { token: Token; path?: string }): Myco.Files.Stats | null;
             list_dir(args: { token: Token; path: string }): Myco.Files.File[];
+            glob(args: GlobArgs): Myco.Files.File[];
+            glob_open(args: GlobArgs): Token;
+            glob_close(args: { token: Token }): void;
+            lock_file(args: { token: Token; path: string }): Token;
+            try_lock_file(args: { token: Token; path: string }): Token | null;
+            unlock_file(args: { token: Token }): void;
+            hash_file(args: { token: Token; path?: string; algorithm: string }): Uint8Array;
             mkdirp(args: { token: Token; path: string }): void;
             rmdir(args: { token: Token; path: string }): void;
             cwd(args: {}): string;
@@ -24,17 +92,64 @@ declare global {
             // Encoding
             encode_utf8(args: { text: string }): Uint8Array;
             decode_utf8(args: { bytes: Uint8Array }): string;
+
+            // Crypto
+            digest(args: { algorithm: string; bytes: Uint8Array }): Uint8Array;
+
+            // Processes
+            spawn_child(args: SpawnArgs): { token: Token; pid: number };
+            child_close_stdin(args: { token: Token }): void;
+            child_kill(args: { token: Token; signal?: string }): void;
+            env_get(args: { token: Token }): string | null;
+            child_release(args: { token: Token }): void;
+
+            // Process lifecycle
+            signal_listen(args: { signal: string; handler: (signal: Myco.Process.Signal) => void }): number;
+            signal_unlisten(args: { id: number }): void;
+            signal_default(args: { handler: (code: number) => Promise<void> }): void;
+            rejection_listen(args: { handler: (reason: unknown, promise: Promise<unknown>) => void }): number;
+            rejection_unlisten(args: { id: number }): void;
+            exit(args: { code: number }): void;
+            process_info(args: {}): { pid: number; platform: string; arch: string };
+
+            // Archive
+            zip(args: ZipArgs): void;
+            unzip(args: UnzipArgs): string[];
         
+            // Web globals
+            btoa(args: { data: string }): string | null;
+            atob(args: { data: string }): string | null;
+            structured_clone<T>(args: { value: T }): T;
+            performance_now(args: {}): number;
+            url_parse(args: { url: string; base?: string }): UrlParts | null;
+            url_set(args: { href: string; part: keyof UrlParts; value: string }): UrlParts;
+            url_query_parse(args: { query: string }): string[][];
+            url_query_stringify(args: { pairs: readonly (readonly string[])[] }): string;
+
             // TOML
             toml_parse(args: { toml_string: string }): any;
             toml_stringify(args: { value: any }): string;
     
             // Core
-            set_timeout(args: { delay: number }): number;
+            set_timeout(args: { callback: () => void, delay: number }): number;
+            set_interval(args: { callback: () => void, delay: number }): number;
+            set_immediate(args: { callback: () => void }): number;
             clear_timeout(args: { timer_id: number }): void;
-            print(args: { message: string }): void;
-            eprint(args: { message: string }): void;
+            timer_ref(args: { timer_id: number, refed: boolean }): void;
+            queue_microtask(args: { callback: () => void }): void;
+            console_write(args: { level: 'log' | 'info' | 'debug' | 'warn' | 'error' | 'trace'; message: string; args?: string }): void;
+            console_colors(args: { level: 'log' | 'info' | 'debug' | 'warn' | 'error' | 'trace' }): boolean;
+            console_json(args: {}): boolean;
+            promise_state(args: { promise: Promise<unknown> }): { state: 'pending' | 'fulfilled' | 'rejected'; result: unknown };
             trace(args: {}): string;
+
+            // Workers
+            worker_spawn(args: { path: string, capabilities: string[][] }): number;
+            worker_post(args: { id: number, message: any }): void;
+            worker_listen(args: { id: number, handler: ((message: any) => void) | undefined }): void;
+            worker_terminate(args: { id: number }): void;
+            parent_post(args: { message: any }): void;
+            parent_listen(args: { handler: ((message: any) => void) | undefined }): void;
         };
         async: {
             // Token requests are always async
@@ -47,19 +162,38 @@ declare global {
 
             // Filesystem
             read_file(args: { token: Token; path?: string }): Promise<Uint8Array>;
-            write_file(args: { token: Token; contents: Uint8Array; path?: string }): Promise<void>;
-            exec_file(args: { token: Token; path?: string; args: readonly string[] }): Promise<ExecResult>;
+            write_file(args: { token: Token; contents: Uint8Array; path?: string; atomic?: boolean }): Promise<void>;
+            exec_file(args: ExecArgs): Promise<ExecResult>;
             remove_file(args: { token: Token; path?: string }): Promise<void>;
             stat_file(args: { token: Token; path?: string }): Promise<Myco.Files.Stats | null>;
             list_dir(args: { token: Token; path: string }): Promise<Myco.Files.File[]>;
+            glob_next(args: { token: Token }): Promise<Myco.Files.File[]>;
+            lock_file(args: { token: Token; path: string }): Promise<Token>;
+            hash_file(args: { token: Token; path?: string; algorithm: string }): Promise<Uint8Array>;
             mkdirp(args: { token: Token; path: string }): Promise<void>;
             rmdir(args: { token: Token; path: string }): Promise<void>;
             rmdir_recursive(args: { token: Token; path: string }): Promise<void>;
 
+            // Crypto
+            digest(args: { algorithm: string; bytes: Uint8Array }): Promise<Uint8Array>;
+
+            // Processes
+            child_read(args: { token: Token; stream: 'stdout' | 'stderr' }): Promise<Uint8Array>;
+            child_write(args: { token: Token; bytes: Uint8Array }): Promise<void>;
+            child_wait(args: { token: Token }): Promise<Myco.Files.ChildStatus>;
+
+            // Archive
+            zip(args: ZipArgs): Promise<void>;
+            unzip(args: UnzipArgs): Promise<string[]>;
+
             // HTTP
             request_fetch_url(url: string): Promise<Token>;
             request_fetch_prefix(url: string): Promise<Token>;
+            request_env(name: string): Promise<Token>;
             fetch_url(args: { token: Token; path?: string }): Promise<Uint8Array>;
+
+            // Workers
+            worker_wait(args: { id: number }): Promise<number>;
         };
     }
 
@@ -13,6 +13,9 @@ import { TestReporter } from "./TestReporter.ts";
 const MYCO_BINARY_PLACEHOLDER = "{{MYCO_BINARY}}";
 
 export class TestRunner {
+    // Env tokens for what every test passes through, requested once for the whole run
+    private inheritEnv?: Promise<Myco.Env.Token[]>;
+
     constructor(private mycoBinary: MycoBinary, private myco: Myco) { }
 
     async runTestSuite(suitePath: string, reporter: TestReporter): Promise<Array<TestResult>> {
@@ -136,7 +139,16 @@ export class TestRunner {
         const startTime = Date.now();
 
         try {
-            const result = await this.mycoBinary.token.exec(args);
+            // Children no longer inherit the environment, so pass through what the
+            // binary under test needs to find other programs
+            this.inheritEnv ??= Promise.all([
+                this.myco.env.request("PATH"),
+                this.myco.env.request("HOME"),
+            ]);
+            const result = await this.mycoBinary.token.exec(args, {
+                inheritEnv: await this.inheritEnv,
+                env: testCase.environment_variables,
+            });
 
             const duration = Date.now() - startTime;
             const testOutput: TestOutput = {
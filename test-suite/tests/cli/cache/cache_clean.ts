@@ -0,0 +1,48 @@
+export default async function(myco: Myco) {
+    const mycoBinaryPath = myco.argv[3];
+    const originalCwd = myco.files.cwd();
+    const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
+    const project = await myco.files.requestReadDir("./fixtures/project");
+
+    async function runMyco(...args: string[]): Promise<string> {
+        const result = await mycoExec.exec(args, { inheritEnv });
+        if (result.exit_code !== 0) {
+            console.error("Command failed:", result.stderr());
+            throw new Error(`Command failed with exit code ${result.exit_code}`);
+        }
+        return result.stdout().trim();
+    }
+
+    // The program's output, and what V8 did with the entry point's code cache
+    async function runWithCodeCache(): Promise<string> {
+        const lines = (await runMyco("--no-color", "--log-level", "debug", "run")).split("\n");
+        const output = lines.filter(line => !line.startsWith("["));
+        const status = lines
+            .filter(line => / code cache for .*\/main\.ts$/.test(line))
+            .map(line => line.slice(line.indexOf(": ") + 2).replace(/ for .*$/, ""));
+        return `${output.join(" ")} (${status.join(", ")})`;
+    }
+
+    try {
+        myco.files.chdir("./fixtures/project");
+        await runMyco("cache", "clean");
+
+        console.log(`First run: ${await runWithCodeCache()}`);
+        const entries = await project.list(".myco/cache/transpile");
+        console.log(`Cached files: ${entries.map(entry => entry.name.replace(/^[0-9a-f]{64}/, "<key>")).sort().join(", ")}`);
+        console.log(`Second run: ${await runWithCodeCache()}`);
+
+        const cache = await myco.files.requestWriteDir(".myco/cache");
+        const [codeCache] = await project.list(".myco/cache/v8");
+        await cache.write(`v8/${codeCache.name}`, "not a code cache");
+        console.log(`Corrupted run: ${await runWithCodeCache()}`);
+        console.log(`Regenerated run: ${await runWithCodeCache()}`);
+
+        console.log(await runMyco("cache", "clean"));
+        console.log(`Cache exists: ${(await project.stat(".myco/cache")) !== null}`);
+        console.log(await runMyco("cache", "clean"));
+    } finally {
+        myco.files.chdir(originalCwd);
+    }
+}
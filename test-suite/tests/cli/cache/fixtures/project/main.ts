@@ -0,0 +1,5 @@
+const greeting: string = "Hello from a cached module";
+
+export default function() {
+    console.log(greeting);
+}
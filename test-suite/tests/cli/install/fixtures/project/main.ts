@@ -0,0 +1,3 @@
+import {greet} from "@old/greeter";
+
+console.log(greet("old archives"));
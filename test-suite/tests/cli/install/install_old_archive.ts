@@ -0,0 +1,35 @@
+export default async function(myco: Myco) {
+    const mycoBinaryPath = myco.argv[3];
+    const originalCwd = myco.files.cwd();
+    const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
+
+    async function runMyco(...args: string[]): Promise<string> {
+        const result = await mycoExec.exec(args, { inheritEnv });
+        if (result.exit_code !== 0) {
+            console.error("Command failed:", result.stderr());
+            throw new Error(`Command failed with exit code ${result.exit_code}`);
+        }
+        return result.stdout().trim();
+    }
+
+    try {
+        myco.files.chdir("./fixtures/project");
+        const project = await myco.files.requestReadWriteDir(".");
+        for (const remove of [() => project.rmdirRecursive("./vendor"), () => project.remove("./myco-lock.toml")]) {
+            try {
+                await remove();
+            } catch (e) {
+                // Not installed yet, which is fine
+            }
+        }
+
+        // The archive was packed before manifests went in it, so the registry's is used
+        await runMyco("install", "--save");
+        const manifest = TOML.parse(await project.read("./vendor/@old/greeter.toml"));
+        console.log(`Installed ${manifest.package.name} v${manifest.package.version}`);
+        console.log(await runMyco("run", "main.ts"));
+    } finally {
+        myco.files.chdir(originalCwd);
+    }
+}
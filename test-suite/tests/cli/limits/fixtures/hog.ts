@@ -0,0 +1,7 @@
+export default function (_myco: Myco) {
+    console.log("Allocating");
+    const hoard: number[][] = [];
+    while (true) {
+        hoard.push(new Array(100_000).fill(hoard.length));
+    }
+}
@@ -0,0 +1,5 @@
+export default async function (myco: Myco) {
+    console.log("Waiting");
+    await new Promise((resolve) => myco.setTimeout(resolve, 60_000));
+    console.log("ERROR: Should have been stopped");
+}
@@ -0,0 +1,4 @@
+export default async function (myco: Myco) {
+    await new Promise((resolve) => myco.setTimeout(resolve, 10));
+    console.log("Finished in time");
+}
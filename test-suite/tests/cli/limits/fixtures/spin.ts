@@ -0,0 +1,4 @@
+export default function (_myco: Myco) {
+    console.log("Spinning");
+    while (true) {}
+}
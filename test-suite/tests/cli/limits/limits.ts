@@ -0,0 +1,29 @@
+export default async function(myco: Myco) {
+    const mycoBinaryPath = myco.argv[3];
+    const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
+
+    async function runMyco(...args: string[]) {
+        const result = await mycoExec.exec(["run", ...args], { inheritEnv });
+        console.log(`${args.join(" ")}: exit code ${result.exit_code}`);
+        for (const line of result.stdout().trim().split("\n")) {
+            console.log(`  stdout: ${line}`);
+        }
+        console.log(`  stderr: ${result.stderr().trim() || "(none)"}`);
+    }
+
+    await runMyco("--timeout", "0.5", "fixtures/spin.ts");
+    await runMyco("--timeout", "0.5", "fixtures/idle.ts");
+    await runMyco("--timeout", "5", "fixtures/quick.ts");
+    await runMyco("--max-heap-mb", "32", "fixtures/hog.ts");
+
+    // Limits from myco.toml's [run] table, which flags override
+    const originalCwd = myco.files.cwd();
+    try {
+        myco.files.chdir("./fixtures/configured");
+        await runMyco("idle");
+        await runMyco("--timeout", "1", "idle");
+    } finally {
+        myco.files.chdir(originalCwd);
+    }
+}
@@ -0,0 +1,4 @@
+export default async function () {
+    console.debug("Debug details");
+    console.log("Always shown");
+}
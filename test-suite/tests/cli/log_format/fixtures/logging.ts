@@ -0,0 +1,11 @@
+export default async function (myco: Myco) {
+    console.log("Hello", "world", 42);
+    console.warn("Low disk space:", { free: 10n, unit: "GB" });
+    const node: any = { name: "root" };
+    node.self = node;
+    console.info(node, [1, undefined, Infinity]);
+    console.error(new TypeError("Bad input"));
+    console.group("Group");
+    console.log(`Nested at ${new Date(0).toISOString()}`);
+    console.groupEnd();
+}
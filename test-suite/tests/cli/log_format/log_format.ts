@@ -0,0 +1,35 @@
+export default async function(myco: Myco) {
+    const mycoBinaryPath = myco.argv[3];
+    const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
+
+    const result = await mycoExec.exec(["run", "--log-format", "json", "fixtures/logging.ts"], { inheritEnv });
+    console.log(`Exit code: ${result.exit_code}`);
+
+    for (const [stream, output] of [["stdout", result.stdout()], ["stderr", result.stderr()]]) {
+        for (const line of output.trim().split("\n")) {
+            const record = JSON.parse(line);
+            const location = record.location.split("/").pop();
+            const timestamp = /^\d{4}-\d\d-\d\dT\d\d:\d\d:\d\d\.\d{3}Z$/.test(record.timestamp);
+            console.log(`${stream} ${record.level} ${location} timestamp=${timestamp}`);
+            console.log(`  message: ${JSON.stringify(record.message.split("\n")[0])}`);
+            if (record.level === "error") {
+                const [error] = record.args;
+                console.log(`  args: ${error.name} ${error.message} ${error.stack.startsWith("TypeError: Bad input")}`);
+            } else {
+                console.log(`  args: ${JSON.stringify(record.args)}`);
+            }
+        }
+    }
+
+    const text = await mycoExec.exec(["run", "fixtures/logging.ts"], { inheritEnv });
+    console.log(`Text mode: ${text.stdout().split("\n")[0]}`);
+
+    // Console output, leaving out the CLI's own log lines
+    const consoleLines = (output: string) =>
+        output.split("\n").filter((line) => line !== "" && !line.startsWith("[")).join(", ");
+    const quiet = await mycoExec.exec(["run", "fixtures/debug.ts"], { inheritEnv });
+    console.log(`Default level: ${consoleLines(quiet.stdout())}`);
+    const verbose = await mycoExec.exec(["--no-color", "--log-level", "debug", "run", "fixtures/debug.ts"], { inheritEnv });
+    console.log(`Debug level: ${consoleLines(verbose.stdout())}`);
+}
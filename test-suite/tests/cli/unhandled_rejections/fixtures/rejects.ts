@@ -0,0 +1,5 @@
+export default async function (myco: Myco) {
+    Promise.reject(new Error("Ignored rejection"));
+    await new Promise((resolve) => myco.setTimeout(resolve, 10));
+    console.log("Still running");
+}
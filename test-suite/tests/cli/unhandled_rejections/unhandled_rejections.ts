@@ -0,0 +1,15 @@
+export default async function(myco: Myco) {
+    const mycoBinaryPath = myco.argv[3];
+    const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
+
+    for (const mode of ["strict", "warn"]) {
+        const result = await mycoExec.exec(
+            ["run", "--unhandled-rejections", mode, "fixtures/rejects.ts"],
+            { inheritEnv },
+        );
+        console.log(`${mode}: exit code ${result.exit_code}`);
+        console.log(`  stdout: ${JSON.stringify(result.stdout().trim())}`);
+        console.log(`  stderr: ${result.stderr().split("\n")[0]}`);
+    }
+}
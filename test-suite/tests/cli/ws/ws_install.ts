@@ -7,6 +7,7 @@ export default async function(myco: Myco) {
                 
     // Get an exec token for the myco binary
     const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
     
     try {
         // Change to the test fixture directory
@@ -23,7 +24,7 @@ export default async function(myco: Myco) {
         await monorepoDir.remove("./test-suite/myco-local.toml");
         
         // Execute workspace install command
-        const result = await mycoExec.exec(["ws", "install", "--save"]);
+        const result = await mycoExec.exec(["ws", "install", "--save"], { inheritEnv });
         
         if (result.exit_code !== 0) {
             console.error("Command failed:", result.stderr());
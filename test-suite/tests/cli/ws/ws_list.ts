@@ -7,13 +7,14 @@ export default async function(myco: Myco) {
                 
     // Get an exec token for the myco binary
     const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
     
     try {
         // Change to the test fixture directory
         myco.files.chdir("./fixtures/monorepo");
         
         // Execute workspace list command
-        const result = await mycoExec.exec(["ws", "list"]);
+        const result = await mycoExec.exec(["ws", "list"], { inheritEnv });
         
         if (result.exit_code !== 0) {
             console.error("Command failed:", result.stderr());
@@ -7,13 +7,14 @@ export default async function(myco: Myco) {
                 
     // Get an exec token for the myco binary
     const mycoExec = await myco.files.requestExec(mycoBinaryPath);
+    const inheritEnv = [await myco.env.request("PATH"), await myco.env.request("HOME")];
     
     try {
         // Change to the test fixture directory
         myco.files.chdir("./fixtures/monorepo");
         
         // Execute workspace run test command (runs in all packages that define 'test')
-        const result = await mycoExec.exec(["ws", "run", "test"]);
+        const result = await mycoExec.exec(["ws", "run", "test"], { inheritEnv });
         
         if (result.exit_code !== 0) {
             console.error("Command failed:", result.stderr());
@@ -0,0 +1,13 @@
+export default async function (myco: Myco) {
+    const parent = myco.workers.parent!;
+    const [input] = parent.capabilities as [Myco.Files.ReadToken];
+    parent.postMessage({ ready: (await input.read()).trim() });
+
+    const stop = parent.onMessage((message) => {
+        if (message === "stop") {
+            stop();
+            return;
+        }
+        parent.postMessage({ echo: message, bytes: message.bytes.length });
+    });
+}
@@ -0,0 +1,5 @@
+export default async function (myco: Myco) {
+    const parent = myco.workers.parent!;
+    const [path] = parent.capabilities as [Myco.Env.Token];
+    parent.postMessage({ hasPath: typeof path.get() === "string" });
+}
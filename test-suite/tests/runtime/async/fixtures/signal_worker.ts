@@ -0,0 +1,9 @@
+export default async function (myco: Myco) {
+    const parent = myco.workers.parent!;
+    try {
+        myco.process.onSignal("SIGUSR1", () => {});
+        parent.postMessage("listening");
+    } catch (e: any) {
+        parent.postMessage(e.message);
+    }
+}
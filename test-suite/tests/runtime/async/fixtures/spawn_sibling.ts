@@ -0,0 +1,4 @@
+// Spawns a worker next to this module, whatever the working directory is
+export function spawnSibling(myco: Myco, name: string): Myco.Workers.Worker {
+    return myco.workers.spawn(`./${name}`);
+}
@@ -0,0 +1,51 @@
+export default async function (myco: Myco) {
+    console.log("Testing setInterval");
+    await new Promise<void>((resolve) => {
+        let ticks = 0;
+        const interval = myco.setInterval(() => {
+            ticks++;
+            console.log("Tick", ticks);
+            if (ticks === 3) {
+                myco.clearInterval(interval);
+                resolve();
+            }
+        }, 10);
+    });
+
+    console.log("Testing queueMicrotask and setImmediate");
+    const order: string[] = [];
+    await new Promise<void>((resolve) => {
+        myco.setImmediate(() => {
+            order.push("immediate");
+            resolve();
+        });
+        myco.queueMicrotask(() => order.push("microtask"));
+        order.push("sync");
+    });
+    console.log("Order:", order.join(","));
+
+    console.log("Testing setImmediate ordering");
+    const turns: string[] = [];
+    await new Promise<void>((resolve) => {
+        myco.setTimeout(() => turns.push("timeout"), 0);
+        const cleared = myco.setImmediate(() => turns.push("cleared"));
+        myco.setImmediate(() => {
+            turns.push("immediate");
+            myco.setImmediate(() => {
+                turns.push("nested immediate");
+                resolve();
+            });
+        });
+        myco.clearImmediate(cleared);
+    });
+    console.log("Turns:", turns.join(","));
+
+    // An unref'd heartbeat must not keep the process alive
+    const heartbeat = myco.setInterval(() => console.log("Heartbeat"), 1000).unref();
+    const watchdog = myco.setTimeout(() => console.log("Unref'd timeout fired"), 1000);
+    console.log("Refed by default:", watchdog.hasRef());
+    watchdog.unref().ref().unref();
+    console.log("Refed after unref:", heartbeat.hasRef(), watchdog.hasRef());
+    myco.setImmediate(() => console.log("Unref'd immediate ran")).unref();
+    console.log("Done");
+}
@@ -0,0 +1,28 @@
+export default async function(myco: Myco) {
+    console.log("Starting process lifecycle test");
+    console.log(`Has pid: ${myco.process.pid > 0}`);
+    console.log(`Has platform: ${myco.process.platform.length > 0}`);
+
+    console.log("Testing signal listener");
+    const sh = await myco.files.requestExec("sh");
+    const received = new Promise<string>(resolve => {
+        const stop = myco.process.onSignal("SIGUSR1", signal => {
+            stop();
+            resolve(signal);
+        });
+    });
+    await sh.exec(["-c", `kill -USR1 ${myco.process.pid}`]);
+    console.log(`Received: ${await received}`);
+
+    console.log("Testing exit");
+    myco.process.beforeExit(async code => {
+        await new Promise<void>(resolve => myco.setTimeout(resolve, 10));
+        console.log(`Flushed before exit with code ${code}`);
+    });
+    myco.process.beforeExit(() => {
+        throw new Error("hook failed");
+    });
+    myco.process.beforeExit(() => console.log("Second hook ran"));
+    await myco.process.exit(3);
+    console.log("Unreachable");
+}
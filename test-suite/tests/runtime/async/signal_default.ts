@@ -0,0 +1,22 @@
+export default async function(myco: Myco) {
+    console.log("Testing default signal action");
+    const sh = await myco.files.requestExec("sh");
+    const inheritEnv = [await myco.env.request("PATH")];
+
+    // Still running when the second signal arrives
+    const stopUsr1 = myco.process.onSignal("SIGUSR1", async () => {
+        stopUsr1();
+        await new Promise<void>(resolve => myco.setTimeout(resolve, 300));
+        console.log("Handler finished");
+    });
+    // Forwarded, but nothing listens by the time it arrives
+    myco.process.onSignal("SIGUSR2", () => {})();
+
+    myco.process.beforeExit(code => console.log(`Before exit with code ${code}`));
+    await sh.exec(
+        ["-c", `kill -USR1 ${myco.process.pid}; sleep 0.1; kill -USR2 ${myco.process.pid}`],
+        { inheritEnv },
+    );
+    await new Promise<void>(resolve => myco.setTimeout(resolve, 2000));
+    console.log("Unreachable");
+}
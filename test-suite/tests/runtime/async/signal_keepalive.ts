@@ -0,0 +1,16 @@
+export default async function(myco: Myco) {
+    console.log("Testing signal listener keeps the process alive");
+    const sh = await myco.files.requestExec("sh");
+    const inheritEnv = [await myco.env.request("PATH")];
+
+    const stop = myco.process.onSignal("SIGUSR1", async signal => {
+        await new Promise<void>(resolve => myco.setTimeout(resolve, 50));
+        console.log(`Handled ${signal} after main returned`);
+        stop();
+    });
+    await sh.exec(
+        ["-c", `(sleep 0.2; kill -USR1 ${myco.process.pid}) >/dev/null 2>&1 &`],
+        { inheritEnv },
+    );
+    console.log("Returning from main");
+}
@@ -113,6 +113,23 @@ export default async function (myco: Myco) {
     
     const result = await timedFunction();
     addLog(`Got result: ${result}`);
+
+    // Delays too long to schedule fire right away, as in Node
+    addLog("Testing overlong delays");
+    await new Promise<void>((resolve) => myco.setTimeout(resolve, Infinity));
+    await new Promise<void>((resolve) => myco.setTimeout(resolve, 1e20));
+    let ticks = 0;
+    await new Promise<void>((resolve) => {
+        const interval = myco.setInterval(() => {
+            if (++ticks === 3) {
+                myco.clearInterval(interval);
+                resolve();
+            }
+        }, Infinity);
+    });
+    const signal = AbortSignal.timeout(Infinity);
+    await new Promise((resolve) => signal.addEventListener("abort", resolve));
+    addLog(`Overlong delays fired, interval ticks: ${ticks}, aborted: ${signal.aborted}`);
     
     console.log("Timeout behavior test completed");
 } 
\ No newline at end of file
@@ -0,0 +1,18 @@
+export default async function (myco: Myco) {
+    console.log("Testing timer ordering");
+
+    const order: string[] = [];
+    await new Promise<void>((resolve) => {
+        myco.setTimeout(() => order.push("a"), 20);
+        const cancelled = myco.setTimeout(() => order.push("cancelled"), 20);
+        myco.setTimeout(() => order.push("b"), 20);
+        myco.setTimeout(() => order.push("early"), 5);
+        myco.clearTimeout(cancelled);
+        myco.setTimeout(resolve, 30);
+    });
+    console.log("Order:", order.join(","));
+
+    // A timer left behind keeps the process alive after the entry point returns
+    myco.setTimeout(() => console.log("Late timer fired"), 50);
+    console.log("Returning from main");
+}
@@ -0,0 +1,21 @@
+export default async function(myco: Myco) {
+    const tick = () => new Promise((resolve) => myco.setTimeout(resolve, 10));
+    const stop = myco.process.onUnhandledRejection((reason, promise) => {
+        const message = reason instanceof Error ? reason.message : reason;
+        console.log(`Unhandled: ${message} (promise: ${promise instanceof Promise})`);
+    });
+
+    Promise.reject(new Error("first"));
+    // A handler attached before the microtasks drain is in time
+    const late = Promise.reject(new Error("late"));
+    myco.queueMicrotask(() => late.catch(() => console.log("Caught in time")));
+    await tick();
+
+    myco.setTimeout(() => {
+        Promise.reject("from a timer");
+    }, 0);
+    await tick();
+
+    stop();
+    console.log("Listener removed");
+}
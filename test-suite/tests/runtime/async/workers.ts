@@ -0,0 +1,58 @@
+import { spawnSibling } from "./fixtures/spawn_sibling.ts";
+
+export default async function (myco: Myco) {
+    console.log("Testing workers");
+    const input = await myco.files.requestRead("./fixtures/worker_input.txt");
+    const worker = myco.workers.spawn("./fixtures/echo_worker.ts", { capabilities: [input] });
+
+    // The token moved to the worker
+    try {
+        await input.read();
+        console.log("Parent could still read the file");
+    } catch (e) {
+        console.log("Parent token revoked after transfer");
+    }
+
+    const messages: any[] = [];
+    await new Promise<void>((resolve) => {
+        worker.onMessage((message) => {
+            messages.push(message);
+            if (messages.length === 2) {
+                resolve();
+            }
+        });
+        worker.postMessage({ name: "ping", bytes: new Uint8Array([1, 2, 3]) });
+    });
+
+    console.log("Worker read:", messages[0].ready);
+    const reply = messages[1];
+    console.log("Echoed name:", reply.echo.name);
+    console.log("Echoed bytes:", reply.echo.bytes instanceof Uint8Array, reply.bytes);
+
+    worker.postMessage("stop");
+    console.log("Worker exited with", await worker.exited);
+
+    const stuck = myco.workers.spawn("./fixtures/echo_worker.ts", {
+        capabilities: [await myco.files.requestRead("./fixtures/worker_input.txt")],
+    });
+    stuck.terminate();
+    console.log("Terminated worker exited with", await stuck.exited);
+
+    // Resolved against the module that spawns it, not the working directory
+    const sibling = spawnSibling(myco, "signal_worker.ts");
+    const signalReply = await new Promise((resolve) => sibling.onMessage(resolve));
+    console.log("Worker signal listener:", signalReply);
+    console.log("Sibling worker exited with", await sibling.exited);
+
+    const path = await myco.env.request("PATH");
+    const envWorker = myco.workers.spawn("./fixtures/env_worker.ts", { capabilities: [path] });
+    const envReply: any = await new Promise((resolve) => envWorker.onMessage(resolve));
+    console.log("Worker read PATH:", envReply.hasPath);
+    try {
+        path.get();
+        console.log("Parent could still read PATH");
+    } catch (e) {
+        console.log("Parent env token revoked after transfer");
+    }
+    console.log("Env worker exited with", await envWorker.exited);
+}
@@ -0,0 +1,48 @@
+class Point {
+    constructor(public x: number, public y: number) {}
+}
+
+export default async function (myco: Myco) {
+    console.log("Testing inspect:");
+    console.log(new Map<string, unknown>([["origin", new Point(0, 0)], ["tags", new Set(["a", "b"])]]));
+    console.log({ missing: undefined, big: 10n, sym: Symbol("id"), nested: { a: { b: { c: { d: 1 } } } } });
+    const cycle: any = { name: "loop" };
+    cycle.self = cycle;
+    console.log(cycle);
+    console.log([1, , 3], new Uint8Array([1, 2, 3]), [function named() {}, class Shape {}]);
+    console.log(Promise.resolve(42), new Promise(() => {}));
+    console.log(Array.from({ length: 20 }, (_, i) => i * 3));
+    console.log(myco.inspect({ a: { b: { c: {} } } }, { depth: 0 }));
+    const error = Object.assign(new RangeError("out of range"), { code: "E_RANGE" });
+    console.log(myco.inspect(error).split("\n")[0]);
+    console.log(new URL("https://example.com/a?b=1").searchParams);
+
+    console.log("Testing format specifiers:");
+    console.log("%s is %d years old (%i, %f)", "Ada", 36.5, 36.5, "1.25");
+    console.log("json %j, object %o, 100%%, %c", { a: [1] }, { b: 2 }, "color: red", "and more");
+
+    console.log("Testing groups:");
+    console.group("Outer");
+    console.log("first\nsecond");
+    console.group();
+    console.info("nested");
+    console.groupEnd();
+    console.groupEnd();
+    console.log("back");
+
+    console.log("Testing count and time:");
+    console.count();
+    console.count("items");
+    console.count();
+    console.countReset();
+    console.count();
+    console.time("work");
+    console.timeEnd("work");
+    console.timeEnd("work");
+
+    console.log("Testing table and dir:");
+    console.table([{ name: "apple", price: 1.5 }, { name: "pear", stock: 3 }]);
+    console.table({ a: 1, b: "two" });
+    console.dir({ a: { b: { c: 1 } } }, { depth: 0 });
+    console.warn("Warning:", { level: 2 });
+}
@@ -25,7 +25,7 @@ export default function(Myco: any) {
     console.info("This is an info message");
     console.info("Info with details:", "version", "1.0.0");
 
-    // Test console.debug
+    // Test console.debug, which is hidden without --log-level debug
     console.debug("Testing console.debug:");
     console.debug("This is a debug message");
     console.debug("Debug data:", { timestamp: "2024-01-01T00:00:00.000Z" });
@@ -0,0 +1,76 @@
+export default async function (myco: Myco) {
+    console.log("Testing URL:");
+    const url = new URL("../docs?page=1#intro", "https://user@example.com:8080/guide/start");
+    console.log("href:", url.href);
+    console.log("origin:", url.origin);
+    console.log("pathname:", url.pathname);
+    url.searchParams.append("q", "a b");
+    console.log("after append:", url.search);
+    url.hash = "";
+    url.port = "443";
+    console.log("after setters:", url.href);
+    console.log("canParse:", URL.canParse("not a url"), URL.canParse("/x", "http://a"));
+    try {
+        new URL("not a url");
+    } catch (e) {
+        console.log("invalid URL throws TypeError:", e instanceof TypeError);
+    }
+
+    console.log("Testing URLSearchParams:");
+    const params = new URLSearchParams("b=2&a=1&b=3");
+    console.log("getAll b:", params.getAll("b").join(","));
+    params.sort();
+    params.set("c", "x&y");
+    console.log("serialized:", params.toString());
+
+    console.log("Testing Headers:");
+    const headers = new Headers({ "Content-Type": "text/plain" });
+    headers.append("Accept", "text/html");
+    headers.append("accept", "application/json");
+    console.log("accept:", headers.get("ACCEPT"));
+    console.log("names:", [...headers.keys()].join(","));
+
+    console.log("Testing AbortController:");
+    const controller = new AbortController();
+    const target = new EventTarget();
+    target.addEventListener("ping", () => console.log("ping received"), { signal: controller.signal });
+    target.dispatchEvent(new Event("ping"));
+    controller.signal.onabort = () => console.log("aborted with", controller.signal.reason.name);
+    controller.abort();
+    target.dispatchEvent(new Event("ping"));
+    try {
+        controller.signal.throwIfAborted();
+    } catch (e) {
+        console.log("throwIfAborted threw", e.name);
+    }
+    // The timeout does not keep the process alive by itself, so wait on a timer that does
+    const timeout = AbortSignal.timeout(10);
+    await new Promise((resolve) => myco.setTimeout(resolve, 50));
+    console.log("timeout reason:", timeout.reason.name);
+
+    console.log("Testing Blob:");
+    const blob = new Blob(["hello ", new TextEncoder().encode("world")], { type: "text/plain" });
+    console.log("size:", blob.size, "type:", blob.type);
+    console.log("text:", await blob.text());
+    console.log("slice:", await blob.slice(6).text());
+
+    console.log("Testing base64 and structuredClone:");
+    console.log("btoa:", btoa("Myco\xff"));
+    console.log("atob:", atob(" TXljbw "));
+    try {
+        btoa("☃");
+    } catch (e) {
+        console.log("btoa outside Latin-1 throws", e.name);
+    }
+    const original = { when: new Date(0), tags: new Set(["a"]), nested: { n: 1 } };
+    const copy = structuredClone(original);
+    console.log("clone is deep:", copy.nested !== original.nested, copy.when instanceof Date, copy.tags.has("a"));
+    try {
+        structuredClone({ f() {} });
+    } catch (e) {
+        console.log("cloning a function throws");
+    }
+
+    const start = performance.now();
+    console.log("performance.now is monotonic:", performance.now() >= start);
+}
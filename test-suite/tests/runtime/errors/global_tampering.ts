@@ -0,0 +1,36 @@
+export default async function (myco: Myco) {
+    const internals = Object.getOwnPropertyNames(globalThis).filter((name) => name.startsWith("__"));
+    console.log("Internal hooks on globalThis:", internals.length);
+    console.log("Timer completion hook:", typeof (globalThis as any).__mycoTimerComplete);
+
+    // Everything the runtime defines on globalThis is a public API. Builtins are
+    // native; the runtime's own functions, and objects holding them, are not.
+    const isRuntimeFunction = (value: unknown) =>
+        typeof value === "function" && !Function.prototype.toString.call(value).includes("[native code]");
+    const defined = Object.getOwnPropertyNames(globalThis).filter((name) => {
+        const value = (globalThis as any)[name];
+        if (typeof value === "function") {
+            return isRuntimeFunction(value);
+        }
+        return value !== null && typeof value === "object" && value !== globalThis
+            && Object.getOwnPropertyNames(value).some((key) => {
+                const property = Object.getOwnPropertyDescriptor(value, key);
+                return property !== undefined && isRuntimeFunction(property.value);
+            });
+    });
+    console.log("Runtime globals:", defined.sort().join(", "));
+
+    // Replacing globals must not change what the runtime hands back
+    const OriginalError = Error;
+    (globalThis as any).Error = function FakeError() {
+        return { fake: true };
+    };
+    try {
+        TOML.parse("this is = = not toml");
+        console.log("FAIL: invalid TOML parsed");
+    } catch (e) {
+        console.log("Runtime error is a real Error:", e instanceof OriginalError);
+    } finally {
+        (globalThis as any).Error = OriginalError;
+    }
+}
@@ -0,0 +1,10 @@
+export default async function(myco: Myco) {
+    console.log("Testing exception in a timer");
+
+    myco.setTimeout(() => {
+        throw new Error("Timer callback failed");
+    }, 10);
+
+    await new Promise((resolve) => myco.setTimeout(resolve, 50));
+    console.log("ERROR: Should have failed before this");
+}
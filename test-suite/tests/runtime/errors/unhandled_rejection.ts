@@ -0,0 +1,9 @@
+export default async function(myco: Myco) {
+    console.log("Testing unhandled rejection");
+
+    // Nobody awaits this promise or attaches a handler to it
+    Promise.reject(new Error("Nobody handled this"));
+
+    await new Promise((resolve) => myco.setTimeout(resolve, 50));
+    console.log("ERROR: Should have failed before this");
+}
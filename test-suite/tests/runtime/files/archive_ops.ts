@@ -0,0 +1,89 @@
+// Builds a stored zip containing a single empty entry, so tests can craft hostile entry names
+function emptyEntryZip(name: string): Uint8Array {
+    const nameBytes = new TextEncoder().encode(name);
+    const local = 30 + nameBytes.length;
+    const central = 46 + nameBytes.length;
+    const bytes = new Uint8Array(local + central + 22);
+    const view = new DataView(bytes.buffer);
+
+    view.setUint32(0, 0x04034b50, true);
+    view.setUint16(4, 20, true);
+    view.setUint16(26, nameBytes.length, true);
+    bytes.set(nameBytes, 30);
+
+    view.setUint32(local, 0x02014b50, true);
+    view.setUint16(local + 4, 20, true);
+    view.setUint16(local + 6, 20, true);
+    view.setUint16(local + 28, nameBytes.length, true);
+    bytes.set(nameBytes, local + 46);
+
+    const end = local + central;
+    view.setUint32(end, 0x06054b50, true);
+    view.setUint16(end + 8, 1, true);
+    view.setUint16(end + 10, 1, true);
+    view.setUint32(end + 12, central, true);
+    view.setUint32(end + 16, local, true);
+    return bytes;
+}
+
+export default async function(myco: Myco) {
+    console.log("Starting archive test");
+
+    const dirToken = await myco.files.requestReadWriteDir("./fixtures/tmp");
+    await dirToken.mkdirp("bundle/lib");
+    await dirToken.write("bundle/index.js", "export default 1;");
+    await dirToken.write("bundle/lib/util.js", "export const util = 2;");
+
+    console.log("Testing zip");
+    const archiveToken = await myco.files.requestReadWrite("./fixtures/tmp/bundle.zip");
+    await myco.archive.zip(dirToken, "bundle", archiveToken);
+    const stats = await archiveToken.stat();
+    console.log(`Archive created: ${stats !== null && stats.size > 0}`);
+
+    console.log("Testing unzip");
+    const names = await myco.archive.unzip(archiveToken, dirToken, "extracted");
+    console.log(`Entries: ${names.sort().join(', ')}`);
+    console.log(`Extracted: ${await dirToken.read("extracted/lib/util.js")}`);
+
+    console.log("Testing sync zip and unzip");
+    const syncArchive = await myco.files.requestReadWrite("./fixtures/tmp/sync.zip");
+    myco.archive.sync.zip(dirToken, "bundle/lib", syncArchive);
+    const syncNames = myco.archive.sync.unzip(syncArchive, dirToken, "sync-out");
+    console.log(`Sync entries: ${syncNames.join(', ')}`);
+
+    console.log("Testing path traversal");
+    const evilToken = await myco.files.requestReadWrite("./fixtures/tmp/evil.zip");
+    await evilToken.write(emptyEntryZip("../escaped.txt"));
+    try {
+        await myco.archive.unzip(evilToken, dirToken, "evil-out");
+        console.log("Traversal accepted");
+    } catch (e) {
+        console.log("Traversal rejected");
+    }
+    console.log(`Escaped file exists: ${(await dirToken.stat("escaped.txt")) !== null}`);
+
+    console.log("Testing symlinked directory");
+    await dirToken.mkdirp("outside");
+    await dirToken.mkdirp("slip-out");
+    const sh = await myco.files.requestExec("sh");
+    sh.sync.exec(["-c", "ln -sfn ../outside ./fixtures/tmp/slip-out/link"]);
+    const slipToken = await myco.files.requestReadWrite("./fixtures/tmp/slip.zip");
+    await slipToken.write(emptyEntryZip("link/sub/file.txt"));
+    try {
+        await myco.archive.unzip(slipToken, dirToken, "slip-out");
+        console.log("Symlinked directory accepted");
+    } catch (e) {
+        console.log("Symlinked directory rejected");
+    }
+    console.log(`Directory created through symlink: ${(await dirToken.stat("outside/sub")) !== null}`);
+
+    console.log("Testing wrong token kind");
+    try {
+        await myco.archive.zip({} as any, "bundle", archiveToken);
+        console.log("Forged token accepted");
+    } catch (e) {
+        console.log(`Forged token rejected: ${(e as Error).message}`);
+    }
+
+    console.log("Archive test completed");
+}
@@ -0,0 +1,47 @@
+export default async function(myco: Myco) {
+    console.log("Starting atomic write and lock test");
+
+    const dirToken = await myco.files.requestReadWriteDir("./fixtures/tmp");
+
+    console.log("Testing atomic write");
+    await dirToken.write("config.json", '{"version":1}');
+    await dirToken.write("config.json", '{"version":2}', { atomic: true });
+    console.log(`Contents: ${await dirToken.read("config.json")}`);
+    dirToken.sync.write("config.json", '{"version":3}', { atomic: true });
+    console.log(`Sync contents: ${dirToken.sync.read("config.json")}`);
+
+    const leftovers = (await dirToken.list(".")).filter(f => f.name.endsWith(".tmp"));
+    console.log(`Temp files left: ${leftovers.length}`);
+
+    console.log("Testing file token atomic write");
+    const fileToken = await myco.files.requestReadWrite("./fixtures/tmp/state.txt");
+    await fileToken.write("hello", { atomic: true });
+    console.log(`File contents: ${await fileToken.read()}`);
+
+    console.log("Testing lock");
+    const lock = await dirToken.lock("state.txt");
+    const contended = await dirToken.tryLock("state.txt");
+    console.log(`tryLock while held: ${contended === null ? "null" : "acquired"}`);
+    lock.release();
+    lock.release();
+
+    const relocked = dirToken.sync.tryLock("state.txt");
+    console.log(`tryLock after release: ${relocked === null ? "null" : "acquired"}`);
+    relocked?.release();
+
+    console.log("Testing lock survives atomic replace");
+    const guard = dirToken.sync.lock("state.txt");
+    await fileToken.write("replaced", { atomic: true });
+    const duringReplace = await dirToken.tryLock("state.txt");
+    console.log(`tryLock while held across replace: ${duringReplace === null ? "null" : "acquired"}`);
+    duringReplace?.release();
+    guard.release();
+    const afterReplace = await dirToken.tryLock("state.txt");
+    console.log(`tryLock after replace: ${afterReplace === null ? "null" : "acquired"}`);
+    afterReplace?.release();
+
+    // The lock file is not part of what a single-file token grants
+    console.log(`File token can lock: ${"lock" in fileToken}`);
+
+    console.log("Atomic write and lock test completed");
+}
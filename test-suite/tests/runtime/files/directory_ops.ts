@@ -100,6 +100,12 @@ export default async function(myco: Myco) {
     const deepFiles = await readDirToken.list("deep", { recursive: true, include_dirs: false });
     const deepFileNames = deepFiles.map(f => f.name).sort();
     console.log(`Files in deep structure: ${deepFileNames.join(', ')}`);
+
+    // Recursive listings are depth first in file name order, each directory right before its contents
+    const deepOrder = await readDirToken.list("deep", { recursive: true });
+    console.log(`Recursive order: ${deepOrder.map(f => f.name).join(', ')}`);
+    const deepOrderSync = readDirToken.sync.list("deep", { recursive: true });
+    console.log(`Sync recursive order matches: ${deepOrderSync.map(f => f.name).join(', ') === deepOrder.map(f => f.name).join(', ')}`);
     
     // Test recursive removal
     await writeDirToken.rmdirRecursive("deep");
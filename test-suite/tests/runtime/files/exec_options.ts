@@ -0,0 +1,58 @@
+export default async function(myco: Myco) {
+    console.log("Starting exec options test");
+
+    const sh = await myco.files.requestExec("sh");
+
+    console.log("Testing environment");
+    const clean = await sh.exec(["-c", 'echo "${HOME:-unset} $FOO"'], { env: { FOO: "bar" } });
+    console.log(`Env: ${clean.stdout().trim()}`);
+    const path = await myco.env.request("PATH");
+    console.log(`Env token reads PATH: ${path.get() !== undefined}`);
+    const inherited = await sh.exec(["-c", 'test -n "$PATH" && echo has-path'], { inheritEnv: [path] });
+    console.log(`Inherited: ${inherited.stdout().trim()}`);
+    try {
+        await sh.exec(["-c", "echo $HOME"], { inheritEnv: ["HOME" as any] });
+        console.log("Ungranted variable inherited");
+    } catch (e) {
+        console.log(`Ungranted variable rejected: ${(e as Error).message}`);
+    }
+    try {
+        sh.spawn(["-c", "echo $HOME"], { inheritEnv: [{ get: () => "forged" }] });
+        console.log("Forged env token accepted");
+    } catch (e) {
+        console.log(`Forged env token rejected: ${(e as Error).message}`);
+    }
+    const spawned = sh.spawn(["-c", 'echo "$GREETING"'], { env: { GREETING: "hi" } });
+    console.log(`Spawn env: ${(await spawned.stdout.text()).trim()}`);
+
+    console.log("Testing cwd");
+    const tmp = await myco.files.requestReadDir("./fixtures/tmp");
+    const inTmp = sh.sync.exec(["-c", 'echo "${PWD##*/}"'], { cwd: tmp });
+    console.log(`Cwd: ${inTmp.stdout().trim()}`);
+    try {
+        await sh.exec(["-c", "true"], { cwd: sh as any });
+        console.log("Non-directory cwd accepted");
+    } catch (e) {
+        console.log(`Non-directory cwd rejected: ${(e as Error).message}`);
+    }
+
+    console.log("Testing timeout");
+    try {
+        await sh.exec(["-c", "sleep 5"], { inheritEnv: [path], timeout: 100 });
+        console.log("Timeout not enforced");
+    } catch (e) {
+        console.log(`Timed out: ${(e as Error).message.includes("timed out after 100 ms")}`);
+    }
+    const quick = await sh.exec(["-c", "echo fast"], { timeout: 5000 });
+    console.log(`Within timeout: ${quick.stdout().trim()}`);
+
+    console.log("Testing output cap");
+    try {
+        sh.sync.exec(["-c", "while :; do echo xxxxxxxxxxxxxxxx; done"], { maxOutput: 1000 });
+        console.log("Output cap not enforced");
+    } catch (e) {
+        console.log(`Capped: ${(e as Error).message.includes("output exceeded 1000 bytes")}`);
+    }
+
+    console.log("Exec options test completed");
+}
@@ -0,0 +1,71 @@
+export default async function(myco: Myco) {
+    console.log("Starting glob test");
+
+    const writeDirToken = await myco.files.requestWriteDir("./fixtures/tmp");
+    await writeDirToken.mkdirp("src/nested");
+    await writeDirToken.mkdirp("build");
+    await writeDirToken.mkdirp("node_modules/dep");
+    await writeDirToken.mkdirp("many");
+    await writeDirToken.write("src/index.ts", "export {}");
+    await writeDirToken.write("src/util.ts", "export {}");
+    await writeDirToken.write("src/README.md", "# readme");
+    await writeDirToken.write("src/nested/deep.ts", "export {}");
+    await writeDirToken.write("build/out.js", "");
+    await writeDirToken.write("node_modules/dep/index.ts", "export {}");
+    await writeDirToken.write(".gitignore", "build/\n*.md\n");
+
+    const readDirToken = await myco.files.requestReadDir("./fixtures/tmp");
+
+    const names = (files: Myco.Files.File[]) => files.map(f => f.name).sort().join(', ');
+    const collect = async (files: AsyncIterable<Myco.Files.File>) => {
+        const result: Myco.Files.File[] = [];
+        for await (const file of files) {
+            result.push(file);
+        }
+        return result;
+    };
+
+    console.log("Testing glob with gitignore");
+    console.log(`All files: ${names(await collect(readDirToken.glob("**/*")))}`);
+
+    console.log("Testing single-segment wildcard");
+    console.log(`Top-level ts: ${names(await collect(readDirToken.glob("src/*.ts")))}`);
+
+    console.log("Testing ignore patterns");
+    console.log(`Ignored node_modules: ${names(await collect(readDirToken.glob("**/*.ts", { ignore: ["node_modules"] })))}`);
+
+    console.log("Testing gitignore disabled");
+    console.log(`Markdown: ${names(await collect(readDirToken.glob("**/*.md", { gitignore: false })))}`);
+
+    console.log("Testing cwd option");
+    console.log(`Relative to src: ${names(await collect(readDirToken.glob("**/*.ts", { cwd: "src" })))}`);
+
+    console.log("Testing streaming order");
+    console.log(`Streamed: ${(await collect(readDirToken.glob("src/**/*.ts"))).map(f => f.name).join(', ')}`);
+
+    console.log("Testing early break");
+    for (let i = 0; i < 300; i++) {
+        await writeDirToken.write(`many/file${String(i).padStart(3, '0')}.txt`, "");
+    }
+    let seen = 0;
+    for await (const file of readDirToken.glob("many/*.txt")) {
+        if (++seen === 3) {
+            console.log(`Stopped after ${seen}, at ${file.name}`);
+            break;
+        }
+    }
+    console.log(`Walk again after break: ${(await collect(readDirToken.glob("many/*.txt"))).length}`);
+
+    console.log("Testing sync glob");
+    console.log(`Sync: ${names(readDirToken.sync.glob("build/*", { gitignore: false }))}`);
+
+    console.log("Testing invalid pattern");
+    try {
+        await collect(readDirToken.glob("src/["));
+        console.log("ERROR: invalid pattern was accepted");
+    } catch (e) {
+        console.log("Invalid pattern rejected");
+    }
+
+    console.log("Glob test completed");
+}
@@ -0,0 +1,30 @@
+export default async function(myco: Myco) {
+    console.log("Starting hash test");
+
+    const hex = (bytes: Uint8Array) => Array.from(bytes, b => b.toString(16).padStart(2, '0')).join('');
+
+    console.log("Testing digest");
+    console.log(`sha1: ${hex(await myco.crypto.digest("sha1", "abc"))}`);
+    console.log(`SHA-256: ${hex(await myco.crypto.digest("SHA-256", new TextEncoder().encode("abc")))}`);
+    console.log(`sha512 prefix: ${hex(myco.crypto.sync.digest("sha512", "abc")).slice(0, 32)}`);
+    console.log(`blake3: ${hex(myco.crypto.sync.digest("blake3", "abc"))}`);
+
+    console.log("Testing file hash");
+    const dirToken = await myco.files.requestReadWriteDir("./fixtures/tmp");
+    await dirToken.write("artifact.bin", "abc");
+    console.log(`Dir hash: ${await dirToken.hash("artifact.bin")}`);
+    console.log(`Dir sync blake3: ${dirToken.sync.hash("artifact.bin", "blake3")}`);
+
+    const fileToken = await myco.files.requestRead("./fixtures/tmp/artifact.bin");
+    console.log(`File sha1: ${await fileToken.hash("sha1")}`);
+
+    console.log("Testing unsupported algorithm");
+    try {
+        await myco.crypto.digest("md5" as any, "abc");
+        console.log("md5 accepted");
+    } catch (e) {
+        console.log("md5 rejected");
+    }
+
+    console.log("Hash test completed");
+}
@@ -0,0 +1,47 @@
+export default async function(myco: Myco) {
+    console.log("Starting sandbox test");
+
+    const tmp = await myco.files.requestWriteDir("./fixtures/tmp");
+    await tmp.mkdirp("allowed");
+    await tmp.write("allowed/data.txt", "granted contents");
+    await tmp.write("secret.txt", "secret contents");
+
+    const cat = await myco.files.requestExec("cat");
+    const allowed = await myco.files.requestReadDir("./fixtures/tmp/allowed");
+
+    console.log("Testing granted read");
+    const granted = await cat.exec(["./fixtures/tmp/allowed/data.txt"], { grant: [allowed] });
+    console.log(`Exit code: ${granted.exit_code}, stdout: ${granted.stdout()}`);
+
+    console.log("Testing denied read");
+    const denied = await cat.exec(["./fixtures/tmp/secret.txt"], { grant: [allowed] });
+    console.log(`Exit code: ${denied.exit_code}, stdout: ${denied.stdout()}`);
+
+    console.log("Testing unsandboxed read");
+    const open = await cat.exec(["./fixtures/tmp/secret.txt"]);
+    console.log(`Exit code: ${open.exit_code}, stdout: ${open.stdout()}`);
+
+    console.log("Testing granted write");
+    const sh = await myco.files.requestExec("sh");
+    const outDir = await myco.files.requestWriteDir("./fixtures/tmp/allowed");
+    const write = sh.sync.exec(["-c", "echo written > ./fixtures/tmp/allowed/out.txt && echo ok"], { grant: [outDir] });
+    console.log(`Write exit code: ${write.exit_code}, stdout: ${write.stdout().trim()}`);
+    const blocked = sh.sync.exec(["-c", "echo written > ./fixtures/tmp/blocked.txt || echo blocked"], { grant: [outDir] });
+    console.log(`Blocked write: ${blocked.stdout().trim()}`);
+
+    console.log("Testing baseline paths");
+    const baseline = sh.sync.exec(["-c", "cat /etc/passwd /proc/self/status > /dev/null && ls /tmp > /dev/null && echo readable"], { grant: [outDir] });
+    console.log(`Baseline read: ${baseline.stdout().trim()}`);
+    const tmpWrite = sh.sync.exec(["-c", "echo written > /tmp/myco-sandbox-test || echo read-only"], { grant: [outDir] });
+    console.log(`Baseline write: ${tmpWrite.stdout().trim()}`);
+
+    console.log("Testing invalid grant");
+    try {
+        await cat.exec([], { grant: [{}] });
+        console.log("Invalid grant accepted");
+    } catch (e) {
+        console.log(`Invalid grant rejected: ${(e as Error).message}`);
+    }
+
+    console.log("Sandbox test completed");
+}
@@ -0,0 +1,54 @@
+export default async function(myco: Myco) {
+    console.log("Starting spawn test");
+
+    console.log("Testing stdin piping");
+    const cat = await myco.files.requestExec("cat");
+    const child = cat.spawn();
+    console.log(`Has pid: ${child.pid > 0}`);
+    await child.stdin.write("hello ");
+    await child.stdin.write(new TextEncoder().encode("from stdin"));
+    child.stdin.close();
+    console.log(`Stdout: ${await child.stdout.text()}`);
+    const status = await child.wait();
+    console.log(`Exit code: ${status.exit_code}`);
+
+    console.log("Testing incremental output");
+    const sh = await myco.files.requestExec("sh");
+    const lines = sh.spawn(["-c", "echo one; sleep 0.1; echo two; echo oops >&2; exit 3"]);
+    let chunks = 0;
+    let output = "";
+    for await (const chunk of lines.stdout) {
+        chunks++;
+        output += new TextDecoder().decode(chunk);
+    }
+    console.log(`Output: ${output.trim().split("\n").join(", ")}`);
+    console.log(`Read in chunks: ${chunks >= 2}`);
+    console.log(`Stderr: ${(await lines.stderr.text()).trim()}`);
+    console.log(`Exit code: ${(await lines.wait()).exit_code}`);
+
+    console.log("Testing kill");
+    const sleeper = sh.spawn(["-c", "exec sleep 30"], { stdin: "null" });
+    sleeper.kill();
+    const killed = await sleeper.wait();
+    console.log(`Killed exit code: ${killed.exit_code}, signal: ${killed.signal}`);
+    sleeper.kill("SIGKILL");
+
+    console.log("Testing output after exit");
+    const late = sh.spawn(["-c", "echo late"], { stdin: "null", stderr: "null" });
+    const lateStatus = await late.wait();
+    console.log(`Exit code: ${lateStatus.exit_code}, stdout: ${(await late.stdout.text()).trim()}`);
+    console.log(`Waited again: ${(await late.wait()).exit_code}`);
+    console.log(`Read after release: ${await late.stdout.read()}`);
+    late.kill();
+    console.log("Kill after release ignored");
+
+    console.log("Testing invalid signal");
+    try {
+        cat.spawn().kill("SIGNOPE" as any);
+        console.log("Invalid signal accepted");
+    } catch (e) {
+        console.log("Invalid signal rejected");
+    }
+
+    console.log("Spawn test completed");
+}
@@ -0,0 +1,7 @@
+import { greet } from "greeter";
+import { legacyDep } from "legacy";
+
+export default async function (myco: Myco) {
+    const { shout } = await import("greeter/shout.ts");
+    myco.workers.parent!.postMessage(`${greet("worker")}, ${shout(legacyDep)}`);
+}
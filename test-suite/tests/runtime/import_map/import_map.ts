@@ -0,0 +1,14 @@
+import { greet } from "greeter";
+import { shout } from "greeter/shout.ts";
+import { version } from "dep";
+import { legacyDep } from "legacy";
+
+export default async function (_myco: Myco) {
+    console.log(greet("import maps"));
+    console.log(shout("prefix mappings"));
+    console.log(`top level uses ${version}`);
+    console.log(`scoped package uses ${legacyDep}`);
+
+    const dynamic = await import("greeter/shout.ts");
+    console.log(dynamic.shout("dynamic imports too"));
+}
@@ -0,0 +1 @@
+export const version = "dep 2";
@@ -0,0 +1,3 @@
+import { version } from "dep";
+
+export const legacyDep = version;
@@ -0,0 +1,6 @@
+// Workers resolve imports through the same import map as their parent
+export default async function (myco: Myco) {
+    const worker = myco.workers.spawn("./fixtures/import_worker.ts");
+    console.log(await new Promise((resolve) => worker.onMessage(resolve)));
+    console.log("Worker exited with", await worker.exited);
+}
@@ -0,0 +1,5 @@
+// Workers resolve the aliases in myco-local.toml like their parent does
+export default async function (myco: Myco) {
+    const worker = myco.workers.spawn("./fixtures/alias_worker.ts");
+    console.log("Worker exited with", await worker.exited);
+}
@@ -0,0 +1,24 @@
+import math, { add, double, name } from "./fixtures/commonjs/math.cjs";
+import greeting from "./fixtures/commonjs/uses_esm.cjs";
+import cycle from "./fixtures/commonjs/cycle_a.cjs";
+import counter from "./fixtures/commonjs/counter.cjs";
+import shared from "./fixtures/commonjs/shares_counter.cjs";
+
+export default async function (myco: Myco) {
+    console.log(`${add(2, 3)} ${double(4)} ${name}`);
+    console.log(`default is module.exports: ${math.add === add}`);
+    console.log(greeting);
+    console.log(cycle.fromB);
+
+    // Importing and requiring a file give the same exports
+    counter.increment();
+    console.log(`shared count ${shared.increment()}`);
+
+    for (const file of ["builtin.cjs", "requires_tla.cjs"]) {
+        try {
+            await import(`./fixtures/commonjs/${file}`);
+        } catch (e) {
+            console.log((e as Error).message);
+        }
+    }
+}
@@ -0,0 +1,26 @@
+import { increment } from "./fixtures/dynamic/counter.ts";
+
+export default async function (myco: Myco) {
+    // Dynamic imports resolve against the importing module, not the working directory
+    myco.files.chdir("/");
+
+    // A module imported both ways is the same instance
+    const counter = await import("./fixtures/dynamic/counter.ts");
+    increment();
+    console.log(`shared instance count ${counter.count}`);
+
+    const nested = await import("./fixtures/dynamic/nested.ts");
+    console.log(await nested.load());
+
+    const { ready } = await import("./fixtures/dynamic/tla.ts");
+    console.log(ready);
+
+    const aliased = await import("@fixture/workspace/file.ts");
+    aliased.file();
+
+    try {
+        await import("./fixtures/dynamic/throws.ts");
+    } catch (e) {
+        console.log(`caught: ${(e as Error).message}`);
+    }
+}
@@ -0,0 +1,7 @@
+import { index } from "@fixture/workspace";
+import { file } from "@fixture/workspace/file.ts";
+
+export default function () {
+    file();
+    index();
+}
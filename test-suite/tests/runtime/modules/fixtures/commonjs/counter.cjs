@@ -0,0 +1,5 @@
+let count = 0;
+
+module.exports = {
+    increment: () => ++count,
+};
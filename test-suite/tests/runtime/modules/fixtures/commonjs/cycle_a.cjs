@@ -0,0 +1,3 @@
+exports.name = "a";
+const b = require("./cycle_b.cjs");
+exports.fromB = b.seen;
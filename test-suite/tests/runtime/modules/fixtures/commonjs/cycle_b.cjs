@@ -0,0 +1,2 @@
+const a = require("./cycle_a.cjs");
+exports.seen = `b saw ${a.name} while it was loading`;
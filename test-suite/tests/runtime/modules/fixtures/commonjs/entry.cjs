@@ -0,0 +1,3 @@
+module.exports = function (myco) {
+    console.log(`CommonJS entry point got ${typeof myco}`);
+};
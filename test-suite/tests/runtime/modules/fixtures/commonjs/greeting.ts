@@ -0,0 +1 @@
+export const greeting = "required an ES module";
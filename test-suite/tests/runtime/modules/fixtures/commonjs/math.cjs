@@ -0,0 +1,6 @@
+const { double } = require("./helpers/double");
+const config = require("./config.json");
+
+exports.add = (a, b) => a + b;
+exports.double = double;
+exports.name = config.name;
@@ -0,0 +1 @@
+require("./tla.ts");
@@ -0,0 +1,5 @@
+export async function load(): Promise<string> {
+    // Relative to this file, not to the module that imported it
+    const { count } = await import("./counter.ts");
+    return `nested import sees count ${count}`;
+}
@@ -0,0 +1,3 @@
+await new Promise<void>((resolve) => setTimeout(resolve, 10));
+
+throw new Error("failed during top-level await");
@@ -0,0 +1,3 @@
+await new Promise<void>((resolve) => setTimeout(resolve, 10));
+
+export const ready = "top-level await finished";
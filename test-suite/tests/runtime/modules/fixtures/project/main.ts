@@ -0,0 +1,18 @@
+import own from "./own.json" with { type: "json" };
+
+// This project is nested in the modules test project, whose JSON is outside it
+export default async function (_myco: Myco) {
+    console.log(own.name);
+
+    try {
+        await import("../json/config.json", { with: { type: "json" } });
+    } catch (e) {
+        console.log((e as Error).message);
+    }
+
+    try {
+        await import("./requires_json.cjs");
+    } catch (e) {
+        console.log((e as Error).message);
+    }
+}
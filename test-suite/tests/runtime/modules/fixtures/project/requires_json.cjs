@@ -0,0 +1 @@
+module.exports = require("../json/config.json");
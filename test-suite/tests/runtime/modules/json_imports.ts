@@ -0,0 +1,7 @@
+import config from "./fixtures/json/config.json" with { type: "json" };
+
+export default function (_myco: Myco) {
+    console.log(config.name, config.version);
+    console.log(config.features.join(", "));
+    console.log(config.nested.enabled);
+}
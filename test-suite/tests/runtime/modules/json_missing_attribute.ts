@@ -0,0 +1,5 @@
+import config from "./fixtures/json/config.json";
+
+export default function (_myco: Myco) {
+    console.log(config.name);
+}
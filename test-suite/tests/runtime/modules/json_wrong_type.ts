@@ -0,0 +1,5 @@
+import { file } from "./fixtures/workspace/file.ts" with { type: "json" };
+
+export default function (_myco: Myco) {
+    file();
+}
@@ -0,0 +1,4 @@
+import {report} from "@test/report";
+
+// A dependency imports its own dependencies by name, and they import theirs
+console.log(report("chained"));
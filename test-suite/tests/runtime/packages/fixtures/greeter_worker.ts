@@ -0,0 +1,5 @@
+import {greet} from "@test/greeter";
+
+export default function (myco: Myco) {
+    myco.workers.parent!.postMessage(greet("worker"));
+}
@@ -0,0 +1,42 @@
+import {greet} from "@test/greeter";
+import {shout} from "@test/greeter/loud";
+import legacy, {echo} from "@test/legacy";
+
+console.log(greet("packages"));
+console.log(shout("exports"));
+
+// The package declares its .js files CommonJS
+console.log(echo("legacy"), legacy.format);
+
+// Only what the package exports can be imported
+try {
+    await import("@test/greeter/internal.ts");
+} catch (e) {
+    console.log((e as Error).message);
+}
+
+// A dependency's own dependencies are not the project's
+try {
+    await import("@test/strings");
+} catch (e) {
+    console.log((e as Error).message);
+}
+
+try {
+    await import("@test/missing");
+} catch (e) {
+    console.log((e as Error).message);
+}
+
+// Nor can a package's files be reached by path, from outside it or from inside it
+try {
+    await import("./vendor/@test/greeter/internal.ts");
+} catch (e) {
+    console.log((e as Error).message);
+}
+
+try {
+    await import("@test/greeter/sneaky");
+} catch (e) {
+    console.log((e as Error).message);
+}
@@ -0,0 +1,6 @@
+// Workers import installed packages by name like their parent does
+export default async function (myco: Myco) {
+    const worker = myco.workers.spawn("./fixtures/greeter_worker.ts");
+    console.log(await new Promise((resolve) => worker.onMessage(resolve)));
+    console.log("Worker exited with", await worker.exited);
+}
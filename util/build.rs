@@ -0,0 +1,38 @@
+use std::path::Path;
+use std::{env, fs};
+
+/// Records the versions of the swc crates the transpiler is built with, read from the
+/// workspace lockfile, so that its output can be cached per swc release.
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let lockfile = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file());
+
+    let versions = match &lockfile {
+        Some(path) => {
+            println!("cargo:rerun-if-changed={}", path.display());
+            swc_versions(&fs::read_to_string(path).expect("Failed to read Cargo.lock"))
+        }
+        None => "unknown".to_string(),
+    };
+    println!("cargo:rustc-env=SWC_VERSIONS={versions}");
+}
+
+/// Every `swc_*` package in the lockfile, as `name@version` joined by commas.
+fn swc_versions(lockfile: &str) -> String {
+    let mut versions = Vec::new();
+    let mut name = None;
+    for line in lockfile.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"'));
+        } else if let Some(version) = line.strip_prefix("version = ") {
+            if let Some(name) = name.take().filter(|name| name.starts_with("swc_")) {
+                versions.push(format!("{}@{}", name, version.trim_matches('"')));
+            }
+        }
+    }
+    versions.sort();
+    versions.join(",")
+}
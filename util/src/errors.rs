@@ -72,6 +72,9 @@ pub enum UtilError {
         source: std::io::Error,
     },
 
+    #[error("Archive entry '{name}' would be extracted outside the destination")]
+    UnsafeArchiveEntry { name: String },
+
     #[error("Walkdir error: {source}")]
     WalkDir {
         #[source]
@@ -1,13 +1,19 @@
 use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
 
 use swc_common::comments::SingleThreadedComments;
 use swc_common::errors::{ColorConfig, Handler};
 use swc_common::input::SourceFileInput;
 use swc_common::sync::Lrc;
-use swc_common::{FileName, FilePathMapping, Globals, Mark, SourceMap, GLOBALS};
+use swc_common::{BytePos, FileName, FilePathMapping, Globals, Mark, SourceMap, Span, GLOBALS};
 use swc_ecma_ast::*;
-use swc_ecma_codegen::{text_writer::JsWriter, Emitter};
-use swc_ecma_parser::{lexer::Lexer, Parser, Syntax, TsConfig};
+use swc_ecma_codegen::text_writer::{JsWriter, WriteJs};
+use swc_ecma_codegen::Emitter;
+use swc_ecma_parser::error::Error;
+use swc_ecma_parser::lexer::{Lexer, TokenContexts};
+use swc_ecma_parser::token::{Keyword, Token, TokenAndSpan, Word};
+use swc_ecma_parser::{Context, Parser, Syntax, Tokens, TsConfig};
 use swc_ecma_transforms_base::{fixer::fixer, hygiene::hygiene, resolver};
 use swc_ecma_transforms_typescript::strip;
 use swc_ecma_visit::FoldWith;
@@ -20,9 +26,37 @@ pub struct TranspiledFile {
     pub source_map: Vec<u8>,
 }
 
+/// The JavaScript version the parser accepts.
+const ES_VERSION: EsVersion = EsVersion::latest();
+
+fn syntax(path: &Path) -> Syntax {
+    Syntax::Typescript(TsConfig {
+        tsx: path.to_string_lossy().ends_with(".tsx"),
+        ..Default::default()
+    })
+}
+
+fn codegen_config() -> swc_ecma_codegen::Config {
+    Default::default()
+}
+
+/// Identifies the transpiler's output: the swc crate versions it is built with and
+/// the options it passes them, so cached output from a different build is not reused.
+pub fn transpiler_id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(|| {
+        format!(
+            "{}\n{:?}\n{:?}\n{:?}\n{:?}",
+            env!("SWC_VERSIONS"),
+            ES_VERSION,
+            syntax(Path::new("module.ts")),
+            syntax(Path::new("module.tsx")),
+            codegen_config(),
+        )
+    })
+}
+
 pub fn parse_and_gen(module_specifier: &Url) -> Result<TranspiledFile, UtilError> {
-    let cm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
-    let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
     let path = module_specifier
         .to_file_path()
         .map_err(|_| UtilError::InvalidUrl {
@@ -32,21 +66,26 @@ pub fn parse_and_gen(module_specifier: &Url) -> Result<TranspiledFile, UtilError
         path: path.display().to_string(),
         source: e,
     })?;
-    let fm = cm.new_source_file(FileName::Real(path.clone()), source);
+    parse_and_gen_source(&path, source)
+}
+
+/// Transpiles `source`, which was read from `path`. The path names the file in
+/// diagnostics and in the source map.
+pub fn parse_and_gen_source(path: &Path, source: String) -> Result<TranspiledFile, UtilError> {
+    let cm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+    let handler = Handler::with_tty_emitter(ColorConfig::Auto, true, false, Some(cm.clone()));
+    let fm = cm.new_source_file(FileName::Real(path.to_path_buf()), source);
 
     let comments = SingleThreadedComments::default();
 
     let lexer = Lexer::new(
-        Syntax::Typescript(TsConfig {
-            tsx: path.to_string_lossy().ends_with(".tsx"),
-            ..Default::default()
-        }),
-        EsVersion::latest(),
+        syntax(path),
+        ES_VERSION,
         SourceFileInput::from(&*fm),
         Some(&comments),
     );
 
-    let mut parser = Parser::new_from(lexer);
+    let mut parser = Parser::new_from(ImportAttributesTokens::new(lexer));
 
     for e in parser.take_errors() {
         e.into_diagnostic(&handler).emit();
@@ -80,10 +119,15 @@ pub fn parse_and_gen(module_specifier: &Url) -> Result<TranspiledFile, UtilError
         let mut source_map = vec![];
         {
             let mut emitter = Emitter {
-                cfg: Default::default(),
+                cfg: codegen_config(),
                 cm: cm.clone(),
                 comments: None,
-                wr: JsWriter::new(cm.clone(), "\n", &mut code, Some(&mut source_map)),
+                wr: ImportAttributesWriter(JsWriter::new(
+                    cm.clone(),
+                    "\n",
+                    &mut code,
+                    Some(&mut source_map),
+                )),
             };
 
             emitter
@@ -106,7 +150,187 @@ pub fn parse_and_gen(module_specifier: &Url) -> Result<TranspiledFile, UtilError
     })
 }
 
-pub fn parse_and_gen_path(path: &std::path::Path) -> Result<TranspiledFile, UtilError> {
+/// Reads import attributes written with the standard `with` keyword.
+///
+/// This version of the parser only reads the older `import x from "./x.json" assert
+/// { type: "json" }` form, so a `with` right after the source of an import or export
+/// is read as `assert`. Anywhere else, `with` is left alone.
+#[derive(Clone)]
+struct ImportAttributesTokens<I> {
+    tokens: I,
+    // The previous two tokens were `import` or `from`, then a string
+    after_source: bool,
+    after_from: bool,
+}
+
+impl<I: Tokens> ImportAttributesTokens<I> {
+    fn new(tokens: I) -> Self {
+        Self {
+            tokens,
+            after_source: false,
+            after_from: false,
+        }
+    }
+}
+
+impl<I: Tokens> Iterator for ImportAttributesTokens<I> {
+    type Item = TokenAndSpan;
+
+    fn next(&mut self) -> Option<TokenAndSpan> {
+        let mut next = self.tokens.next()?;
+        if self.after_source && next.token == Token::Word(Word::Keyword(Keyword::With)) {
+            next.token = Token::Word(Word::Ident("assert".into()));
+        }
+
+        self.after_source = self.after_from && matches!(next.token, Token::Str { .. });
+        self.after_from = match &next.token {
+            Token::Word(Word::Keyword(Keyword::Import)) => true,
+            Token::Word(Word::Ident(word)) => &**word == "from",
+            _ => false,
+        };
+        Some(next)
+    }
+}
+
+impl<I: Tokens> Tokens for ImportAttributesTokens<I> {
+    fn set_ctx(&mut self, ctx: Context) {
+        self.tokens.set_ctx(ctx)
+    }
+
+    fn ctx(&self) -> Context {
+        self.tokens.ctx()
+    }
+
+    fn syntax(&self) -> Syntax {
+        self.tokens.syntax()
+    }
+
+    fn target(&self) -> EsVersion {
+        self.tokens.target()
+    }
+
+    fn start_pos(&self) -> BytePos {
+        self.tokens.start_pos()
+    }
+
+    fn set_expr_allowed(&mut self, allow: bool) {
+        self.tokens.set_expr_allowed(allow)
+    }
+
+    fn set_next_regexp(&mut self, start: Option<BytePos>) {
+        self.tokens.set_next_regexp(start)
+    }
+
+    fn token_context(&self) -> &TokenContexts {
+        self.tokens.token_context()
+    }
+
+    fn token_context_mut(&mut self) -> &mut TokenContexts {
+        self.tokens.token_context_mut()
+    }
+
+    fn set_token_context(&mut self, c: TokenContexts) {
+        self.tokens.set_token_context(c)
+    }
+
+    fn add_error(&self, error: Error) {
+        self.tokens.add_error(error)
+    }
+
+    fn add_module_mode_error(&self, error: Error) {
+        self.tokens.add_module_mode_error(error)
+    }
+
+    fn take_errors(&mut self) -> Vec<Error> {
+        self.tokens.take_errors()
+    }
+}
+
+/// Writes import attributes with the standard `with` keyword.
+///
+/// The parser reads them as `assert`, and the emitter writes them back the same way,
+/// but V8 only accepts `with`. `assert` is never a keyword anywhere else in emitted
+/// JavaScript.
+struct ImportAttributesWriter<W>(W);
+
+impl<W: WriteJs> WriteJs for ImportAttributesWriter<W> {
+    fn increase_indent(&mut self) -> std::io::Result<()> {
+        self.0.increase_indent()
+    }
+
+    fn decrease_indent(&mut self) -> std::io::Result<()> {
+        self.0.decrease_indent()
+    }
+
+    fn write_semi(&mut self, span: Option<Span>) -> std::io::Result<()> {
+        self.0.write_semi(span)
+    }
+
+    fn write_space(&mut self) -> std::io::Result<()> {
+        self.0.write_space()
+    }
+
+    fn write_keyword(&mut self, span: Option<Span>, s: &'static str) -> std::io::Result<()> {
+        match s {
+            "assert" => self.0.write_keyword(span, "with"),
+            _ => self.0.write_keyword(span, s),
+        }
+    }
+
+    fn write_operator(&mut self, span: Option<Span>, s: &str) -> std::io::Result<()> {
+        self.0.write_operator(span, s)
+    }
+
+    fn write_param(&mut self, s: &str) -> std::io::Result<()> {
+        self.0.write_param(s)
+    }
+
+    fn write_property(&mut self, s: &str) -> std::io::Result<()> {
+        self.0.write_property(s)
+    }
+
+    fn write_line(&mut self) -> std::io::Result<()> {
+        self.0.write_line()
+    }
+
+    fn write_lit(&mut self, span: Span, s: &str) -> std::io::Result<()> {
+        self.0.write_lit(span, s)
+    }
+
+    fn write_comment(&mut self, s: &str) -> std::io::Result<()> {
+        self.0.write_comment(s)
+    }
+
+    fn write_str_lit(&mut self, span: Span, s: &str) -> std::io::Result<()> {
+        self.0.write_str_lit(span, s)
+    }
+
+    fn write_str(&mut self, s: &str) -> std::io::Result<()> {
+        self.0.write_str(s)
+    }
+
+    fn write_symbol(&mut self, span: Span, s: &str) -> std::io::Result<()> {
+        self.0.write_symbol(span, s)
+    }
+
+    fn write_punct(&mut self, span: Option<Span>, s: &'static str) -> std::io::Result<()> {
+        self.0.write_punct(span, s)
+    }
+
+    fn care_about_srcmap(&self) -> bool {
+        self.0.care_about_srcmap()
+    }
+
+    fn add_srcmap(&mut self, pos: BytePos) -> std::io::Result<()> {
+        self.0.add_srcmap(pos)
+    }
+
+    fn commit_pending_semi(&mut self) -> std::io::Result<()> {
+        self.0.commit_pending_semi()
+    }
+}
+
+pub fn parse_and_gen_path(path: &Path) -> Result<TranspiledFile, UtilError> {
     let url = url::Url::from_file_path(path).map_err(|_| UtilError::InvalidFilePath {
         path: path.display().to_string(),
     })?;
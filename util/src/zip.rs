@@ -11,6 +11,11 @@ pub struct ZipOptions {
     pub compression_method: CompressionMethod,
     pub strip_prefix: Option<String>,
     pub apply_prefix: Option<String>,
+    /// Whether symlinks in the source directory are archived as the files they
+    /// point to. When false they are skipped.
+    pub follow_symlinks: bool,
+    /// Files added by name after the directory, with their contents.
+    pub extra_files: Vec<(String, Vec<u8>)>,
 }
 
 impl Default for ZipOptions {
@@ -19,6 +24,8 @@ impl Default for ZipOptions {
             compression_method: CompressionMethod::Deflated,
             strip_prefix: None,
             apply_prefix: None,
+            follow_symlinks: true,
+            extra_files: Vec::new(),
         }
     }
 }
@@ -38,6 +45,9 @@ where
 
     let mut buffer = Vec::new();
     for entry in it {
+        if !zip_options.follow_symlinks && entry.path_is_symlink() {
+            continue;
+        }
         let path = entry.path();
         let mut name: PathBuf = path.to_path_buf();
         if let Some(prefix) = zip_options.strip_prefix.as_ref() {
@@ -71,6 +81,10 @@ where
             zip.add_directory_from_path(name, options)?;
         }
     }
+    for (name, contents) in &zip_options.extra_files {
+        zip.start_file(name, options)?;
+        zip.write_all(contents)?;
+    }
     zip.finish()?;
     Ok(())
 }
@@ -99,3 +113,84 @@ pub fn zip_directory<T1: AsRef<str>, T2: AsRef<str>>(
 
     Ok(())
 }
+
+/// Extracts every entry of the archive into `dst_dir`, returning the entry names.
+///
+/// Entries whose names are absolute or contain `..`, and entries that would be
+/// written through a symlink already present in `dst_dir`, are rejected rather
+/// than skipped, so a partially-trusted archive fails loudly. Each directory on
+/// an entry's path is checked before it is entered or created, so a rejected
+/// entry leaves nothing behind outside `dst_dir`.
+pub fn extract_archive<R: Read + Seek>(
+    reader: R,
+    dst_dir: &Path,
+) -> Result<Vec<String>, UtilError> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let root = dst_dir.canonicalize().map_err(|e| UtilError::FileRead {
+        path: dst_dir.display().to_string(),
+        source: e,
+    })?;
+
+    let mut names = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let relative = entry
+            .enclosed_name()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| UtilError::UnsafeArchiveEntry {
+                name: entry.name().to_string(),
+            })?;
+        let unsafe_entry = || UtilError::UnsafeArchiveEntry {
+            name: entry.name().to_string(),
+        };
+
+        let mut components: Vec<_> = relative.components().collect();
+        let file_name = if entry.is_dir() {
+            None
+        } else {
+            components.pop()
+        };
+        let mut out_path = root.clone();
+        for component in components {
+            out_path.push(component);
+            match std::fs::symlink_metadata(&out_path) {
+                Ok(metadata) if metadata.file_type().is_symlink() => return Err(unsafe_entry()),
+                Ok(metadata) if metadata.is_dir() => {}
+                Ok(_) => return Err(unsafe_entry()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    std::fs::create_dir(&out_path).map_err(|e| UtilError::FileCreate {
+                        path: out_path.display().to_string(),
+                        source: e,
+                    })?;
+                }
+                Err(e) => {
+                    return Err(UtilError::FileRead {
+                        path: out_path.display().to_string(),
+                        source: e,
+                    })
+                }
+            }
+        }
+
+        if let Some(file_name) = file_name {
+            out_path.push(file_name);
+            let is_symlink = std::fs::symlink_metadata(&out_path)
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false);
+            if is_symlink {
+                return Err(unsafe_entry());
+            }
+            let mut out_file = File::create(&out_path).map_err(|e| UtilError::FileCreate {
+                path: out_path.display().to_string(),
+                source: e,
+            })?;
+            std::io::copy(&mut entry, &mut out_file).map_err(|e| UtilError::FileWrite {
+                path: out_path.display().to_string(),
+                source: e,
+            })?;
+        }
+        names.push(relative.to_string_lossy().replace('\\', "/"));
+    }
+
+    Ok(names)
+}